@@ -28,6 +28,10 @@ impl JwtKeys {
 pub struct Claims {
     pub sub: Uuid,
     pub role: String,
+    /// Tenant the token is scoped to. Absent in tokens issued before org scoping,
+    /// which are not accepted for tenant data.
+    #[serde(default)]
+    pub org_id: Option<i32>,
     pub iat: i64,
     pub exp: i64,
     pub jti: String, // unique id to tie refresh tokens to DB records
@@ -49,10 +53,18 @@ pub fn new_jti() -> String {
     base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
 }
 
+/// Random URL-safe code handed out with an invitation; only its hash is stored.
+pub fn new_invitation_code() -> String {
+    let mut bytes = [0u8; 24];
+    thread_rng().fill_bytes(&mut bytes);
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
+
 pub fn sign_access(
     keys: &JwtKeys,
     user_id: Uuid,
     role: &str,
+    org_id: i32,
     ttl_secs: i64,
 ) -> Result<String, AuthError> {
     let iat = now_ts();
@@ -60,6 +72,7 @@ pub fn sign_access(
     let claims = Claims {
         sub: user_id,
         role: role.into(),
+        org_id: Some(org_id),
         iat,
         exp,
         jti: new_jti(),
@@ -72,6 +85,7 @@ pub fn sign_refresh(
     keys: &JwtKeys,
     user_id: Uuid,
     role: &str,
+    org_id: i32,
     ttl_secs: i64,
 ) -> Result<(String, Claims), AuthError> {
     let iat = now_ts();
@@ -79,6 +93,7 @@ pub fn sign_refresh(
     let claims = Claims {
         sub: user_id,
         role: role.into(),
+        org_id: Some(org_id),
         iat,
         exp,
         jti: new_jti(),
@@ -2,18 +2,21 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub mod pagination;
+pub mod patch;
+pub mod secret;
+
+pub use pagination::{ListParams, Page, PageRequest};
+pub use patch::Patch;
+pub use secret::Secret;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 pub enum Role {
     Admin,
+    #[default]
     User,
 }
 
-impl Default for Role {
-    fn default() -> Self {
-        Role::User
-    }
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct User {
     pub id: Uuid,
@@ -69,6 +72,12 @@ pub struct DoctorUserRow {
     pub doctor_roll: Option<i32>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
+    #[serde(skip_serializing)]
     pub password_hash: String,
     pub is_active: bool,
+    pub department: Option<String>,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub deactivated_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub deactivated_by: Option<uuid::Uuid>,
 }
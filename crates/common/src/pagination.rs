@@ -0,0 +1,157 @@
+//! Keyset pagination shared by list endpoints.
+//!
+//! Clients pass `?limit=&sort=&cursor=` plus the common filters, and get back
+//! `{ "items": [...], "next_cursor": "..." }`. The cursor is opaque: it holds
+//! the sort key and id of the last row served, so the next page starts right
+//! after it no matter what was inserted in between.
+
+use crate::AppError;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub const DEFAULT_LIMIT: i64 = 50;
+pub const MAX_LIMIT: i64 = 200;
+
+/// Raw query parameters.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ListParams {
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+    /// Field name, `-` prefix for descending: `sort=-created_at`.
+    pub sort: Option<String>,
+    /// Case-insensitive substring search on the endpoint's text column.
+    pub q: Option<String>,
+    pub created_from: Option<DateTime<Utc>>,
+    pub created_to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sort {
+    pub field: String,
+    pub desc: bool,
+}
+
+/// Position after the last row of a page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cursor {
+    /// Sort the cursor was issued for; it is rejected under any other sort.
+    pub sort: String,
+    pub desc: bool,
+    /// Sort key of the last row, as text (timestamps in RFC 3339).
+    pub value: String,
+    pub id: Uuid,
+}
+
+impl Cursor {
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_vec(self).expect("cursor serializes");
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(json)
+    }
+
+    pub fn decode(s: &str) -> Result<Self, AppError> {
+        let bad = || AppError::BadRequest("invalid cursor".into());
+        let json = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(s)
+            .map_err(|_| bad())?;
+        serde_json::from_slice(&json).map_err(|_| bad())
+    }
+}
+
+/// Validated [`ListParams`].
+#[derive(Debug, Clone)]
+pub struct PageRequest {
+    pub limit: i64,
+    pub sort: Sort,
+    pub after: Option<Cursor>,
+    pub q: Option<String>,
+    pub created_from: Option<DateTime<Utc>>,
+    pub created_to: Option<DateTime<Utc>>,
+}
+
+impl ListParams {
+    /// Checks `sort` against the endpoint's whitelist (`default_sort` applies
+    /// when absent) and that the cursor belongs to the same sort.
+    pub fn validate(&self, sortable: &[&str], default_sort: &str) -> Result<PageRequest, AppError> {
+        let raw = self.sort.as_deref().unwrap_or(default_sort);
+        let (field, desc) = match raw.strip_prefix('-') {
+            Some(field) => (field, true),
+            None => (raw, false),
+        };
+        if !sortable.contains(&field) {
+            return Err(AppError::BadRequest(format!(
+                "sort must be one of {}",
+                sortable.join(", ")
+            )));
+        }
+        let limit = self.limit.unwrap_or(DEFAULT_LIMIT);
+        if !(1..=MAX_LIMIT).contains(&limit) {
+            return Err(AppError::BadRequest(format!(
+                "limit must be between 1 and {MAX_LIMIT}"
+            )));
+        }
+        let after = self.cursor.as_deref().map(Cursor::decode).transpose()?;
+        if let Some(c) = &after
+            && (c.sort != field || c.desc != desc)
+        {
+            return Err(AppError::BadRequest(
+                "cursor was issued for a different sort".into(),
+            ));
+        }
+        Ok(PageRequest {
+            limit,
+            sort: Sort {
+                field: field.to_string(),
+                desc,
+            },
+            after,
+            q: self
+                .q
+                .as_deref()
+                .map(str::trim)
+                .filter(|q| !q.is_empty())
+                .map(str::to_string),
+            created_from: self.created_from,
+            created_to: self.created_to,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+impl<T> Page<T> {
+    /// `rows` is fetched with `LIMIT limit + 1`; the extra row only signals
+    /// that another page exists. `key` gives a row's sort value and id.
+    pub fn from_rows(
+        mut rows: Vec<T>,
+        req: &PageRequest,
+        key: impl Fn(&T) -> (String, Uuid),
+    ) -> Self {
+        let more = rows.len() as i64 > req.limit;
+        rows.truncate(req.limit as usize);
+        let next_cursor = match rows.last() {
+            Some(last) if more => {
+                let (value, id) = key(last);
+                Some(
+                    Cursor {
+                        sort: req.sort.field.clone(),
+                        desc: req.sort.desc,
+                        value,
+                        id,
+                    }
+                    .encode(),
+                )
+            }
+            _ => None,
+        };
+        Page {
+            items: rows,
+            next_cursor,
+        }
+    }
+}
@@ -0,0 +1,89 @@
+//! Tri-state fields for RFC 7396 JSON merge patch: a member is either absent
+//! (leave unchanged), `null` (clear) or a value (set). Patch bodies put
+//! `#[serde(default)]` on the struct so absent members become [`Patch::Unchanged`].
+
+use crate::AppError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Cow;
+use std::ops::Deref;
+use validator::{ValidateEmail, ValidateLength};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Patch<T> {
+    #[default]
+    Unchanged,
+    Clear,
+    Set(T),
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Patch<T> {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        Ok(match Option::<T>::deserialize(d)? {
+            Some(v) => Patch::Set(v),
+            None => Patch::Clear,
+        })
+    }
+}
+
+impl<T: Serialize> Serialize for Patch<T> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Patch::Set(v) => v.serialize(s),
+            _ => s.serialize_none(),
+        }
+    }
+}
+
+impl<T> Patch<T> {
+    pub fn is_unchanged(&self) -> bool {
+        matches!(self, Patch::Unchanged)
+    }
+
+    pub fn as_deref(&self) -> Patch<&T::Target>
+    where
+        T: Deref,
+    {
+        match self {
+            Patch::Unchanged => Patch::Unchanged,
+            Patch::Clear => Patch::Clear,
+            Patch::Set(v) => Patch::Set(v.deref()),
+        }
+    }
+
+    /// For nullable columns: `None` leaves the column alone, `Some(None)` sets NULL.
+    pub fn into_update(self) -> Option<Option<T>> {
+        match self {
+            Patch::Unchanged => None,
+            Patch::Clear => Some(None),
+            Patch::Set(v) => Some(Some(v)),
+        }
+    }
+
+    /// For NOT NULL columns, where `null` is rejected.
+    pub fn required(self, field: &str) -> Result<Option<T>, AppError> {
+        match self {
+            Patch::Unchanged => Ok(None),
+            Patch::Clear => Err(AppError::BadRequest(format!("{field} cannot be null"))),
+            Patch::Set(v) => Ok(Some(v)),
+        }
+    }
+}
+
+// Validation rules only apply to a value being set; absent and `null` pass.
+impl<T: ValidateLength<u64>> ValidateLength<u64> for Patch<T> {
+    fn length(&self) -> Option<u64> {
+        match self {
+            Patch::Set(v) => v.length(),
+            _ => None,
+        }
+    }
+}
+
+impl<T: ValidateEmail> ValidateEmail for Patch<T> {
+    fn as_email_string(&self) -> Option<Cow<'_, str>> {
+        match self {
+            Patch::Set(v) => v.as_email_string(),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,57 @@
+//! Passwords, tokens and keys that must never reach logs or error bodies:
+//! `Debug`, `Display` and `Serialize` all print a placeholder, and the value
+//! is only reachable through [`Secret::expose`].
+
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt;
+use validator::ValidateLength;
+
+const REDACTED: &str = "[REDACTED]";
+
+#[derive(Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct Secret<T = String>(T);
+
+impl<T> Secret<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T> fmt::Display for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T> Serialize for Secret<T> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(REDACTED)
+    }
+}
+
+impl<T: ValidateLength<u64>> ValidateLength<u64> for Secret<T> {
+    fn length(&self) -> Option<u64> {
+        self.0.length()
+    }
+}
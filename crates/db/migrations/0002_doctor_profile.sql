@@ -0,0 +1,9 @@
+-- ================================================
+--  🧑‍⚕️ Doctor profile: department + contact
+-- ================================================
+ALTER TABLE public.doctor_user
+    ADD COLUMN IF NOT EXISTS department TEXT,
+    ADD COLUMN IF NOT EXISTS phone TEXT,
+    ADD COLUMN IF NOT EXISTS email TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_doctor_user_department ON public.doctor_user (org_id, department);
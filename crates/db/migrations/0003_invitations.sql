@@ -0,0 +1,19 @@
+-- ================================================
+--  ✉️ Invitations (admin-issued, single-use)
+-- ================================================
+CREATE TABLE IF NOT EXISTS public.invitations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    code_hash TEXT UNIQUE NOT NULL,
+    org_id INT4 NOT NULL,
+    org_name TEXT,
+    doctor_roll INT4 REFERENCES public.doctor_rolls(roll_id),
+    department TEXT,
+    created_by UUID NOT NULL REFERENCES public.doctor_user(id),
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ,
+    used_by UUID REFERENCES public.doctor_user(id),
+    revoked BOOLEAN NOT NULL DEFAULT false,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_invitations_org_id ON public.invitations (org_id);
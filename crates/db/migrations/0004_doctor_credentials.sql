@@ -0,0 +1,18 @@
+-- ================================================
+--  📜 Doctor credentials (licenses, specialties, certifications)
+-- ================================================
+CREATE TABLE IF NOT EXISTS public.doctor_credentials (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    doctor_id UUID NOT NULL REFERENCES public.doctor_user(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL CHECK (kind IN ('license', 'specialty', 'certification')),
+    title TEXT NOT NULL,
+    number TEXT,
+    issuer TEXT,
+    issued_on DATE,
+    expires_on DATE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_doctor_credentials_doctor_id ON public.doctor_credentials (doctor_id);
+CREATE INDEX IF NOT EXISTS idx_doctor_credentials_expires_on ON public.doctor_credentials (expires_on);
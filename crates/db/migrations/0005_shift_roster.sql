@@ -0,0 +1,31 @@
+-- ================================================
+--  🗓️ Shift definitions, rosters and on-call
+-- ================================================
+CREATE TABLE IF NOT EXISTS public.shift_definitions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    org_id INT4 NOT NULL,
+    name TEXT NOT NULL,
+    start_time TIME NOT NULL,
+    duration_minutes INT4 NOT NULL CHECK (duration_minutes > 0),
+    timezone TEXT NOT NULL DEFAULT 'Asia/Ulaanbaatar',
+    on_call BOOLEAN NOT NULL DEFAULT false,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (org_id, name)
+);
+
+CREATE TABLE IF NOT EXISTS public.shift_assignments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    doctor_id UUID NOT NULL REFERENCES public.doctor_user(id) ON DELETE CASCADE,
+    org_id INT4 NOT NULL,
+    department TEXT NOT NULL,
+    shift_definition_id UUID REFERENCES public.shift_definitions(id) ON DELETE SET NULL,
+    starts_at TIMESTAMPTZ NOT NULL,
+    ends_at TIMESTAMPTZ NOT NULL,
+    on_call BOOLEAN NOT NULL DEFAULT false,
+    created_by UUID REFERENCES public.doctor_user(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (ends_at > starts_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_shift_assignments_doctor ON public.shift_assignments (doctor_id, starts_at);
+CREATE INDEX IF NOT EXISTS idx_shift_assignments_roster ON public.shift_assignments (org_id, lower(department), starts_at);
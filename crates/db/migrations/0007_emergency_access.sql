@@ -0,0 +1,36 @@
+-- ================================================
+--  🚨 Break-the-glass emergency access
+-- ================================================
+INSERT INTO public.doctor_rolls (roll_name)
+VALUES ('privacy_officer')
+ON CONFLICT DO NOTHING;
+
+CREATE TABLE IF NOT EXISTS public.emergency_access (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    doctor_id UUID NOT NULL REFERENCES public.doctor_user(id),
+    org_id INT4 NOT NULL,
+    resource_type TEXT,
+    resource_id TEXT,
+    justification TEXT NOT NULL,
+    granted_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ,
+    CHECK (expires_at > granted_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_emergency_access_doctor ON public.emergency_access (doctor_id, expires_at);
+CREATE INDEX IF NOT EXISTS idx_emergency_access_org ON public.emergency_access (org_id, granted_at);
+
+-- ================================================
+--  🔔 Notifications
+-- ================================================
+CREATE TABLE IF NOT EXISTS public.notifications (
+    id BIGSERIAL PRIMARY KEY,
+    recipient_id UUID NOT NULL REFERENCES public.doctor_user(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    payload JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    read_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_notifications_recipient ON public.notifications (recipient_id, created_at);
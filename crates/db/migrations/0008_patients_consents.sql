@@ -0,0 +1,43 @@
+-- ================================================
+--  🧑‍🦽 Patients
+-- ================================================
+CREATE TABLE IF NOT EXISTS public.patients (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    org_id INT4 NOT NULL,
+    first_name TEXT NOT NULL,
+    last_name TEXT NOT NULL,
+    national_id TEXT,
+    birth_date DATE,
+    gender TEXT,
+    phone TEXT,
+    created_by UUID REFERENCES public.doctor_user(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_patients_org_id ON public.patients (org_id);
+
+-- ================================================
+--  ✍️ Patient consents (versioned, withdrawable)
+-- ================================================
+CREATE TABLE IF NOT EXISTS public.patient_consents (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    patient_id UUID NOT NULL REFERENCES public.patients(id),
+    kind TEXT NOT NULL CHECK (kind IN ('treatment', 'data_sharing', 'research')),
+    -- data_sharing: organization allowed to receive records, NULL = any
+    scope_org_id INT4,
+    -- increments per (patient, kind, scope); the newest one supersedes the rest
+    version INT4 NOT NULL,
+    -- version of the consent form text the patient signed
+    form_version TEXT NOT NULL,
+    signed_by TEXT NOT NULL,
+    signature TEXT NOT NULL,
+    signed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ,
+    recorded_by UUID REFERENCES public.doctor_user(id),
+    withdrawn_at TIMESTAMPTZ,
+    withdrawn_reason TEXT
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS uq_patient_consents_version
+    ON public.patient_consents (patient_id, kind, COALESCE(scope_org_id, -1), version);
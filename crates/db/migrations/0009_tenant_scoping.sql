@@ -0,0 +1,27 @@
+-- ================================================
+--  🏥 Tenant scoping: items belong to an organization
+-- ================================================
+ALTER TABLE public.items ADD COLUMN IF NOT EXISTS org_id INT4;
+
+UPDATE public.items i
+SET org_id = d.org_id
+FROM public.doctor_user d
+WHERE d.id = i.owner_id AND i.org_id IS NULL;
+
+ALTER TABLE public.items ALTER COLUMN org_id SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_items_org_id ON public.items (org_id);
+
+-- ================================================
+--  🔒 Row-level security
+--  TenantDb sets `app.org_id` per transaction. Rows are invisible when it is
+--  unset. Superusers and BYPASSRLS roles are unaffected, so run the API as an
+--  ordinary role to get this second line of defence.
+-- ================================================
+ALTER TABLE public.items ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.items FORCE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS items_tenant_isolation ON public.items;
+CREATE POLICY items_tenant_isolation ON public.items
+    USING (org_id = NULLIF(current_setting('app.org_id', true), '')::int4)
+    WITH CHECK (org_id = NULLIF(current_setting('app.org_id', true), '')::int4);
@@ -0,0 +1,6 @@
+-- ================================================
+--  🩺 Department head: may modify items owned by doctors of their department
+-- ================================================
+INSERT INTO public.doctor_rolls (roll_name)
+VALUES ('department_head')
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,20 @@
+-- ================================================
+--  📎 Patient documents (bytes live in blob storage)
+-- ================================================
+CREATE TABLE IF NOT EXISTS public.patient_documents (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    patient_id UUID NOT NULL REFERENCES public.patients(id),
+    org_id INT4 NOT NULL,
+    kind TEXT NOT NULL DEFAULT 'other'
+        CHECK (kind IN ('referral', 'consent_form', 'image', 'lab_report', 'other')),
+    file_name TEXT NOT NULL,
+    -- sniffed from the content on upload
+    content_type TEXT NOT NULL,
+    size_bytes INT8 NOT NULL,
+    sha256 TEXT NOT NULL,
+    storage_key TEXT NOT NULL UNIQUE,
+    uploaded_by UUID REFERENCES public.doctor_user(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_patient_documents_patient ON public.patient_documents (patient_id);
@@ -0,0 +1,18 @@
+-- ================================================
+--  🔐 Envelope encryption for sensitive patient fields
+--  dek_wrapped: per-row data key, wrapped by master key `dek_version`.
+--  *_enc: nonce || AES-256-GCM ciphertext under that data key.
+--  national_id_bidx: HMAC blind index for equality lookups.
+--  The plaintext national_id column is legacy; the `reencrypt` tool moves
+--  remaining values into national_id_enc and clears it.
+-- ================================================
+ALTER TABLE public.patients
+    ADD COLUMN IF NOT EXISTS dek_wrapped BYTEA,
+    ADD COLUMN IF NOT EXISTS dek_version INT4,
+    ADD COLUMN IF NOT EXISTS national_id_enc BYTEA,
+    ADD COLUMN IF NOT EXISTS national_id_bidx TEXT,
+    ADD COLUMN IF NOT EXISTS diagnoses_enc BYTEA,
+    ADD COLUMN IF NOT EXISTS notes_enc BYTEA;
+
+CREATE INDEX IF NOT EXISTS idx_patients_national_id_bidx
+    ON public.patients (org_id, national_id_bidx);
@@ -0,0 +1,27 @@
+-- ================================================
+--  🔎 Full-text search
+--  'simple' config: lowercases and splits on word boundaries without
+--  language-specific stemming, so Mongolian Cyrillic is indexed as written.
+--  Needs a UTF-8 LC_CTYPE (e.g. C.UTF-8, mn_MN.UTF-8); under plain C the
+--  parser drops Cyrillic letters entirely.
+-- ================================================
+ALTER TABLE public.items
+    ADD COLUMN IF NOT EXISTS search_tsv tsvector GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', coalesce(title, '')), 'A') ||
+        setweight(to_tsvector('simple', coalesce(description, '')), 'B')
+    ) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_items_search_tsv ON public.items USING GIN (search_tsv);
+
+-- Search snippets are HTML (<mark> highlights); escape the source text first.
+CREATE OR REPLACE FUNCTION public.html_escape(t TEXT) RETURNS TEXT
+    LANGUAGE sql IMMUTABLE STRICT
+    AS $$ SELECT replace(replace(replace(t, '&', '&amp;'), '<', '&lt;'), '>', '&gt;') $$;
+
+DO $$
+BEGIN
+    IF to_tsvector('simple', 'эмнэлэг') = ''::tsvector THEN
+        RAISE WARNING 'database LC_CTYPE % does not tokenize Cyrillic; full-text search will miss Mongolian text',
+            current_setting('lc_ctype');
+    END IF;
+END $$;
@@ -0,0 +1,46 @@
+-- ================================================
+--  🗑️ Soft delete: clinical rows are marked, never removed
+-- ================================================
+ALTER TABLE public.items
+    ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS deleted_by UUID REFERENCES public.doctor_user(id);
+
+ALTER TABLE public.patient_documents
+    ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS deleted_by UUID REFERENCES public.doctor_user(id);
+
+-- Default listings only ever see live rows.
+CREATE INDEX IF NOT EXISTS idx_items_live ON public.items (org_id, created_at)
+    WHERE deleted_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_patient_documents_live ON public.patient_documents (patient_id)
+    WHERE deleted_at IS NULL;
+
+-- Hard deletes are refused outright.
+CREATE OR REPLACE FUNCTION public.forbid_hard_delete() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION '% rows are soft-deleted; set deleted_at instead', TG_TABLE_NAME;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS items_no_hard_delete ON public.items;
+CREATE TRIGGER items_no_hard_delete
+    BEFORE DELETE ON public.items
+    FOR EACH ROW EXECUTE FUNCTION public.forbid_hard_delete();
+
+DROP TRIGGER IF EXISTS patient_documents_no_hard_delete ON public.patient_documents;
+CREATE TRIGGER patient_documents_no_hard_delete
+    BEFORE DELETE ON public.patient_documents
+    FOR EACH ROW EXECUTE FUNCTION public.forbid_hard_delete();
+
+-- ================================================
+--  🧑‍⚕️ Doctors are deactivated, not deleted
+-- ================================================
+ALTER TABLE public.doctor_user
+    ADD COLUMN IF NOT EXISTS deactivated_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS deactivated_by UUID REFERENCES public.doctor_user(id);
+
+-- Removing a doctor row must not take their items with it.
+ALTER TABLE public.items DROP CONSTRAINT IF EXISTS items_owner_id_fkey;
+ALTER TABLE public.items
+    ADD CONSTRAINT items_owner_id_fkey
+    FOREIGN KEY (owner_id) REFERENCES public.doctor_user(id) ON DELETE RESTRICT;
@@ -0,0 +1,19 @@
+-- ================================================
+--  🔢 Row versions for optimistic concurrency (ETag / If-Match)
+-- ================================================
+-- Any table with a `version` column can attach this trigger; every UPDATE
+-- bumps the version, so writers cannot forget to.
+CREATE OR REPLACE FUNCTION public.bump_row_version() RETURNS trigger AS $$
+BEGIN
+    NEW.version := OLD.version + 1;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+ALTER TABLE public.items
+    ADD COLUMN IF NOT EXISTS version INT8 NOT NULL DEFAULT 1;
+
+DROP TRIGGER IF EXISTS items_bump_version ON public.items;
+CREATE TRIGGER items_bump_version
+    BEFORE UPDATE ON public.items
+    FOR EACH ROW EXECUTE FUNCTION public.bump_row_version();
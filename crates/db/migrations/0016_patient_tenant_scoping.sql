@@ -0,0 +1,66 @@
+-- ================================================
+--  🗂️ Patient directory: which organization owns a patient
+--  Carries no patient data, so it stays outside row-level security. Access
+--  checks read it to find the owning org, then read the patient through a
+--  transaction scoped to that org.
+-- ================================================
+CREATE TABLE IF NOT EXISTS public.patient_orgs (
+    patient_id UUID PRIMARY KEY REFERENCES public.patients(id),
+    org_id INT4 NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_patient_orgs_org_id ON public.patient_orgs (org_id);
+
+INSERT INTO public.patient_orgs (patient_id, org_id)
+SELECT id, org_id FROM public.patients
+ON CONFLICT (patient_id) DO NOTHING;
+
+CREATE OR REPLACE FUNCTION public.patient_orgs_sync() RETURNS trigger AS $$
+BEGIN
+    INSERT INTO public.patient_orgs (patient_id, org_id)
+    VALUES (NEW.id, NEW.org_id)
+    ON CONFLICT (patient_id) DO UPDATE SET org_id = EXCLUDED.org_id;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS patients_sync_org ON public.patients;
+CREATE TRIGGER patients_sync_org
+    AFTER INSERT OR UPDATE OF org_id ON public.patients
+    FOR EACH ROW EXECUTE FUNCTION public.patient_orgs_sync();
+
+-- ================================================
+--  🔒 Row-level security for patient data, as for items (0009)
+-- ================================================
+ALTER TABLE public.patients ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.patients FORCE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS patients_tenant_isolation ON public.patients;
+CREATE POLICY patients_tenant_isolation ON public.patients
+    USING (org_id = NULLIF(current_setting('app.org_id', true), '')::int4)
+    WITH CHECK (org_id = NULLIF(current_setting('app.org_id', true), '')::int4);
+
+ALTER TABLE public.patient_documents ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.patient_documents FORCE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS patient_documents_tenant_isolation ON public.patient_documents;
+CREATE POLICY patient_documents_tenant_isolation ON public.patient_documents
+    USING (org_id = NULLIF(current_setting('app.org_id', true), '')::int4)
+    WITH CHECK (org_id = NULLIF(current_setting('app.org_id', true), '')::int4);
+
+-- Consents have no org column; they follow their patient.
+ALTER TABLE public.patient_consents ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.patient_consents FORCE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS patient_consents_tenant_isolation ON public.patient_consents;
+CREATE POLICY patient_consents_tenant_isolation ON public.patient_consents
+    USING (EXISTS (
+        SELECT 1 FROM public.patient_orgs o
+        WHERE o.patient_id = patient_consents.patient_id
+          AND o.org_id = NULLIF(current_setting('app.org_id', true), '')::int4
+    ))
+    WITH CHECK (EXISTS (
+        SELECT 1 FROM public.patient_orgs o
+        WHERE o.patient_id = patient_consents.patient_id
+          AND o.org_id = NULLIF(current_setting('app.org_id', true), '')::int4
+    ));
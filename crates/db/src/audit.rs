@@ -0,0 +1,302 @@
+//! Append-only audit trail. Every row carries the SHA-256 of its content chained
+//! with the previous row's hash, so editing or removing a row breaks the chain
+//! from that point on (see [`verify_chain`]).
+
+use crate::{Db, DbError};
+use chrono::{DateTime, SubsecRound, Utc};
+use common::{Page, PageRequest};
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+/// `prev_hash` of the very first row.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Arbitrary constant used to serialize writers of the chain.
+const AUDIT_LOCK_KEY: i64 = 710_030;
+
+/// Who performed an action and from where; built per request by the API layer.
+#[derive(Debug, Clone, Default)]
+pub struct AuditContext {
+    pub actor_id: Option<Uuid>,
+    pub ip: Option<String>,
+    pub request_id: Option<String>,
+}
+
+/// What happened.
+#[derive(Debug, Clone)]
+pub struct AuditEvent<'a> {
+    /// e.g. `read`, `list`, `create`, `update`, `delete`, `restore`, `http.post`.
+    pub action: &'a str,
+    pub resource_type: &'a str,
+    pub resource_id: Option<String>,
+    /// Before/after snapshot or other structured detail.
+    pub diff: Option<Value>,
+}
+
+impl<'a> AuditEvent<'a> {
+    pub fn new(action: &'a str, resource_type: &'a str, resource_id: impl ToString) -> Self {
+        Self {
+            action,
+            resource_type,
+            resource_id: Some(resource_id.to_string()),
+            diff: None,
+        }
+    }
+
+    pub fn with_diff(
+        mut self,
+        before: Option<impl Serialize>,
+        after: Option<impl Serialize>,
+    ) -> Self {
+        self.diff = Some(serde_json::json!({
+            "before": before.and_then(|b| serde_json::to_value(b).ok()),
+            "after": after.and_then(|a| serde_json::to_value(a).ok()),
+        }));
+        self
+    }
+}
+
+#[derive(sqlx::FromRow, Debug, Clone, Serialize)]
+pub struct AuditRow {
+    pub id: i64,
+    pub occurred_at: DateTime<Utc>,
+    pub actor_id: Option<Uuid>,
+    pub action: String,
+    pub resource_type: String,
+    pub resource_id: Option<String>,
+    pub diff: Option<Value>,
+    pub ip: Option<String>,
+    pub request_id: Option<String>,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+/// Records an event in its own transaction. Reads use this after their own
+/// transaction has committed, so they hold the chain lock only for the append.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn record(db: &Db, ctx: &AuditContext, ev: AuditEvent<'_>) -> Result<(), DbError> {
+    let mut tx = db.0.begin().await?;
+    record_in(&mut tx, ctx, ev).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Records an event inside the caller's transaction, so the audit row commits
+/// (or rolls back) together with the change it describes. The chain lock is
+/// held until that transaction ends; call this as its last statement.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn record_in(
+    conn: &mut PgConnection,
+    ctx: &AuditContext,
+    ev: AuditEvent<'_>,
+) -> Result<(), DbError> {
+    sqlx::query("SELECT pg_advisory_xact_lock($1)")
+        .bind(AUDIT_LOCK_KEY)
+        .execute(&mut *conn)
+        .await?;
+
+    let prev_hash =
+        sqlx::query_scalar::<_, String>("SELECT hash FROM audit_log ORDER BY id DESC LIMIT 1")
+            .fetch_optional(&mut *conn)
+            .await?
+            .unwrap_or_else(|| GENESIS_HASH.to_string());
+
+    // Postgres keeps microseconds; truncate first so the hash survives a round trip.
+    let occurred_at = Utc::now().trunc_subsecs(6);
+    let hash = chain_hash(
+        &prev_hash,
+        occurred_at,
+        ctx.actor_id,
+        ev.action,
+        ev.resource_type,
+        ev.resource_id.as_deref(),
+        ev.diff.as_ref(),
+        ctx.ip.as_deref(),
+        ctx.request_id.as_deref(),
+    );
+
+    sqlx::query(
+        r#"INSERT INTO audit_log
+               (occurred_at, actor_id, action, resource_type, resource_id, diff, ip, request_id, prev_hash, hash)
+           VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10)"#,
+    )
+    .bind(occurred_at)
+    .bind(ctx.actor_id)
+    .bind(ev.action)
+    .bind(ev.resource_type)
+    .bind(ev.resource_id.as_deref())
+    .bind(ev.diff.as_ref())
+    .bind(ctx.ip.as_deref())
+    .bind(ctx.request_id.as_deref())
+    .bind(&prev_hash)
+    .bind(&hash)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn chain_hash(
+    prev_hash: &str,
+    occurred_at: DateTime<Utc>,
+    actor_id: Option<Uuid>,
+    action: &str,
+    resource_type: &str,
+    resource_id: Option<&str>,
+    diff: Option<&Value>,
+    ip: Option<&str>,
+    request_id: Option<&str>,
+) -> String {
+    let mut h = Sha256::new();
+    for part in [
+        prev_hash,
+        &occurred_at.format("%Y-%m-%dT%H:%M:%S%.6fZ").to_string(),
+        &actor_id.map(|a| a.to_string()).unwrap_or_default(),
+        action,
+        resource_type,
+        resource_id.unwrap_or_default(),
+        &diff.map(canonical_json).unwrap_or_default(),
+        ip.unwrap_or_default(),
+        request_id.unwrap_or_default(),
+    ] {
+        h.update(part.as_bytes());
+        h.update([0x1f]);
+    }
+    hex::encode(h.finalize())
+}
+
+/// JSON with object keys sorted recursively; JSONB does not preserve key order.
+fn canonical_json(v: &Value) -> String {
+    match v {
+        Value::Object(map) => {
+            let mut keys: Vec<_> = map.keys().collect();
+            keys.sort();
+            let fields: Vec<String> = keys
+                .into_iter()
+                .map(|k| format!("{}:{}", Value::String(k.clone()), canonical_json(&map[k])))
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(canonical_json).collect();
+            format!("[{}]", items.join(","))
+        }
+        other => other.to_string(),
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct AuditFilter<'a> {
+    pub actor_id: Option<Uuid>,
+    pub action: Option<&'a str>,
+    pub resource_type: Option<&'a str>,
+    pub resource_id: Option<&'a str>,
+}
+
+/// Rows are only ever appended, so `id` is also the order they happened in.
+pub const AUDIT_SORTS: [&str; 1] = ["id"];
+
+/// `page.created_from` / `created_to` bound `occurred_at`. Cursors carry the
+/// row id as their value (callers check it parses); ids are unique, so the
+/// cursor's tie-break id is unused.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn search(
+    db: &Db,
+    f: &AuditFilter<'_>,
+    page: &PageRequest,
+) -> Result<Page<AuditRow>, DbError> {
+    let after = page
+        .after
+        .as_ref()
+        .and_then(|c| c.value.parse::<i64>().ok());
+    let (dir, cmp) = if page.sort.desc {
+        ("DESC", "<")
+    } else {
+        ("ASC", ">")
+    };
+    let rows = sqlx::query_as::<_, AuditRow>(&format!(
+        r#"SELECT * FROM audit_log
+           WHERE ($1::uuid IS NULL OR actor_id = $1)
+             AND ($2::text IS NULL OR action = $2)
+             AND ($3::text IS NULL OR resource_type = $3)
+             AND ($4::text IS NULL OR resource_id = $4)
+             AND ($5::timestamptz IS NULL OR occurred_at >= $5)
+             AND ($6::timestamptz IS NULL OR occurred_at < $6)
+             AND ($7::int8 IS NULL OR id {cmp} $7)
+           ORDER BY id {dir}
+           LIMIT $8"#
+    ))
+    .bind(f.actor_id)
+    .bind(f.action)
+    .bind(f.resource_type)
+    .bind(f.resource_id)
+    .bind(page.created_from)
+    .bind(page.created_to)
+    .bind(after)
+    .bind(page.limit + 1)
+    .fetch_all(&db.0)
+    .await?;
+    Ok(Page::from_rows(rows, page, |r| {
+        (r.id.to_string(), Uuid::nil())
+    }))
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainReport {
+    pub checked: u64,
+    pub valid: bool,
+    /// First row whose `prev_hash` or `hash` does not match.
+    pub broken_at: Option<i64>,
+}
+
+/// Recomputes the whole chain from the genesis row.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn verify_chain(db: &Db) -> Result<ChainReport, DbError> {
+    const BATCH: i64 = 1000;
+    let mut prev = GENESIS_HASH.to_string();
+    let mut last_id = 0i64;
+    let mut checked = 0u64;
+    loop {
+        let rows = sqlx::query_as::<_, AuditRow>(
+            "SELECT * FROM audit_log WHERE id > $1 ORDER BY id LIMIT $2",
+        )
+        .bind(last_id)
+        .bind(BATCH)
+        .fetch_all(&db.0)
+        .await?;
+        if rows.is_empty() {
+            break;
+        }
+        for r in &rows {
+            let expected = chain_hash(
+                &prev,
+                r.occurred_at,
+                r.actor_id,
+                &r.action,
+                &r.resource_type,
+                r.resource_id.as_deref(),
+                r.diff.as_ref(),
+                r.ip.as_deref(),
+                r.request_id.as_deref(),
+            );
+            if r.prev_hash != prev || r.hash != expected {
+                return Ok(ChainReport {
+                    checked,
+                    valid: false,
+                    broken_at: Some(r.id),
+                });
+            }
+            checked += 1;
+            prev = r.hash.clone();
+            last_id = r.id;
+        }
+    }
+    Ok(ChainReport {
+        checked,
+        valid: true,
+        broken_at: None,
+    })
+}
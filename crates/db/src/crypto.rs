@@ -0,0 +1,174 @@
+//! Envelope encryption for sensitive patient fields.
+//!
+//! Every row gets its own data key (DEK). Fields are sealed with AES-256-GCM
+//! under the DEK, and the DEK is stored wrapped by a versioned master key. To
+//! rotate the master key, add a new version, make it current, and run the
+//! `reencrypt` tool: it rewraps the DEKs without touching field ciphertexts.
+//!
+//! Lookups by national ID go through a blind index: a keyed HMAC of the
+//! normalized value, so equality search works without decrypting. The blind
+//! index key is not versioned; changing it means recomputing every index.
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::fmt;
+
+const NONCE_LEN: usize = 12;
+
+#[derive(thiserror::Error, Debug)]
+pub enum CryptoError {
+    #[error("invalid key material: {0}")]
+    InvalidKey(String),
+    #[error("unknown master key version {0}")]
+    UnknownVersion(i32),
+    #[error("decryption failed")]
+    Decrypt,
+}
+
+/// Master keys by version plus the blind index key. Loaded from `Settings`.
+#[derive(Clone)]
+pub struct KeyRing {
+    current: i32,
+    masters: HashMap<i32, Key<Aes256Gcm>>,
+    blind: [u8; 32],
+}
+
+impl fmt::Debug for KeyRing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut versions: Vec<_> = self.masters.keys().collect();
+        versions.sort();
+        f.debug_struct("KeyRing")
+            .field("current", &self.current)
+            .field("versions", &versions)
+            .finish_non_exhaustive()
+    }
+}
+
+impl KeyRing {
+    pub fn new(
+        current: i32,
+        masters: impl IntoIterator<Item = (i32, [u8; 32])>,
+        blind: [u8; 32],
+    ) -> Result<Self, CryptoError> {
+        let masters: HashMap<_, _> = masters
+            .into_iter()
+            .map(|(v, k)| (v, Key::<Aes256Gcm>::from(k)))
+            .collect();
+        if !masters.contains_key(&current) {
+            return Err(CryptoError::UnknownVersion(current));
+        }
+        Ok(Self {
+            current,
+            masters,
+            blind,
+        })
+    }
+
+    /// `masters` is `version:base64key[,version:base64key...]`; every key is
+    /// 32 bytes, standard base64.
+    pub fn parse(masters: &str, current: i32, blind: &str) -> Result<Self, CryptoError> {
+        let mut parsed = Vec::new();
+        for entry in masters.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (version, key) = entry.split_once(':').ok_or_else(|| {
+                CryptoError::InvalidKey(format!("expected version:key in {entry:?}"))
+            })?;
+            let version = version
+                .parse::<i32>()
+                .map_err(|_| CryptoError::InvalidKey(format!("bad version {version:?}")))?;
+            parsed.push((version, decode_key(key)?));
+        }
+        Self::new(current, parsed, decode_key(blind)?)
+    }
+
+    pub fn current_version(&self) -> i32 {
+        self.current
+    }
+
+    /// Fresh DEK for a new row, with its wrapped form under the current master key.
+    pub fn new_data_key(&self, aad: &str) -> Result<(DataKey, Vec<u8>), CryptoError> {
+        let dek = DataKey(Aes256Gcm::generate_key(OsRng));
+        let wrapped = self.wrap(&dek, aad)?;
+        Ok((dek, wrapped))
+    }
+
+    /// Wraps `dek` under the current master key.
+    pub fn wrap(&self, dek: &DataKey, aad: &str) -> Result<Vec<u8>, CryptoError> {
+        seal(
+            &self.masters[&self.current],
+            dek.0.as_slice(),
+            aad.as_bytes(),
+        )
+    }
+
+    pub fn unwrap(&self, version: i32, wrapped: &[u8], aad: &str) -> Result<DataKey, CryptoError> {
+        let master = self
+            .masters
+            .get(&version)
+            .ok_or(CryptoError::UnknownVersion(version))?;
+        let raw = open(master, wrapped, aad.as_bytes())?;
+        let key: [u8; 32] = raw.try_into().map_err(|_| CryptoError::Decrypt)?;
+        Ok(DataKey(key.into()))
+    }
+
+    /// Keyed HMAC of the normalized value, hex encoded. `purpose` separates
+    /// indexes of different fields.
+    pub fn blind_index(&self, purpose: &str, value: &str) -> String {
+        let normalized: String = value
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '-')
+            .flat_map(char::to_uppercase)
+            .collect();
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&self.blind).expect("any key length");
+        mac.update(purpose.as_bytes());
+        mac.update(&[0x1f]);
+        mac.update(normalized.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+}
+
+/// Per-row data key.
+pub struct DataKey(Key<Aes256Gcm>);
+
+impl DataKey {
+    /// `aad` ties the ciphertext to its row and column, so it can't be moved.
+    pub fn seal(&self, plaintext: &str, aad: &str) -> Result<Vec<u8>, CryptoError> {
+        seal(&self.0, plaintext.as_bytes(), aad.as_bytes())
+    }
+
+    pub fn open(&self, sealed: &[u8], aad: &str) -> Result<String, CryptoError> {
+        String::from_utf8(open(&self.0, sealed, aad.as_bytes())?).map_err(|_| CryptoError::Decrypt)
+    }
+}
+
+fn decode_key(b64: &str) -> Result<[u8; 32], CryptoError> {
+    base64::engine::general_purpose::STANDARD
+        .decode(b64.trim())
+        .map_err(|e| CryptoError::InvalidKey(e.to_string()))?
+        .try_into()
+        .map_err(|_| CryptoError::InvalidKey("key must be 32 bytes".into()))
+}
+
+/// `nonce || ciphertext+tag`.
+fn seal(key: &Key<Aes256Gcm>, msg: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ct = Aes256Gcm::new(key)
+        .encrypt(&nonce, Payload { msg, aad })
+        .map_err(|_| CryptoError::Decrypt)?;
+    let mut out = nonce.to_vec();
+    out.extend_from_slice(&ct);
+    Ok(out)
+}
+
+fn open(key: &Key<Aes256Gcm>, sealed: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    if sealed.len() < NONCE_LEN {
+        return Err(CryptoError::Decrypt);
+    }
+    let (nonce, ct) = sealed.split_at(NONCE_LEN);
+    Aes256Gcm::new(key)
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: ct, aad })
+        .map_err(|_| CryptoError::Decrypt)
+}
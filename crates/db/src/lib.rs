@@ -1,9 +1,18 @@
-use chrono::{DateTime, Utc};
-use common::DoctorUserRow;
+use chrono::{DateTime, NaiveDate, NaiveTime, SecondsFormat, Utc};
+use common::{DoctorUserRow, Page, PageRequest};
 use serde::Serialize;
-use sqlx::{PgPool, postgres::PgPoolOptions};
+use sqlx::migrate::Migrator;
+use sqlx::{PgPool, Postgres, Transaction, postgres::PgPoolOptions};
+use std::time::Duration;
 use uuid::Uuid;
 
+pub mod audit;
+pub mod crypto;
+
+pub use audit::{AuditContext, AuditEvent};
+use crypto::DataKey;
+pub use crypto::KeyRing;
+
 #[derive(Debug, Clone)]
 pub struct Db(pub PgPool);
 
@@ -14,8 +23,51 @@ pub enum DbError {
 
     #[error("migration error: {0}")]
     Migration(#[from] sqlx::migrate::MigrateError),
+
+    #[error("crypto error: {0}")]
+    Crypto(#[from] crypto::CryptoError),
+}
+
+/// Handle bound to one organization. Tenant-owned tables are only reachable
+/// through it: queries filter on `org_id`, and every transaction sets
+/// `app.org_id` so the row-level security policies apply as well.
+#[derive(Debug, Clone)]
+pub struct TenantDb {
+    db: Db,
+    org_id: i32,
+}
+
+impl Db {
+    pub fn tenant(&self, org_id: i32) -> TenantDb {
+        TenantDb {
+            db: self.clone(),
+            org_id,
+        }
+    }
+}
+
+impl TenantDb {
+    pub fn org_id(&self) -> i32 {
+        self.org_id
+    }
+
+    /// Unscoped handle, for tables that are not tenant-owned.
+    pub fn db(&self) -> &Db {
+        &self.db
+    }
+
+    /// Transaction with `app.org_id` set for its duration.
+    pub async fn begin(&self) -> Result<Transaction<'static, Postgres>, DbError> {
+        let mut tx = self.db.0.begin().await?;
+        sqlx::query("SELECT set_config('app.org_id', $1, true)")
+            .bind(self.org_id.to_string())
+            .execute(&mut *tx)
+            .await?;
+        Ok(tx)
+    }
 }
 
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn connect(database_url: &str, max: u32) -> Result<Db, DbError> {
     let pool = PgPoolOptions::new()
         .max_connections(max)
@@ -24,8 +76,70 @@ pub async fn connect(database_url: &str, max: u32) -> Result<Db, DbError> {
     Ok(Db(pool))
 }
 
+/// [`connect`], retried up to `attempts` times with exponential backoff
+/// (0.5 s doubling to at most 30 s) so the service can boot before Postgres.
+/// Each attempt is cut off after 5 s rather than sqlx's 30 s pool timeout.
+pub async fn connect_with_retry(
+    database_url: &str,
+    max: u32,
+    attempts: u32,
+) -> Result<Db, DbError> {
+    let mut delay = Duration::from_millis(500);
+    let mut attempt = 1;
+    loop {
+        let result = tokio::time::timeout(Duration::from_secs(5), connect(database_url, max))
+            .await
+            .unwrap_or(Err(DbError::Sqlx(sqlx::Error::PoolTimedOut)));
+        match result {
+            Ok(db) => return Ok(db),
+            Err(e) if attempt < attempts => {
+                tracing::warn!(
+                    attempt,
+                    retry_in_ms = delay.as_millis() as u64,
+                    error = %e,
+                    "database unavailable"
+                );
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(Duration::from_secs(30));
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn migrate(db: &Db) -> Result<(), DbError> {
-    sqlx::migrate!("./migrations").run(&db.0).await?;
+    MIGRATOR.run(&db.0).await?;
+    Ok(())
+}
+
+/// Versions of embedded migrations not yet applied successfully.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn pending_migrations(db: &Db) -> Result<Vec<i64>, DbError> {
+    let tracked: bool = sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+        .fetch_one(&db.0)
+        .await?;
+    let applied: Vec<i64> = if tracked {
+        sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success")
+            .fetch_all(&db.0)
+            .await?
+    } else {
+        Vec::new()
+    };
+    Ok(MIGRATOR
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration() && !applied.contains(&m.version))
+        .map(|m| m.version)
+        .collect())
+}
+
+/// Round trip to Postgres through the pool.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn ping(db: &Db) -> Result<(), DbError> {
+    sqlx::query("SELECT 1").execute(&db.0).await?;
     Ok(())
 }
 
@@ -44,12 +158,29 @@ pub struct UserRow {
 pub struct ItemRow {
     pub id: Uuid,
     pub owner_id: Uuid,
+    pub org_id: i32,
     pub title: String,
     pub description: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub deleted_by: Option<Uuid>,
+    pub version: i64,
 }
 
+/// Rows with a `version` column that the `bump_row_version` trigger increments
+/// on every update. The API exposes it as the `ETag` for optimistic locking.
+pub trait Versioned {
+    fn version(&self) -> i64;
+}
+
+impl Versioned for ItemRow {
+    fn version(&self) -> i64 {
+        self.version
+    }
+}
+
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn find_doctor_by_reg_no(
     db: &Db,
     reg_no: &str,
@@ -64,54 +195,289 @@ pub async fn find_doctor_by_reg_no(
     Ok(row)
 }
 
-pub async fn insert_doctor_user(
-    db: &Db,
-    reg_no: &str,
-    first_name: &str,
-    last_name: &str,
-    rank_name: Option<&str>,
-    org_name: Option<&str>,
-    org_id: i32,
-    position: Option<&str>,
-    birth_date: Option<chrono::NaiveDate>,
-    gender: Option<&str>,
-    doctor_roll: Option<i32>,
-    password_hash: &str,
-) -> Result<DoctorUserRow, DbError> {
+/// Column values for a new `doctor_user` row.
+#[derive(Debug)]
+pub struct NewDoctor<'a> {
+    pub reg_no: &'a str,
+    pub first_name: &'a str,
+    pub last_name: &'a str,
+    pub rank_name: Option<&'a str>,
+    pub org_name: Option<&'a str>,
+    pub org_id: i32,
+    pub position: Option<&'a str>,
+    pub birth_date: Option<chrono::NaiveDate>,
+    pub gender: Option<&'a str>,
+    pub doctor_roll: Option<i32>,
+    pub department: Option<&'a str>,
+    pub password_hash: &'a str,
+}
+
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn insert_doctor_user(db: &Db, d: &NewDoctor<'_>) -> Result<DoctorUserRow, DbError> {
+    insert_doctor_with(&db.0, d).await
+}
+
+async fn insert_doctor_with<'e, E>(exec: E, d: &NewDoctor<'_>) -> Result<DoctorUserRow, DbError>
+where
+    E: sqlx::PgExecutor<'e>,
+{
     let row = sqlx::query_as!(
         DoctorUserRow,
         r#"
         INSERT INTO doctor_user (
             reg_no, first_name, last_name, rank_name, org_name, org_id,
-            position, birth_date, gender, doctor_roll, password_hash, is_active
+            position, birth_date, gender, doctor_roll, department, password_hash, is_active
         )
         VALUES (
             $1,$2,$3,$4,$5,$6,
-            $7,$8,$9,$10,$11,TRUE
+            $7,$8,$9,$10,$11,$12,TRUE
         )
         RETURNING 
             id, doctor_id, rank_name, first_name, last_name, org_name,
             org_id, reg_no, position, birth_date, gender,
-            doctor_roll, created_at, updated_at, password_hash, is_active
+            doctor_roll, created_at, updated_at, password_hash, is_active,
+            department, phone, email, deactivated_at, deactivated_by
         "#,
-        reg_no,
-        first_name,
-        last_name,
-        rank_name,
-        org_name,
-        org_id,
-        position,
-        birth_date,
-        gender,
-        doctor_roll,
-        password_hash
+        d.reg_no,
+        d.first_name,
+        d.last_name,
+        d.rank_name,
+        d.org_name,
+        d.org_id,
+        d.position,
+        d.birth_date,
+        d.gender,
+        d.doctor_roll,
+        d.department,
+        d.password_hash
     )
-    .fetch_one(&db.0)
+    .fetch_one(exec)
+    .await?;
+
+    Ok(row)
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+pub async fn get_doctor(db: &Db, id: Uuid) -> Result<Option<DoctorUserRow>, DbError> {
+    let row = sqlx::query_as::<_, DoctorUserRow>("SELECT * FROM doctor_user WHERE id=$1")
+        .bind(id)
+        .fetch_optional(&db.0)
+        .await?;
+    Ok(row)
+}
+
+/// Name of the `doctor_rolls` entry a doctor is assigned to (e.g. "admin").
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn doctor_roll_name(db: &Db, roll_id: i32) -> Result<Option<String>, DbError> {
+    let name =
+        sqlx::query_scalar::<_, String>("SELECT roll_name FROM doctor_rolls WHERE roll_id=$1")
+            .bind(roll_id)
+            .fetch_optional(&db.0)
+            .await?;
+    Ok(name)
+}
+
+/// Fields a doctor may change on their own profile. `None` leaves the column
+/// untouched; `Some(None)` clears one of the optional fields.
+#[derive(Debug, Default)]
+pub struct DoctorProfileUpdate<'a> {
+    pub first_name: Option<&'a str>,
+    pub last_name: Option<&'a str>,
+    pub rank_name: Option<Option<&'a str>>,
+    pub position: Option<Option<&'a str>>,
+    pub phone: Option<Option<&'a str>>,
+    pub email: Option<Option<&'a str>>,
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+pub async fn update_doctor_profile(
+    db: &Db,
+    id: Uuid,
+    upd: &DoctorProfileUpdate<'_>,
+) -> Result<Option<DoctorUserRow>, DbError> {
+    let row = sqlx::query_as::<_, DoctorUserRow>(
+        r#"UPDATE doctor_user SET
+               first_name = COALESCE($2, first_name),
+               last_name  = COALESCE($3, last_name),
+               rank_name  = CASE WHEN $4 THEN $5 ELSE rank_name END,
+               position   = CASE WHEN $6 THEN $7 ELSE position END,
+               phone      = CASE WHEN $8 THEN $9 ELSE phone END,
+               email      = CASE WHEN $10 THEN $11 ELSE email END,
+               updated_at = NOW()
+           WHERE id=$1
+           RETURNING *"#,
+    )
+    .bind(id)
+    .bind(upd.first_name)
+    .bind(upd.last_name)
+    .bind(upd.rank_name.is_some())
+    .bind(upd.rank_name.flatten())
+    .bind(upd.position.is_some())
+    .bind(upd.position.flatten())
+    .bind(upd.phone.is_some())
+    .bind(upd.phone.flatten())
+    .bind(upd.email.is_some())
+    .bind(upd.email.flatten())
+    .fetch_optional(&db.0)
+    .await?;
+    Ok(row)
+}
+
+/// Organisational fields only an admin may change.
+#[derive(Debug, Default)]
+pub struct DoctorAdminUpdate<'a> {
+    pub org_id: Option<i32>,
+    pub org_name: Option<&'a str>,
+    pub department: Option<&'a str>,
+    pub doctor_roll: Option<i32>,
+    /// Only `true` (reactivation) has an effect; deactivate with
+    /// [`deactivate_doctor`].
+    pub is_active: Option<bool>,
+}
+
+/// Applies `upd` to doctor `id` if they belong to `org_id`; `None` otherwise.
+#[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+pub async fn admin_update_doctor(
+    db: &Db,
+    id: Uuid,
+    org_id: i32,
+    upd: &DoctorAdminUpdate<'_>,
+) -> Result<Option<DoctorUserRow>, DbError> {
+    let row = sqlx::query_as::<_, DoctorUserRow>(
+        r#"UPDATE doctor_user SET
+               org_id      = COALESCE($2, org_id),
+               org_name    = COALESCE($3, org_name),
+               department  = COALESCE($4, department),
+               doctor_roll = COALESCE($5, doctor_roll),
+               is_active   = is_active OR COALESCE($6, false),
+               deactivated_at = CASE WHEN $6 THEN NULL ELSE deactivated_at END,
+               deactivated_by = CASE WHEN $6 THEN NULL ELSE deactivated_by END,
+               updated_at  = NOW()
+           WHERE id=$1 AND org_id=$7
+           RETURNING *"#,
+    )
+    .bind(id)
+    .bind(upd.org_id)
+    .bind(upd.org_name)
+    .bind(upd.department)
+    .bind(upd.doctor_roll)
+    .bind(upd.is_active)
+    .bind(org_id)
+    .fetch_optional(&db.0)
     .await?;
+    Ok(row)
+}
 
+/// Doctors are never deleted: their items, credentials and roster history stay.
+/// Deactivation blocks login and revokes outstanding refresh tokens. Doctors
+/// outside `org_id` are left alone and give `None`.
+#[tracing::instrument(level = "debug", skip_all, fields(id = %id, deactivated_by = %deactivated_by))]
+pub async fn deactivate_doctor(
+    db: &Db,
+    ctx: &AuditContext,
+    id: Uuid,
+    org_id: i32,
+    deactivated_by: Uuid,
+) -> Result<Option<DoctorUserRow>, DbError> {
+    let mut tx = db.0.begin().await?;
+    let row = sqlx::query_as::<_, DoctorUserRow>(
+        r#"UPDATE doctor_user SET
+               is_active = false,
+               deactivated_at = COALESCE(deactivated_at, NOW()),
+               deactivated_by = COALESCE(deactivated_by, $2),
+               updated_at = NOW()
+           WHERE id=$1 AND org_id=$3
+           RETURNING *"#,
+    )
+    .bind(id)
+    .bind(deactivated_by)
+    .bind(org_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    if row.is_some() {
+        sqlx::query("UPDATE refresh_tokens SET revoked=true WHERE doctor_id=$1 AND NOT revoked")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        audit::record_in(&mut tx, ctx, AuditEvent::new("deactivate", "doctor", id)).await?;
+    }
+    tx.commit().await?;
     Ok(row)
 }
 
+#[derive(Debug, Default)]
+pub struct DoctorSearch<'a> {
+    pub org_id: i32,
+    pub department: Option<&'a str>,
+}
+
+pub const DOCTOR_SORTS: [&str; 2] = ["last_name", "created_at"];
+
+/// Admin directory listing for one org. `page.q` is matched case-insensitively against
+/// first/last name, reg_no and org_name.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn search_doctors(
+    db: &Db,
+    f: &DoctorSearch<'_>,
+    page: &PageRequest,
+) -> Result<Page<DoctorUserRow>, DbError> {
+    let (column, sql_type) = match page.sort.field.as_str() {
+        "created_at" => ("created_at", "timestamptz"),
+        _ => ("COALESCE(last_name, '')", "text"),
+    };
+    let (dir, cmp) = if page.sort.desc {
+        ("DESC", "<")
+    } else {
+        ("ASC", ">")
+    };
+
+    let mut qb = sqlx::QueryBuilder::<Postgres>::new("SELECT * FROM doctor_user WHERE org_id = ");
+    qb.push_bind(f.org_id);
+    if let Some(q) = &page.q {
+        let pattern = like_pattern(q);
+        qb.push(" AND (first_name ILIKE ")
+            .push_bind(pattern.clone())
+            .push(" OR last_name ILIKE ")
+            .push_bind(pattern.clone())
+            .push(" OR reg_no ILIKE ")
+            .push_bind(pattern.clone())
+            .push(" OR org_name ILIKE ")
+            .push_bind(pattern)
+            .push(")");
+    }
+    if let Some(department) = f.department {
+        qb.push(" AND department ILIKE ").push_bind(department);
+    }
+    if let Some(from) = page.created_from {
+        qb.push(" AND created_at >= ").push_bind(from);
+    }
+    if let Some(to) = page.created_to {
+        qb.push(" AND created_at < ").push_bind(to);
+    }
+    if let Some(after) = &page.after {
+        qb.push(format_args!(" AND ({column}, id) {cmp} ("))
+            .push_bind(&after.value)
+            .push(format_args!("::{sql_type}, "))
+            .push_bind(after.id)
+            .push(")");
+    }
+    qb.push(format_args!(" ORDER BY {column} {dir}, id {dir} LIMIT "))
+        .push_bind(page.limit + 1);
+
+    let rows = qb
+        .build_query_as::<DoctorUserRow>()
+        .fetch_all(&db.0)
+        .await?;
+    Ok(Page::from_rows(rows, page, |r| {
+        let value = match page.sort.field.as_str() {
+            "created_at" => r.created_at.to_rfc3339_opts(SecondsFormat::Micros, true),
+            _ => r.last_name.clone().unwrap_or_default(),
+        };
+        (value, r.id)
+    }))
+}
+
+#[tracing::instrument(level = "debug", skip_all)]
 pub async fn insert_user(
     db: &Db,
     email: &str,
@@ -133,135 +499,2102 @@ pub async fn insert_user(
     Ok(row)
 }
 
-// ==== Items ====
-pub async fn list_items(db: &Db, owner: Option<Uuid>) -> Result<Vec<ItemRow>, DbError> {
-    if let Some(owner_id) = owner {
-        let rows = sqlx::query_as::<_, ItemRow>(
-            "SELECT * FROM items WHERE owner_id = $1 ORDER BY created_at DESC",
-        )
-        .bind(owner_id)
-        .fetch_all(&db.0)
-        .await?;
-        Ok(rows)
-    } else {
-        let rows = sqlx::query_as::<_, ItemRow>("SELECT * FROM items ORDER BY created_at DESC")
-            .fetch_all(&db.0)
-            .await?;
-        Ok(rows)
-    }
+// ==== Invitations ====
+
+#[derive(sqlx::FromRow, Debug, Clone, Serialize)]
+pub struct InvitationRow {
+    pub id: Uuid,
+    #[serde(skip_serializing)]
+    pub code_hash: String,
+    pub org_id: i32,
+    pub org_name: Option<String>,
+    pub doctor_roll: Option<i32>,
+    pub department: Option<String>,
+    pub created_by: Uuid,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+    pub used_by: Option<Uuid>,
+    pub revoked: bool,
+    pub created_at: DateTime<Utc>,
 }
 
-pub async fn get_item(db: &Db, id: Uuid) -> Result<Option<ItemRow>, DbError> {
-    let row = sqlx::query_as::<_, ItemRow>("SELECT * FROM items WHERE id=$1")
-        .bind(id)
-        .fetch_optional(&db.0)
-        .await?;
-    Ok(row)
+#[derive(Debug)]
+pub struct NewInvitation<'a> {
+    pub code_hash: &'a str,
+    pub org_id: i32,
+    pub org_name: Option<&'a str>,
+    pub doctor_roll: Option<i32>,
+    pub department: Option<&'a str>,
+    pub created_by: Uuid,
+    pub expires_at: DateTime<Utc>,
 }
 
-pub async fn insert_item(
-    db: &Db,
-    owner_id: Uuid,
-    title: &str,
-    description: Option<&str>,
-) -> Result<ItemRow, DbError> {
-    let row = sqlx::query_as::<_, ItemRow>(
-        r#"INSERT INTO items (owner_id,title,description)
-           VALUES ($1,$2,$3)
-           RETURNING id, owner_id, title, description, created_at, updated_at"#,
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn insert_invitation(db: &Db, inv: &NewInvitation<'_>) -> Result<InvitationRow, DbError> {
+    let row = sqlx::query_as::<_, InvitationRow>(
+        r#"INSERT INTO invitations
+               (code_hash, org_id, org_name, doctor_roll, department, created_by, expires_at)
+           VALUES ($1,$2,$3,$4,$5,$6,$7)
+           RETURNING *"#,
     )
-    .bind(owner_id)
-    .bind(title)
-    .bind(description)
+    .bind(inv.code_hash)
+    .bind(inv.org_id)
+    .bind(inv.org_name)
+    .bind(inv.doctor_roll)
+    .bind(inv.department)
+    .bind(inv.created_by)
+    .bind(inv.expires_at)
     .fetch_one(&db.0)
     .await?;
     Ok(row)
 }
 
-pub async fn update_item(
+/// Invitations for an org, newest first. `pending` hides used/revoked/expired ones.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn list_invitations(
     db: &Db,
-    id: Uuid,
-    title: &str,
-    description: Option<&str>,
-) -> Result<Option<ItemRow>, DbError> {
-    let row = sqlx::query_as::<_, ItemRow>(
-        r#"UPDATE items SET title=$2, description=$3, updated_at=NOW()
-           WHERE id=$1
-           RETURNING id, owner_id, title, description, created_at, updated_at"#,
+    org_id: i32,
+    pending: bool,
+) -> Result<Vec<InvitationRow>, DbError> {
+    let rows = sqlx::query_as::<_, InvitationRow>(
+        r#"SELECT * FROM invitations
+           WHERE org_id = $1
+             AND (NOT $2 OR (used_at IS NULL AND NOT revoked AND expires_at > NOW()))
+           ORDER BY created_at DESC"#,
+    )
+    .bind(org_id)
+    .bind(pending)
+    .fetch_all(&db.0)
+    .await?;
+    Ok(rows)
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+pub async fn revoke_invitation(db: &Db, id: Uuid, org_id: i32) -> Result<u64, DbError> {
+    let res = sqlx::query(
+        "UPDATE invitations SET revoked=true WHERE id=$1 AND org_id=$2 AND used_at IS NULL",
     )
     .bind(id)
-    .bind(title)
-    .bind(description)
-    .fetch_optional(&db.0)
+    .bind(org_id)
+    .execute(&db.0)
+    .await?;
+    Ok(res.rows_affected())
+}
+
+/// Redeems a single-use invitation and creates the doctor in one transaction.
+///
+/// Org, org name, role and department come from the invitation and override `doctor`.
+/// Returns `None` when the code is unknown, used, revoked or expired.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn register_with_invitation(
+    db: &Db,
+    code_hash: &str,
+    doctor: NewDoctor<'_>,
+) -> Result<Option<DoctorUserRow>, DbError> {
+    let mut tx = db.0.begin().await?;
+
+    let inv = sqlx::query_as::<_, InvitationRow>(
+        r#"SELECT * FROM invitations
+           WHERE code_hash=$1 AND used_at IS NULL AND NOT revoked AND expires_at > NOW()
+           FOR UPDATE"#,
+    )
+    .bind(code_hash)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some(inv) = inv else {
+        return Ok(None);
+    };
+
+    let doctor = NewDoctor {
+        org_id: inv.org_id,
+        org_name: inv.org_name.as_deref(),
+        doctor_roll: inv.doctor_roll,
+        department: inv.department.as_deref(),
+        ..doctor
+    };
+    let row = insert_doctor_with(&mut *tx, &doctor).await?;
+
+    sqlx::query("UPDATE invitations SET used_at=NOW(), used_by=$2 WHERE id=$1")
+        .bind(inv.id)
+        .bind(row.id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(Some(row))
+}
+
+// ==== Credentials (licenses, specialties, certifications) ====
+
+#[derive(sqlx::FromRow, Debug, Clone, Serialize)]
+pub struct CredentialRow {
+    pub id: Uuid,
+    pub doctor_id: Uuid,
+    pub kind: String,
+    pub title: String,
+    pub number: Option<String>,
+    pub issuer: Option<String>,
+    pub issued_on: Option<NaiveDate>,
+    pub expires_on: Option<NaiveDate>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+pub struct NewCredential<'a> {
+    pub doctor_id: Uuid,
+    /// One of `license`, `specialty`, `certification`.
+    pub kind: &'a str,
+    pub title: &'a str,
+    pub number: Option<&'a str>,
+    pub issuer: Option<&'a str>,
+    pub issued_on: Option<NaiveDate>,
+    pub expires_on: Option<NaiveDate>,
+}
+
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn insert_credential(db: &Db, c: &NewCredential<'_>) -> Result<CredentialRow, DbError> {
+    let row = sqlx::query_as::<_, CredentialRow>(
+        r#"INSERT INTO doctor_credentials
+               (doctor_id, kind, title, number, issuer, issued_on, expires_on)
+           VALUES ($1,$2,$3,$4,$5,$6,$7)
+           RETURNING *"#,
+    )
+    .bind(c.doctor_id)
+    .bind(c.kind)
+    .bind(c.title)
+    .bind(c.number)
+    .bind(c.issuer)
+    .bind(c.issued_on)
+    .bind(c.expires_on)
+    .fetch_one(&db.0)
     .await?;
     Ok(row)
 }
 
-pub async fn delete_item(db: &Db, id: Uuid) -> Result<u64, DbError> {
-    let res = sqlx::query("DELETE FROM items WHERE id=$1")
+#[tracing::instrument(level = "debug", skip_all, fields(doctor_id = %doctor_id))]
+pub async fn list_credentials(db: &Db, doctor_id: Uuid) -> Result<Vec<CredentialRow>, DbError> {
+    let rows = sqlx::query_as::<_, CredentialRow>(
+        "SELECT * FROM doctor_credentials WHERE doctor_id=$1 ORDER BY kind, expires_on NULLS LAST",
+    )
+    .bind(doctor_id)
+    .fetch_all(&db.0)
+    .await?;
+    Ok(rows)
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(doctor_id = %doctor_id, id = %id))]
+pub async fn delete_credential(db: &Db, doctor_id: Uuid, id: Uuid) -> Result<u64, DbError> {
+    let res = sqlx::query("DELETE FROM doctor_credentials WHERE id=$1 AND doctor_id=$2")
         .bind(id)
+        .bind(doctor_id)
         .execute(&db.0)
         .await?;
     Ok(res.rows_affected())
 }
 
-// ==== Refresh tokens (rotation) ====
+/// True when the doctor holds at least one license that has no expiry or has not expired yet.
+#[tracing::instrument(level = "debug", skip_all, fields(doctor_id = %doctor_id))]
+pub async fn has_active_license(db: &Db, doctor_id: Uuid) -> Result<bool, DbError> {
+    let ok = sqlx::query_scalar::<_, bool>(
+        r#"SELECT EXISTS (
+               SELECT 1 FROM doctor_credentials
+               WHERE doctor_id=$1 AND kind='license'
+                 AND (expires_on IS NULL OR expires_on >= CURRENT_DATE)
+           )"#,
+    )
+    .bind(doctor_id)
+    .fetch_one(&db.0)
+    .await?;
+    Ok(ok)
+}
 
-#[derive(sqlx::FromRow, Debug, Serialize, Clone)]
-pub struct RefreshRow {
-    pub id: i64,
+#[derive(sqlx::FromRow, Debug, Clone, Serialize)]
+pub struct ExpiringCredentialRow {
+    pub id: Uuid,
     pub doctor_id: Uuid,
-    pub jti: String,
-    pub token_hash: String,
-    pub expires_at: DateTime<Utc>,
-    pub revoked: bool,
-    pub created_at: DateTime<Utc>,
+    pub reg_no: String,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub org_id: i32,
+    pub department: Option<String>,
+    pub kind: String,
+    pub title: String,
+    pub number: Option<String>,
+    pub expires_on: NaiveDate,
+    pub days_left: i32,
 }
 
-pub async fn insert_refresh(
+/// Credentials of an org's doctors expiring within `days` (already expired
+/// ones included), soonest first.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn expiring_credentials(
     db: &Db,
-    doctor_id: Uuid,
-    jti: &str,
-    token_hash: &str,
-    expires_at: DateTime<Utc>,
-) -> Result<(), DbError> {
-    println!("➡️ INSERT REFRESH doctor_id={doctor_id}, jti={jti}, expires_at={expires_at}");
-    let result = sqlx::query!(
-        r#"
-        INSERT INTO refresh_tokens (doctor_id, jti, token_hash, expires_at)
-        VALUES ($1, $2, $3, $4)
-        "#,
-        doctor_id,
-        jti,
-        token_hash,
-        expires_at
+    org_id: i32,
+    days: i32,
+) -> Result<Vec<ExpiringCredentialRow>, DbError> {
+    let rows = sqlx::query_as::<_, ExpiringCredentialRow>(
+        r#"SELECT c.id, c.doctor_id, d.reg_no, d.first_name, d.last_name, d.org_id, d.department,
+                  c.kind, c.title, c.number, c.expires_on,
+                  (c.expires_on - CURRENT_DATE)::int4 AS days_left
+           FROM doctor_credentials c
+           JOIN doctor_user d ON d.id = c.doctor_id
+           WHERE c.expires_on IS NOT NULL
+             AND c.expires_on <= CURRENT_DATE + $2::int4
+             AND d.org_id = $1
+           ORDER BY c.expires_on, d.last_name"#,
     )
-    .execute(&db.0)
-    .await;
+    .bind(org_id)
+    .bind(days)
+    .fetch_all(&db.0)
+    .await?;
+    Ok(rows)
+}
 
-    match result {
-        Ok(_) => {
-            println!("✅ REFRESH INSERT OK");
-            Ok(())
-        }
-        Err(e) => {
-            eprintln!("❌ REFRESH INSERT ERROR: {:?}", e);
-            Err(DbError::from(e))
-        }
-    }
+// ==== Shift roster & on-call ====
+
+#[derive(sqlx::FromRow, Debug, Clone, Serialize)]
+pub struct ShiftDefinitionRow {
+    pub id: Uuid,
+    pub org_id: i32,
+    pub name: String,
+    pub start_time: NaiveTime,
+    pub duration_minutes: i32,
+    pub timezone: String,
+    pub on_call: bool,
+    pub created_at: DateTime<Utc>,
 }
 
-pub async fn get_refresh_by_jti(db: &Db, jti: &str) -> Result<Option<RefreshRow>, DbError> {
-    let row = sqlx::query_as::<_, RefreshRow>("SELECT * FROM refresh_tokens WHERE jti=$1")
-        .bind(jti)
-        .fetch_optional(&db.0)
-        .await?;
+#[derive(Debug)]
+pub struct NewShiftDefinition<'a> {
+    pub org_id: i32,
+    pub name: &'a str,
+    pub start_time: NaiveTime,
+    pub duration_minutes: i32,
+    pub timezone: Option<&'a str>,
+    pub on_call: bool,
+}
+
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn insert_shift_definition(
+    db: &Db,
+    d: &NewShiftDefinition<'_>,
+) -> Result<ShiftDefinitionRow, DbError> {
+    let row = sqlx::query_as::<_, ShiftDefinitionRow>(
+        r#"INSERT INTO shift_definitions
+               (org_id, name, start_time, duration_minutes, timezone, on_call)
+           VALUES ($1,$2,$3,$4,COALESCE($5,'Asia/Ulaanbaatar'),$6)
+           RETURNING *"#,
+    )
+    .bind(d.org_id)
+    .bind(d.name)
+    .bind(d.start_time)
+    .bind(d.duration_minutes)
+    .bind(d.timezone)
+    .bind(d.on_call)
+    .fetch_one(&db.0)
+    .await?;
     Ok(row)
 }
 
-pub async fn revoke_refresh(db: &Db, jti: &str) -> Result<u64, DbError> {
-    let res = sqlx::query("UPDATE refresh_tokens SET revoked=true WHERE jti=$1")
-        .bind(jti)
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn list_shift_definitions(
+    db: &Db,
+    org_id: i32,
+) -> Result<Vec<ShiftDefinitionRow>, DbError> {
+    let rows = sqlx::query_as::<_, ShiftDefinitionRow>(
+        "SELECT * FROM shift_definitions WHERE org_id=$1 ORDER BY start_time, name",
+    )
+    .bind(org_id)
+    .fetch_all(&db.0)
+    .await?;
+    Ok(rows)
+}
+
+/// A shift definition with its start and end on a given date.
+pub type ShiftWindow = (ShiftDefinitionRow, DateTime<Utc>, DateTime<Utc>);
+
+/// Concrete window of a shift definition on `date`, in the definition's time zone.
+#[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+pub async fn shift_definition_window(
+    db: &Db,
+    id: Uuid,
+    date: NaiveDate,
+) -> Result<Option<ShiftWindow>, DbError> {
+    let Some(def) =
+        sqlx::query_as::<_, ShiftDefinitionRow>("SELECT * FROM shift_definitions WHERE id=$1")
+            .bind(id)
+            .fetch_optional(&db.0)
+            .await?
+    else {
+        return Ok(None);
+    };
+    let (starts_at, ends_at) = sqlx::query_as::<_, (DateTime<Utc>, DateTime<Utc>)>(
+        r#"SELECT s, s + make_interval(mins => $3)
+           FROM (SELECT ($1::date + $2::time) AT TIME ZONE $4 AS s) t"#,
+    )
+    .bind(date)
+    .bind(def.start_time)
+    .bind(def.duration_minutes)
+    .bind(&def.timezone)
+    .fetch_one(&db.0)
+    .await?;
+    Ok(Some((def, starts_at, ends_at)))
+}
+
+#[derive(sqlx::FromRow, Debug, Clone, Serialize)]
+pub struct ShiftRow {
+    pub id: Uuid,
+    pub doctor_id: Uuid,
+    pub org_id: i32,
+    pub department: String,
+    pub shift_definition_id: Option<Uuid>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub on_call: bool,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+pub struct NewShift<'a> {
+    pub doctor_id: Uuid,
+    pub org_id: i32,
+    pub department: &'a str,
+    pub shift_definition_id: Option<Uuid>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub on_call: bool,
+    pub created_by: Uuid,
+}
+
+/// An existing shift that collides with a requested one.
+#[derive(sqlx::FromRow, Debug, Clone, Serialize)]
+pub struct ShiftConflict {
+    pub shift_id: Uuid,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    /// `overlap` or `rest_period`.
+    pub reason: String,
+}
+
+#[derive(Debug)]
+pub enum AssignShift {
+    Created(ShiftRow),
+    Conflicts(Vec<ShiftConflict>),
+}
+
+/// Assigns a shift unless it overlaps another shift of the same doctor or leaves
+/// less than `min_rest_minutes` between them. Runs under a per-doctor advisory
+/// lock so concurrent assignments can't both slip through.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn assign_shift(
+    db: &Db,
+    s: &NewShift<'_>,
+    min_rest_minutes: i32,
+) -> Result<AssignShift, DbError> {
+    let mut tx = db.0.begin().await?;
+
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1::text))")
+        .bind(s.doctor_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let conflicts = sqlx::query_as::<_, ShiftConflict>(
+        r#"SELECT id AS shift_id, starts_at, ends_at,
+                  CASE WHEN starts_at < $3 AND ends_at > $2 THEN 'overlap' ELSE 'rest_period' END AS reason
+           FROM shift_assignments
+           WHERE doctor_id = $1
+             AND starts_at < $3 + make_interval(mins => $4)
+             AND ends_at + make_interval(mins => $4) > $2
+           ORDER BY starts_at"#,
+    )
+    .bind(s.doctor_id)
+    .bind(s.starts_at)
+    .bind(s.ends_at)
+    .bind(min_rest_minutes)
+    .fetch_all(&mut *tx)
+    .await?;
+    if !conflicts.is_empty() {
+        return Ok(AssignShift::Conflicts(conflicts));
+    }
+
+    let row = sqlx::query_as::<_, ShiftRow>(
+        r#"INSERT INTO shift_assignments
+               (doctor_id, org_id, department, shift_definition_id, starts_at, ends_at, on_call, created_by)
+           VALUES ($1,$2,$3,$4,$5,$6,$7,$8)
+           RETURNING *"#,
+    )
+    .bind(s.doctor_id)
+    .bind(s.org_id)
+    .bind(s.department)
+    .bind(s.shift_definition_id)
+    .bind(s.starts_at)
+    .bind(s.ends_at)
+    .bind(s.on_call)
+    .bind(s.created_by)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(AssignShift::Created(row))
+}
+
+#[derive(Debug, Default)]
+pub struct ShiftFilter<'a> {
+    pub org_id: i32,
+    pub department: Option<&'a str>,
+    pub doctor_id: Option<Uuid>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Roster view: an org's shifts intersecting `[from, to)`.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn list_shifts(db: &Db, f: &ShiftFilter<'_>) -> Result<Vec<ShiftRow>, DbError> {
+    let rows = sqlx::query_as::<_, ShiftRow>(
+        r#"SELECT * FROM shift_assignments
+           WHERE org_id = $1
+             AND ($2::text IS NULL OR lower(department) = lower($2))
+             AND ($3::uuid IS NULL OR doctor_id = $3)
+             AND ($4::timestamptz IS NULL OR ends_at > $4)
+             AND ($5::timestamptz IS NULL OR starts_at < $5)
+           ORDER BY starts_at, department"#,
+    )
+    .bind(f.org_id)
+    .bind(f.department)
+    .bind(f.doctor_id)
+    .bind(f.from)
+    .bind(f.to)
+    .fetch_all(&db.0)
+    .await?;
+    Ok(rows)
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+pub async fn delete_shift(db: &Db, id: Uuid, org_id: i32) -> Result<u64, DbError> {
+    let res = sqlx::query("DELETE FROM shift_assignments WHERE id=$1 AND org_id=$2")
+        .bind(id)
+        .bind(org_id)
+        .execute(&db.0)
+        .await?;
+    Ok(res.rows_affected())
+}
+
+#[derive(sqlx::FromRow, Debug, Clone, Serialize)]
+pub struct OnCallRow {
+    pub shift_id: Uuid,
+    pub doctor_id: Uuid,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub position: Option<String>,
+    pub phone: Option<String>,
+    pub department: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+}
+
+/// Doctors on call at `at` for a department of an org (inactive doctors excluded).
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn on_call_at(
+    db: &Db,
+    org_id: i32,
+    department: &str,
+    at: DateTime<Utc>,
+) -> Result<Vec<OnCallRow>, DbError> {
+    let rows = sqlx::query_as::<_, OnCallRow>(
+        r#"SELECT s.id AS shift_id, s.doctor_id, d.first_name, d.last_name, d.position, d.phone,
+                  s.department, s.starts_at, s.ends_at
+           FROM shift_assignments s
+           JOIN doctor_user d ON d.id = s.doctor_id
+           WHERE s.org_id = $1
+             AND lower(s.department) = lower($2)
+             AND s.on_call
+             AND d.is_active
+             AND s.starts_at <= $3 AND s.ends_at > $3
+           ORDER BY s.ends_at DESC"#,
+    )
+    .bind(org_id)
+    .bind(department)
+    .bind(at)
+    .fetch_all(&db.0)
+    .await?;
+    Ok(rows)
+}
+
+// ==== Break-the-glass emergency access ====
+
+#[derive(sqlx::FromRow, Debug, Clone, Serialize)]
+pub struct EmergencyAccessRow {
+    pub id: Uuid,
+    pub doctor_id: Uuid,
+    pub org_id: i32,
+    pub resource_type: Option<String>,
+    pub resource_id: Option<String>,
+    pub justification: String,
+    pub granted_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug)]
+pub struct NewEmergencyAccess<'a> {
+    pub doctor_id: Uuid,
+    /// Organization whose records are being opened.
+    pub org_id: i32,
+    /// Narrows the grant to one record; `None` opens the whole org.
+    pub resource_type: Option<&'a str>,
+    pub resource_id: Option<&'a str>,
+    pub justification: &'a str,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Grants time-boxed emergency access, flags it in the audit trail and notifies
+/// the target org's privacy officers, all in one transaction.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn grant_emergency_access(
+    db: &Db,
+    ctx: &AuditContext,
+    g: &NewEmergencyAccess<'_>,
+) -> Result<EmergencyAccessRow, DbError> {
+    let mut tx = db.0.begin().await?;
+
+    let row = sqlx::query_as::<_, EmergencyAccessRow>(
+        r#"INSERT INTO emergency_access
+               (doctor_id, org_id, resource_type, resource_id, justification, expires_at)
+           VALUES ($1,$2,$3,$4,$5,$6)
+           RETURNING *"#,
+    )
+    .bind(g.doctor_id)
+    .bind(g.org_id)
+    .bind(g.resource_type)
+    .bind(g.resource_id)
+    .bind(g.justification)
+    .bind(g.expires_at)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let ev = AuditEvent {
+        action: "break_glass",
+        resource_type: "emergency_access",
+        resource_id: Some(row.id.to_string()),
+        diff: Some(serde_json::json!({
+            "flag": "break_the_glass",
+            "grant": &row,
+        })),
+    };
+    audit::record_in(&mut tx, ctx, ev).await?;
+
+    sqlx::query(
+        r#"INSERT INTO notifications (recipient_id, kind, payload)
+           SELECT d.id, 'break_glass', $2
+           FROM doctor_user d
+           JOIN doctor_rolls r ON r.roll_id = d.doctor_roll
+           WHERE r.roll_name = 'privacy_officer' AND d.org_id = $1 AND d.is_active"#,
+    )
+    .bind(g.org_id)
+    .bind(serde_json::json!({
+        "grant_id": row.id,
+        "doctor_id": row.doctor_id,
+        "org_id": row.org_id,
+        "resource_type": row.resource_type,
+        "resource_id": row.resource_id,
+        "justification": row.justification,
+        "expires_at": row.expires_at,
+    }))
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(row)
+}
+
+/// Unexpired, unrevoked grant letting `doctor_id` into `org_id` — either org-wide
+/// or for the given resource.
+#[tracing::instrument(level = "debug", skip_all, fields(doctor_id = %doctor_id))]
+pub async fn active_emergency_grant(
+    db: &Db,
+    doctor_id: Uuid,
+    org_id: i32,
+    resource_type: &str,
+    resource_id: &str,
+) -> Result<Option<EmergencyAccessRow>, DbError> {
+    let row = sqlx::query_as::<_, EmergencyAccessRow>(
+        r#"SELECT * FROM emergency_access
+           WHERE doctor_id = $1 AND org_id = $2
+             AND revoked_at IS NULL AND expires_at > NOW()
+             AND (resource_type IS NULL OR (resource_type = $3 AND resource_id = $4))
+           ORDER BY expires_at DESC
+           LIMIT 1"#,
+    )
+    .bind(doctor_id)
+    .bind(org_id)
+    .bind(resource_type)
+    .bind(resource_id)
+    .fetch_optional(&db.0)
+    .await?;
+    Ok(row)
+}
+
+/// Grants for review: by target org and/or by doctor, newest first.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn list_emergency_access(
+    db: &Db,
+    org_id: Option<i32>,
+    doctor_id: Option<Uuid>,
+) -> Result<Vec<EmergencyAccessRow>, DbError> {
+    let rows = sqlx::query_as::<_, EmergencyAccessRow>(
+        r#"SELECT * FROM emergency_access
+           WHERE ($1::int4 IS NULL OR org_id = $1)
+             AND ($2::uuid IS NULL OR doctor_id = $2)
+           ORDER BY granted_at DESC
+           LIMIT 500"#,
+    )
+    .bind(org_id)
+    .bind(doctor_id)
+    .fetch_all(&db.0)
+    .await?;
+    Ok(rows)
+}
+
+/// Ends a grant held by `doctor_id`, or one into `org_id` when given (for
+/// reviewers); `None` when there is no such open grant.
+#[tracing::instrument(level = "debug", skip_all, fields(id = %id, doctor_id = %doctor_id))]
+pub async fn revoke_emergency_access(
+    db: &Db,
+    ctx: &AuditContext,
+    id: Uuid,
+    doctor_id: Uuid,
+    org_id: Option<i32>,
+) -> Result<Option<EmergencyAccessRow>, DbError> {
+    let mut tx = db.0.begin().await?;
+    let row = sqlx::query_as::<_, EmergencyAccessRow>(
+        r#"UPDATE emergency_access SET revoked_at = NOW()
+           WHERE id = $1 AND revoked_at IS NULL
+             AND (doctor_id = $2 OR org_id = $3)
+           RETURNING *"#,
+    )
+    .bind(id)
+    .bind(doctor_id)
+    .bind(org_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    if row.is_some() {
+        audit::record_in(
+            &mut tx,
+            ctx,
+            AuditEvent::new("revoke", "emergency_access", id),
+        )
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(row)
+}
+
+// ==== Notifications ====
+
+#[derive(sqlx::FromRow, Debug, Clone, Serialize)]
+pub struct NotificationRow {
+    pub id: i64,
+    pub recipient_id: Uuid,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+    pub read_at: Option<DateTime<Utc>>,
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(recipient_id = %recipient_id))]
+pub async fn list_notifications(
+    db: &Db,
+    recipient_id: Uuid,
+    unread_only: bool,
+) -> Result<Vec<NotificationRow>, DbError> {
+    let rows = sqlx::query_as::<_, NotificationRow>(
+        r#"SELECT * FROM notifications
+           WHERE recipient_id = $1 AND (NOT $2 OR read_at IS NULL)
+           ORDER BY created_at DESC
+           LIMIT 200"#,
+    )
+    .bind(recipient_id)
+    .bind(unread_only)
+    .fetch_all(&db.0)
+    .await?;
+    Ok(rows)
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(recipient_id = %recipient_id))]
+pub async fn mark_notification_read(db: &Db, recipient_id: Uuid, id: i64) -> Result<u64, DbError> {
+    let res = sqlx::query(
+        "UPDATE notifications SET read_at = NOW() WHERE id=$1 AND recipient_id=$2 AND read_at IS NULL",
+    )
+    .bind(id)
+    .bind(recipient_id)
+    .execute(&db.0)
+    .await?;
+    Ok(res.rows_affected())
+}
+
+/// Deletes notifications read more than `days` days ago.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn purge_read_notifications(db: &Db, days: i32) -> Result<u64, DbError> {
+    let res =
+        sqlx::query("DELETE FROM notifications WHERE read_at < NOW() - make_interval(days => $1)")
+            .bind(days)
+            .execute(&db.0)
+            .await?;
+    Ok(res.rows_affected())
+}
+
+// ==== Patients ====
+// National ID, diagnoses and notes are encrypted at rest; see `crypto`.
+// Patients, their consents and documents are tenant-owned and go through
+// `TenantDb`; only the `patient_orgs` directory is read unscoped.
+
+const NATIONAL_ID_INDEX: &str = "patients.national_id";
+
+/// Stored form of a patient, sensitive columns still sealed. Serializes without
+/// them, which is what goes into audit diffs.
+#[derive(sqlx::FromRow, Debug, Clone, Serialize)]
+struct PatientRecord {
+    id: Uuid,
+    org_id: i32,
+    first_name: String,
+    last_name: String,
+    /// Legacy plaintext, cleared by the `reencrypt` tool.
+    #[serde(skip_serializing)]
+    national_id: Option<String>,
+    birth_date: Option<NaiveDate>,
+    gender: Option<String>,
+    phone: Option<String>,
+    created_by: Option<Uuid>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    #[serde(skip_serializing)]
+    dek_wrapped: Option<Vec<u8>>,
+    dek_version: Option<i32>,
+    #[serde(skip_serializing)]
+    national_id_enc: Option<Vec<u8>>,
+    #[serde(skip_serializing)]
+    diagnoses_enc: Option<Vec<u8>>,
+    #[serde(skip_serializing)]
+    notes_enc: Option<Vec<u8>>,
+}
+
+/// AAD binding a sealed value to its row and column.
+fn patient_aad(id: Uuid, column: &str) -> String {
+    format!("patients:{id}:{column}")
+}
+
+impl PatientRecord {
+    fn data_key(&self, keys: &KeyRing) -> Result<Option<DataKey>, DbError> {
+        match (&self.dek_wrapped, self.dek_version) {
+            (Some(wrapped), Some(version)) => Ok(Some(keys.unwrap(
+                version,
+                wrapped,
+                &patient_aad(self.id, "dek"),
+            )?)),
+            _ => Ok(None),
+        }
+    }
+
+    fn decrypt(self, keys: &KeyRing) -> Result<PatientRow, DbError> {
+        let dek = self.data_key(keys)?;
+        let open = |sealed: &Option<Vec<u8>>, column: &str| -> Result<Option<String>, DbError> {
+            match (sealed, &dek) {
+                (Some(sealed), Some(dek)) => {
+                    Ok(Some(dek.open(sealed, &patient_aad(self.id, column))?))
+                }
+                _ => Ok(None),
+            }
+        };
+        let national_id = match open(&self.national_id_enc, "national_id")? {
+            Some(v) => Some(v),
+            None => self.national_id.clone(),
+        };
+        Ok(PatientRow {
+            id: self.id,
+            org_id: self.org_id,
+            first_name: self.first_name.clone(),
+            last_name: self.last_name.clone(),
+            national_id,
+            birth_date: self.birth_date,
+            gender: self.gender.clone(),
+            phone: self.phone.clone(),
+            diagnoses: open(&self.diagnoses_enc, "diagnoses")?,
+            notes: open(&self.notes_enc, "notes")?,
+            created_by: self.created_by,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        })
+    }
+}
+
+/// A patient with sensitive fields decrypted.
+#[derive(Debug, Clone, Serialize)]
+pub struct PatientRow {
+    pub id: Uuid,
+    pub org_id: i32,
+    pub first_name: String,
+    pub last_name: String,
+    pub national_id: Option<String>,
+    pub birth_date: Option<NaiveDate>,
+    pub gender: Option<String>,
+    pub phone: Option<String>,
+    pub diagnoses: Option<String>,
+    pub notes: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+pub struct NewPatient<'a> {
+    pub first_name: &'a str,
+    pub last_name: &'a str,
+    pub national_id: Option<&'a str>,
+    pub birth_date: Option<NaiveDate>,
+    pub gender: Option<&'a str>,
+    pub phone: Option<&'a str>,
+    pub diagnoses: Option<&'a str>,
+    pub notes: Option<&'a str>,
+    pub created_by: Uuid,
+}
+
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn insert_patient(
+    t: &TenantDb,
+    keys: &KeyRing,
+    ctx: &AuditContext,
+    p: &NewPatient<'_>,
+) -> Result<PatientRow, DbError> {
+    let id = Uuid::new_v4();
+    let (dek, wrapped) = keys.new_data_key(&patient_aad(id, "dek"))?;
+    let seal = |value: Option<&str>, column: &str| {
+        value
+            .map(|v| dek.seal(v, &patient_aad(id, column)))
+            .transpose()
+    };
+
+    let mut tx = t.begin().await?;
+    let rec = sqlx::query_as::<_, PatientRecord>(
+        r#"INSERT INTO patients
+               (id, org_id, first_name, last_name, birth_date, gender, phone, created_by,
+                dek_wrapped, dek_version, national_id_enc, national_id_bidx, diagnoses_enc, notes_enc)
+           VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,$13,$14)
+           RETURNING *"#,
+    )
+    .bind(id)
+    .bind(t.org_id())
+    .bind(p.first_name)
+    .bind(p.last_name)
+    .bind(p.birth_date)
+    .bind(p.gender)
+    .bind(p.phone)
+    .bind(p.created_by)
+    .bind(&wrapped)
+    .bind(keys.current_version())
+    .bind(seal(p.national_id, "national_id")?)
+    .bind(p.national_id.map(|n| keys.blind_index(NATIONAL_ID_INDEX, n)))
+    .bind(seal(p.diagnoses, "diagnoses")?)
+    .bind(seal(p.notes, "notes")?)
+    .fetch_one(&mut *tx)
+    .await?;
+    let ev = AuditEvent::new("create", "patient", id).with_diff(None::<&PatientRecord>, Some(&rec));
+    audit::record_in(&mut tx, ctx, ev).await?;
+    tx.commit().await?;
+    rec.decrypt(keys)
+}
+
+/// Owning organization of a patient, for access checks before the audited
+/// read. Comes from the unscoped `patient_orgs` directory.
+#[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+pub async fn patient_org(db: &Db, id: Uuid) -> Result<Option<i32>, DbError> {
+    let org = sqlx::query_scalar::<_, i32>("SELECT org_id FROM patient_orgs WHERE patient_id=$1")
+        .bind(id)
+        .fetch_optional(&db.0)
+        .await?;
+    Ok(org)
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+pub async fn get_patient(
+    t: &TenantDb,
+    keys: &KeyRing,
+    ctx: &AuditContext,
+    id: Uuid,
+) -> Result<Option<PatientRow>, DbError> {
+    let mut tx = t.begin().await?;
+    let rec =
+        sqlx::query_as::<_, PatientRecord>("SELECT * FROM patients WHERE id=$1 AND org_id=$2")
+            .bind(id)
+            .bind(t.org_id())
+            .fetch_optional(&mut *tx)
+            .await?;
+    let Some(rec) = rec else {
+        return Ok(None);
+    };
+    tx.commit().await?;
+    audit::record(t.db(), ctx, AuditEvent::new("read", "patient", id)).await?;
+    Ok(Some(rec.decrypt(keys)?))
+}
+
+pub const PATIENT_SORTS: [&str; 2] = ["last_name", "created_at"];
+
+/// Patients of the tenant, optionally only those with the given national ID
+/// (matched through the blind index). `page.q` matches first or last name.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn list_patients(
+    t: &TenantDb,
+    keys: &KeyRing,
+    ctx: &AuditContext,
+    national_id: Option<&str>,
+    page: &PageRequest,
+) -> Result<Page<PatientRow>, DbError> {
+    let (column, sql_type) = match page.sort.field.as_str() {
+        "created_at" => ("created_at", "timestamptz"),
+        _ => ("last_name", "text"),
+    };
+    let (dir, cmp) = if page.sort.desc {
+        ("DESC", "<")
+    } else {
+        ("ASC", ">")
+    };
+    let bidx = national_id.map(|n| keys.blind_index(NATIONAL_ID_INDEX, n));
+
+    let mut qb = sqlx::QueryBuilder::<Postgres>::new("SELECT * FROM patients WHERE org_id = ");
+    qb.push_bind(t.org_id());
+    if let Some(bidx) = &bidx {
+        qb.push(" AND national_id_bidx = ").push_bind(bidx.clone());
+    }
+    if let Some(q) = &page.q {
+        let pattern = like_pattern(q);
+        qb.push(" AND (first_name ILIKE ")
+            .push_bind(pattern.clone())
+            .push(" OR last_name ILIKE ")
+            .push_bind(pattern)
+            .push(")");
+    }
+    if let Some(from) = page.created_from {
+        qb.push(" AND created_at >= ").push_bind(from);
+    }
+    if let Some(to) = page.created_to {
+        qb.push(" AND created_at < ").push_bind(to);
+    }
+    if let Some(after) = &page.after {
+        qb.push(format_args!(" AND ({column}, id) {cmp} ("))
+            .push_bind(&after.value)
+            .push(format_args!("::{sql_type}, "))
+            .push_bind(after.id)
+            .push(")");
+    }
+    qb.push(format_args!(" ORDER BY {column} {dir}, id {dir} LIMIT "))
+        .push_bind(page.limit + 1);
+
+    let mut tx = t.begin().await?;
+    let recs = qb
+        .build_query_as::<PatientRecord>()
+        .fetch_all(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    let recs = Page::from_rows(recs, page, |r| {
+        let value = match page.sort.field.as_str() {
+            "created_at" => r.created_at.to_rfc3339_opts(SecondsFormat::Micros, true),
+            _ => r.last_name.clone(),
+        };
+        (value, r.id)
+    });
+    let ev = AuditEvent {
+        action: "list",
+        resource_type: "patient",
+        resource_id: None,
+        diff: Some(serde_json::json!({
+            "org_id": t.org_id(),
+            "by_national_id": bidx.is_some(),
+            "ids": recs.items.iter().map(|r| r.id).collect::<Vec<_>>(),
+        })),
+    };
+    audit::record(t.db(), ctx, ev).await?;
+    Ok(Page {
+        items: recs
+            .items
+            .into_iter()
+            .map(|r| r.decrypt(keys))
+            .collect::<Result<_, _>>()?,
+        next_cursor: recs.next_cursor,
+    })
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct ReencryptReport {
+    pub scanned: u64,
+    /// Data keys rewrapped under the current master key.
+    pub rewrapped: u64,
+    /// Rows whose fields were sealed under a fresh data key.
+    pub resealed: u64,
+    /// Legacy plaintext national IDs moved into the encrypted column.
+    pub migrated_plaintext: u64,
+}
+
+/// Brings every patient row onto the current master key. Rows with a stale
+/// `dek_version` get their data key rewrapped; rows without a data key or with
+/// a legacy plaintext national ID are sealed from scratch. With
+/// `fresh_data_keys` every row gets a new data key and fresh ciphertexts.
+/// `org_id` limits the run to one organization; otherwise every organization in
+/// the `patient_orgs` directory is done in turn.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn reencrypt_patients(
+    db: &Db,
+    keys: &KeyRing,
+    org_id: Option<i32>,
+    batch: i64,
+    fresh_data_keys: bool,
+) -> Result<ReencryptReport, DbError> {
+    let orgs = match org_id {
+        Some(org_id) => vec![org_id],
+        None => {
+            sqlx::query_scalar::<_, i32>("SELECT DISTINCT org_id FROM patient_orgs ORDER BY 1")
+                .fetch_all(&db.0)
+                .await?
+        }
+    };
+    let mut report = ReencryptReport::default();
+    for org_id in orgs {
+        reencrypt_org(
+            &db.tenant(org_id),
+            keys,
+            batch,
+            fresh_data_keys,
+            &mut report,
+        )
+        .await?;
+    }
+    Ok(report)
+}
+
+async fn reencrypt_org(
+    t: &TenantDb,
+    keys: &KeyRing,
+    batch: i64,
+    fresh_data_keys: bool,
+    report: &mut ReencryptReport,
+) -> Result<(), DbError> {
+    let mut last_id: Option<Uuid> = None;
+    loop {
+        let mut tx = t.begin().await?;
+        let recs = sqlx::query_as::<_, PatientRecord>(
+            r#"SELECT * FROM patients
+               WHERE ($1::uuid IS NULL OR id > $1)
+                 AND org_id = $2
+               ORDER BY id
+               LIMIT $3
+               FOR UPDATE"#,
+        )
+        .bind(last_id)
+        .bind(t.org_id())
+        .bind(batch)
+        .fetch_all(&mut *tx)
+        .await?;
+        let Some(last) = recs.last() else {
+            break;
+        };
+        last_id = Some(last.id);
+
+        for rec in recs {
+            report.scanned += 1;
+            let legacy_plaintext = rec.national_id.is_some() && rec.national_id_enc.is_none();
+            let needs_seal = fresh_data_keys
+                || rec.dek_wrapped.is_none()
+                || rec.dek_version.is_none()
+                || rec.national_id.is_some();
+            if needs_seal {
+                let plain = rec.decrypt(keys)?;
+                let (dek, wrapped) = keys.new_data_key(&patient_aad(plain.id, "dek"))?;
+                let seal = |value: &Option<String>, column: &str| {
+                    value
+                        .as_deref()
+                        .map(|v| dek.seal(v, &patient_aad(plain.id, column)))
+                        .transpose()
+                };
+                sqlx::query(
+                    r#"UPDATE patients
+                       SET national_id = NULL, dek_wrapped = $2, dek_version = $3,
+                           national_id_enc = $4, national_id_bidx = $5,
+                           diagnoses_enc = $6, notes_enc = $7
+                       WHERE id = $1"#,
+                )
+                .bind(plain.id)
+                .bind(&wrapped)
+                .bind(keys.current_version())
+                .bind(seal(&plain.national_id, "national_id")?)
+                .bind(
+                    plain
+                        .national_id
+                        .as_deref()
+                        .map(|n| keys.blind_index(NATIONAL_ID_INDEX, n)),
+                )
+                .bind(seal(&plain.diagnoses, "diagnoses")?)
+                .bind(seal(&plain.notes, "notes")?)
+                .execute(&mut *tx)
+                .await?;
+                report.resealed += 1;
+                if legacy_plaintext {
+                    report.migrated_plaintext += 1;
+                }
+            } else if rec.dek_version != Some(keys.current_version()) {
+                let dek = rec.data_key(keys)?.expect("checked above");
+                let wrapped = keys.wrap(&dek, &patient_aad(rec.id, "dek"))?;
+                sqlx::query("UPDATE patients SET dek_wrapped = $2, dek_version = $3 WHERE id = $1")
+                    .bind(rec.id)
+                    .bind(&wrapped)
+                    .bind(keys.current_version())
+                    .execute(&mut *tx)
+                    .await?;
+                report.rewrapped += 1;
+            }
+        }
+        tx.commit().await?;
+    }
+    Ok(())
+}
+
+// ==== Patient consents ====
+
+#[derive(sqlx::FromRow, Debug, Clone, Serialize)]
+pub struct ConsentRow {
+    pub id: Uuid,
+    pub patient_id: Uuid,
+    pub kind: String,
+    pub scope_org_id: Option<i32>,
+    pub version: i32,
+    pub form_version: String,
+    pub signed_by: String,
+    pub signature: String,
+    pub signed_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub recorded_by: Option<Uuid>,
+    pub withdrawn_at: Option<DateTime<Utc>>,
+    pub withdrawn_reason: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct NewConsent<'a> {
+    pub patient_id: Uuid,
+    /// One of `treatment`, `data_sharing`, `research`.
+    pub kind: &'a str,
+    pub scope_org_id: Option<i32>,
+    pub form_version: &'a str,
+    pub signed_by: &'a str,
+    pub signature: &'a str,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub recorded_by: Uuid,
+}
+
+/// Records a new consent version; it supersedes earlier versions of the same
+/// kind and scope.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn insert_consent(
+    t: &TenantDb,
+    ctx: &AuditContext,
+    c: &NewConsent<'_>,
+) -> Result<ConsentRow, DbError> {
+    let mut tx = t.begin().await?;
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1::text))")
+        .bind(c.patient_id)
+        .execute(&mut *tx)
+        .await?;
+    let row = sqlx::query_as::<_, ConsentRow>(
+        r#"INSERT INTO patient_consents
+               (patient_id, kind, scope_org_id, version, form_version, signed_by, signature,
+                expires_at, recorded_by)
+           SELECT $1, $2, $3, COALESCE(MAX(version), 0) + 1, $4, $5, $6, $7, $8
+           FROM patient_consents
+           WHERE patient_id = $1 AND kind = $2 AND scope_org_id IS NOT DISTINCT FROM $3
+           RETURNING *"#,
+    )
+    .bind(c.patient_id)
+    .bind(c.kind)
+    .bind(c.scope_org_id)
+    .bind(c.form_version)
+    .bind(c.signed_by)
+    .bind(c.signature)
+    .bind(c.expires_at)
+    .bind(c.recorded_by)
+    .fetch_one(&mut *tx)
+    .await?;
+    let ev =
+        AuditEvent::new("create", "consent", row.id).with_diff(None::<&ConsentRow>, Some(&row));
+    audit::record_in(&mut tx, ctx, ev).await?;
+    tx.commit().await?;
+    Ok(row)
+}
+
+/// Full history, newest first, withdrawn versions included.
+#[tracing::instrument(level = "debug", skip_all, fields(patient_id = %patient_id))]
+pub async fn list_consents(t: &TenantDb, patient_id: Uuid) -> Result<Vec<ConsentRow>, DbError> {
+    let mut tx = t.begin().await?;
+    let rows = sqlx::query_as::<_, ConsentRow>(
+        "SELECT * FROM patient_consents WHERE patient_id=$1 ORDER BY kind, signed_at DESC, version DESC",
+    )
+    .bind(patient_id)
+    .fetch_all(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(rows)
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(patient_id = %patient_id, id = %id))]
+pub async fn withdraw_consent(
+    t: &TenantDb,
+    ctx: &AuditContext,
+    patient_id: Uuid,
+    id: Uuid,
+    reason: Option<&str>,
+) -> Result<Option<ConsentRow>, DbError> {
+    let mut tx = t.begin().await?;
+    let row = sqlx::query_as::<_, ConsentRow>(
+        r#"UPDATE patient_consents SET withdrawn_at = NOW(), withdrawn_reason = $3
+           WHERE id = $1 AND patient_id = $2 AND withdrawn_at IS NULL
+           RETURNING *"#,
+    )
+    .bind(id)
+    .bind(patient_id)
+    .bind(reason)
+    .fetch_optional(&mut *tx)
+    .await?;
+    if let Some(row) = &row {
+        let ev =
+            AuditEvent::new("withdraw", "consent", id).with_diff(None::<&ConsentRow>, Some(row));
+        audit::record_in(&mut tx, ctx, ev).await?;
+    }
+    tx.commit().await?;
+    Ok(row)
+}
+
+/// Whether the newest consent of `kind` covering `org_id` (an org-specific one or
+/// an "any org" one) is in force: not withdrawn and not expired. `t` is the
+/// patient's own organization, which holds the consent records.
+#[tracing::instrument(level = "debug", skip_all, fields(patient_id = %patient_id))]
+pub async fn has_active_consent(
+    t: &TenantDb,
+    patient_id: Uuid,
+    kind: &str,
+    org_id: Option<i32>,
+) -> Result<bool, DbError> {
+    let mut tx = t.begin().await?;
+    let ok = sqlx::query_scalar::<_, bool>(
+        r#"SELECT EXISTS (
+               SELECT 1 FROM (
+                   SELECT DISTINCT ON (scope_org_id) *
+                   FROM patient_consents
+                   WHERE patient_id = $1 AND kind = $2
+                     AND (scope_org_id IS NULL OR scope_org_id = $3)
+                   ORDER BY scope_org_id, version DESC
+               ) latest
+               WHERE withdrawn_at IS NULL AND (expires_at IS NULL OR expires_at > NOW())
+           )"#,
+    )
+    .bind(patient_id)
+    .bind(kind)
+    .bind(org_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(ok)
+}
+
+// ==== Patient documents ====
+
+#[derive(sqlx::FromRow, Debug, Clone, Serialize)]
+pub struct DocumentRow {
+    pub id: Uuid,
+    pub patient_id: Uuid,
+    pub org_id: i32,
+    pub kind: String,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub sha256: String,
+    #[serde(skip_serializing)]
+    pub storage_key: String,
+    pub uploaded_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub deleted_by: Option<Uuid>,
+}
+
+#[derive(Debug)]
+pub struct NewDocument<'a> {
+    pub id: Uuid,
+    pub patient_id: Uuid,
+    pub kind: &'a str,
+    pub file_name: &'a str,
+    pub content_type: &'a str,
+    pub size_bytes: i64,
+    pub sha256: &'a str,
+    pub storage_key: &'a str,
+    pub uploaded_by: Uuid,
+}
+
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn insert_document(
+    t: &TenantDb,
+    ctx: &AuditContext,
+    d: &NewDocument<'_>,
+) -> Result<DocumentRow, DbError> {
+    let mut tx = t.begin().await?;
+    let row = sqlx::query_as::<_, DocumentRow>(
+        r#"INSERT INTO patient_documents
+               (id, patient_id, org_id, kind, file_name, content_type, size_bytes, sha256,
+                storage_key, uploaded_by)
+           VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10)
+           RETURNING *"#,
+    )
+    .bind(d.id)
+    .bind(d.patient_id)
+    .bind(t.org_id())
+    .bind(d.kind)
+    .bind(d.file_name)
+    .bind(d.content_type)
+    .bind(d.size_bytes)
+    .bind(d.sha256)
+    .bind(d.storage_key)
+    .bind(d.uploaded_by)
+    .fetch_one(&mut *tx)
+    .await?;
+    let ev =
+        AuditEvent::new("create", "document", row.id).with_diff(None::<&DocumentRow>, Some(&row));
+    audit::record_in(&mut tx, ctx, ev).await?;
+    tx.commit().await?;
+    Ok(row)
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(patient_id = %patient_id))]
+pub async fn list_documents(t: &TenantDb, patient_id: Uuid) -> Result<Vec<DocumentRow>, DbError> {
+    let mut tx = t.begin().await?;
+    let rows = sqlx::query_as::<_, DocumentRow>(
+        "SELECT * FROM patient_documents
+         WHERE patient_id=$1 AND org_id=$2 AND deleted_at IS NULL
+         ORDER BY created_at DESC",
+    )
+    .bind(patient_id)
+    .bind(t.org_id())
+    .fetch_all(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(rows)
+}
+
+/// Looks up a document for download; the read is audited.
+#[tracing::instrument(level = "debug", skip_all, fields(patient_id = %patient_id, id = %id))]
+pub async fn get_document(
+    t: &TenantDb,
+    ctx: &AuditContext,
+    patient_id: Uuid,
+    id: Uuid,
+) -> Result<Option<DocumentRow>, DbError> {
+    let mut tx = t.begin().await?;
+    let row = sqlx::query_as::<_, DocumentRow>(
+        "SELECT * FROM patient_documents
+         WHERE id=$1 AND patient_id=$2 AND org_id=$3 AND deleted_at IS NULL",
+    )
+    .bind(id)
+    .bind(patient_id)
+    .bind(t.org_id())
+    .fetch_optional(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    if row.is_some() {
+        audit::record(t.db(), ctx, AuditEvent::new("read", "document", id)).await?;
+    }
+    Ok(row)
+}
+
+/// Soft delete; the blob is kept so the document can be restored.
+#[tracing::instrument(level = "debug", skip_all, fields(patient_id = %patient_id, id = %id, deleted_by = %deleted_by))]
+pub async fn delete_document(
+    t: &TenantDb,
+    ctx: &AuditContext,
+    patient_id: Uuid,
+    id: Uuid,
+    deleted_by: Uuid,
+) -> Result<Option<DocumentRow>, DbError> {
+    let mut tx = t.begin().await?;
+    let row = sqlx::query_as::<_, DocumentRow>(
+        r#"UPDATE patient_documents SET deleted_at=NOW(), deleted_by=$3
+           WHERE id=$1 AND patient_id=$2 AND org_id=$4 AND deleted_at IS NULL
+           RETURNING *"#,
+    )
+    .bind(id)
+    .bind(patient_id)
+    .bind(deleted_by)
+    .bind(t.org_id())
+    .fetch_optional(&mut *tx)
+    .await?;
+    if let Some(row) = &row {
+        let ev =
+            AuditEvent::new("delete", "document", id).with_diff(None::<&DocumentRow>, Some(row));
+        audit::record_in(&mut tx, ctx, ev).await?;
+    }
+    tx.commit().await?;
+    Ok(row)
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(patient_id = %patient_id, id = %id))]
+pub async fn restore_document(
+    t: &TenantDb,
+    ctx: &AuditContext,
+    patient_id: Uuid,
+    id: Uuid,
+) -> Result<Option<DocumentRow>, DbError> {
+    let mut tx = t.begin().await?;
+    let row = sqlx::query_as::<_, DocumentRow>(
+        r#"UPDATE patient_documents SET deleted_at=NULL, deleted_by=NULL
+           WHERE id=$1 AND patient_id=$2 AND org_id=$3 AND deleted_at IS NOT NULL
+           RETURNING *"#,
+    )
+    .bind(id)
+    .bind(patient_id)
+    .bind(t.org_id())
+    .fetch_optional(&mut *tx)
+    .await?;
+    if let Some(row) = &row {
+        let ev =
+            AuditEvent::new("restore", "document", id).with_diff(None::<&DocumentRow>, Some(row));
+        audit::record_in(&mut tx, ctx, ev).await?;
+    }
+    tx.commit().await?;
+    Ok(row)
+}
+
+// ==== Items ====
+// Items hold patient data: every read and mutation is written to the audit log.
+// They are tenant-owned, so all functions go through `TenantDb`. Deleting sets
+// `deleted_at`; deleted items are invisible except to the admin trash listing.
+
+/// Sortable item columns and their SQL types, for cursor comparison.
+pub const ITEM_SORTS: [&str; 3] = ["created_at", "updated_at", "title"];
+
+fn item_sort_column(field: &str) -> (&'static str, &'static str) {
+    match field {
+        "updated_at" => ("updated_at", "timestamptz"),
+        "title" => ("title", "text"),
+        _ => ("created_at", "timestamptz"),
+    }
+}
+
+fn item_sort_value(row: &ItemRow, field: &str) -> String {
+    match field {
+        "updated_at" => row.updated_at.to_rfc3339_opts(SecondsFormat::Micros, true),
+        "title" => row.title.clone(),
+        _ => row.created_at.to_rfc3339_opts(SecondsFormat::Micros, true),
+    }
+}
+
+/// `%`, `_` and `\` are literal in the search text.
+fn like_pattern(q: &str) -> String {
+    let escaped = q
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{escaped}%")
+}
+
+/// Live items, or with `deleted` only the soft-deleted ones.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn list_items(
+    t: &TenantDb,
+    ctx: &AuditContext,
+    owner: Option<Uuid>,
+    deleted: bool,
+    page: &PageRequest,
+) -> Result<Page<ItemRow>, DbError> {
+    let (column, sql_type) = item_sort_column(&page.sort.field);
+    let (dir, cmp) = if page.sort.desc {
+        ("DESC", "<")
+    } else {
+        ("ASC", ">")
+    };
+
+    let mut qb = sqlx::QueryBuilder::<Postgres>::new("SELECT * FROM items WHERE org_id = ");
+    qb.push_bind(t.org_id());
+    qb.push(if deleted {
+        " AND deleted_at IS NOT NULL"
+    } else {
+        " AND deleted_at IS NULL"
+    });
+    if let Some(owner_id) = owner {
+        qb.push(" AND owner_id = ").push_bind(owner_id);
+    }
+    if let Some(q) = &page.q {
+        qb.push(" AND title ILIKE ").push_bind(like_pattern(q));
+    }
+    if let Some(from) = page.created_from {
+        qb.push(" AND created_at >= ").push_bind(from);
+    }
+    if let Some(to) = page.created_to {
+        qb.push(" AND created_at < ").push_bind(to);
+    }
+    if let Some(after) = &page.after {
+        qb.push(format_args!(" AND ({column}, id) {cmp} ("))
+            .push_bind(&after.value)
+            .push(format_args!("::{sql_type}, "))
+            .push_bind(after.id)
+            .push(")");
+    }
+    qb.push(format_args!(" ORDER BY {column} {dir}, id {dir} LIMIT "))
+        .push_bind(page.limit + 1);
+
+    let mut tx = t.begin().await?;
+    let rows = qb.build_query_as::<ItemRow>().fetch_all(&mut *tx).await?;
+    let page = Page::from_rows(rows, page, |r| (item_sort_value(r, &page.sort.field), r.id));
+    let ev = AuditEvent {
+        action: "list",
+        resource_type: "item",
+        resource_id: None,
+        diff: Some(serde_json::json!({
+            "org_id": t.org_id(),
+            "owner_id": owner,
+            "deleted": deleted,
+            "ids": page.items.iter().map(|r| r.id).collect::<Vec<_>>(),
+        })),
+    };
+    tx.commit().await?;
+    audit::record(t.db(), ctx, ev).await?;
+    Ok(page)
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+pub async fn get_item(
+    t: &TenantDb,
+    ctx: &AuditContext,
+    id: Uuid,
+) -> Result<Option<ItemRow>, DbError> {
+    let mut tx = t.begin().await?;
+    let row = sqlx::query_as::<_, ItemRow>(
+        "SELECT * FROM items WHERE id=$1 AND org_id=$2 AND deleted_at IS NULL",
+    )
+    .bind(id)
+    .bind(t.org_id())
+    .fetch_optional(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    if row.is_some() {
+        audit::record(t.db(), ctx, AuditEvent::new("read", "item", id)).await?;
+    }
+    Ok(row)
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(owner_id = %owner_id))]
+pub async fn insert_item(
+    t: &TenantDb,
+    ctx: &AuditContext,
+    owner_id: Uuid,
+    title: &str,
+    description: Option<&str>,
+) -> Result<ItemRow, DbError> {
+    let mut tx = t.begin().await?;
+    let row = sqlx::query_as::<_, ItemRow>(
+        r#"INSERT INTO items (owner_id,org_id,title,description)
+           VALUES ($1,$2,$3,$4)
+           RETURNING *"#,
+    )
+    .bind(owner_id)
+    .bind(t.org_id())
+    .bind(title)
+    .bind(description)
+    .fetch_one(&mut *tx)
+    .await?;
+    let ev = AuditEvent::new("create", "item", row.id).with_diff(None::<&ItemRow>, Some(&row));
+    audit::record_in(&mut tx, ctx, ev).await?;
+    tx.commit().await?;
+    Ok(row)
+}
+
+/// Who is modifying an item. Owners, heads of the owner's department and
+/// admins may; everyone else in the org gets [`ItemWrite::Forbidden`].
+#[derive(Debug, Clone, Copy)]
+pub struct ItemEditor {
+    pub user_id: Uuid,
+    pub is_admin: bool,
+    pub is_department_head: bool,
+}
+
+#[derive(Debug)]
+pub enum ItemWrite<T> {
+    Done(T),
+    NotFound,
+    Forbidden,
+    /// The caller's `If-Match` versions do not include the current one; carries
+    /// the current row.
+    Stale(T),
+}
+
+/// Locks the item and checks `editor` against it.
+async fn lock_item_for(
+    conn: &mut sqlx::PgConnection,
+    t: &TenantDb,
+    editor: &ItemEditor,
+    id: Uuid,
+) -> Result<ItemWrite<ItemRow>, DbError> {
+    let Some(item) = sqlx::query_as::<_, ItemRow>(
+        "SELECT * FROM items WHERE id=$1 AND org_id=$2 AND deleted_at IS NULL FOR UPDATE",
+    )
+    .bind(id)
+    .bind(t.org_id())
+    .fetch_optional(&mut *conn)
+    .await?
+    else {
+        return Ok(ItemWrite::NotFound);
+    };
+    if editor.is_admin || item.owner_id == editor.user_id {
+        return Ok(ItemWrite::Done(item));
+    }
+    if editor.is_department_head {
+        let same_department = sqlx::query_scalar::<_, bool>(
+            r#"SELECT EXISTS (
+                   SELECT 1 FROM doctor_user o
+                   JOIN doctor_user h ON h.id = $2
+                   WHERE o.id = $1 AND o.org_id = h.org_id
+                     AND o.department IS NOT NULL AND o.department = h.department
+               )"#,
+        )
+        .bind(item.owner_id)
+        .bind(editor.user_id)
+        .fetch_one(&mut *conn)
+        .await?;
+        if same_department {
+            return Ok(ItemWrite::Done(item));
+        }
+    }
+    Ok(ItemWrite::Forbidden)
+}
+
+/// Full replacement of the editable fields.
+#[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+pub async fn update_item(
+    t: &TenantDb,
+    ctx: &AuditContext,
+    editor: &ItemEditor,
+    id: Uuid,
+    if_match: &[i64],
+    title: &str,
+    description: Option<&str>,
+) -> Result<ItemWrite<ItemRow>, DbError> {
+    let changes = ItemChanges {
+        title: Some(title),
+        description: Some(description),
+    };
+    patch_item(t, ctx, editor, id, if_match, &changes).await
+}
+
+/// Partial item update. `None` leaves a column untouched; `Some(None)` clears
+/// a nullable one.
+#[derive(Debug, Default)]
+pub struct ItemChanges<'a> {
+    pub title: Option<&'a str>,
+    pub description: Option<Option<&'a str>>,
+}
+
+/// Applies only when the item is still at one of the `if_match` versions.
+/// An empty change set returns the item as is, without bumping its version.
+#[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+pub async fn patch_item(
+    t: &TenantDb,
+    ctx: &AuditContext,
+    editor: &ItemEditor,
+    id: Uuid,
+    if_match: &[i64],
+    changes: &ItemChanges<'_>,
+) -> Result<ItemWrite<ItemRow>, DbError> {
+    let mut tx = t.begin().await?;
+    let before = match lock_item_for(&mut tx, t, editor, id).await? {
+        ItemWrite::Done(item) if !if_match.contains(&item.version) => {
+            return Ok(ItemWrite::Stale(item));
+        }
+        ItemWrite::Done(item) => item,
+        other => return Ok(other),
+    };
+    if changes.title.is_none() && changes.description.is_none() {
+        return Ok(ItemWrite::Done(before));
+    }
+    let row = sqlx::query_as::<_, ItemRow>(
+        r#"UPDATE items SET
+               title       = COALESCE($2, title),
+               description = CASE WHEN $3 THEN $4 ELSE description END,
+               updated_at  = NOW()
+           WHERE id=$1
+           RETURNING *"#,
+    )
+    .bind(id)
+    .bind(changes.title)
+    .bind(changes.description.is_some())
+    .bind(changes.description.flatten())
+    .fetch_one(&mut *tx)
+    .await?;
+    let ev = AuditEvent::new("update", "item", id).with_diff(Some(&before), Some(&row));
+    audit::record_in(&mut tx, ctx, ev).await?;
+    tx.commit().await?;
+    Ok(ItemWrite::Done(row))
+}
+
+/// Soft delete: the row stays, marked with who deleted it and when.
+#[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+pub async fn delete_item(
+    t: &TenantDb,
+    ctx: &AuditContext,
+    editor: &ItemEditor,
+    id: Uuid,
+) -> Result<ItemWrite<ItemRow>, DbError> {
+    let mut tx = t.begin().await?;
+    let before = match lock_item_for(&mut tx, t, editor, id).await? {
+        ItemWrite::Done(item) => item,
+        other => return Ok(other),
+    };
+    let row = sqlx::query_as::<_, ItemRow>(
+        "UPDATE items SET deleted_at=NOW(), deleted_by=$2 WHERE id=$1 RETURNING *",
+    )
+    .bind(id)
+    .bind(editor.user_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    let ev = AuditEvent::new("delete", "item", id).with_diff(Some(&before), Some(&row));
+    audit::record_in(&mut tx, ctx, ev).await?;
+    tx.commit().await?;
+    Ok(ItemWrite::Done(row))
+}
+
+/// Undoes a soft delete. `None` when the item does not exist or is not deleted.
+#[tracing::instrument(level = "debug", skip_all, fields(id = %id))]
+pub async fn restore_item(
+    t: &TenantDb,
+    ctx: &AuditContext,
+    id: Uuid,
+) -> Result<Option<ItemRow>, DbError> {
+    let mut tx = t.begin().await?;
+    let Some(before) = sqlx::query_as::<_, ItemRow>(
+        "SELECT * FROM items WHERE id=$1 AND org_id=$2 AND deleted_at IS NOT NULL FOR UPDATE",
+    )
+    .bind(id)
+    .bind(t.org_id())
+    .fetch_optional(&mut *tx)
+    .await?
+    else {
+        return Ok(None);
+    };
+    let row = sqlx::query_as::<_, ItemRow>(
+        r#"UPDATE items SET deleted_at=NULL, deleted_by=NULL, updated_at=NOW()
+           WHERE id=$1
+           RETURNING *"#,
+    )
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await?;
+    let ev = AuditEvent::new("restore", "item", id).with_diff(Some(&before), Some(&row));
+    audit::record_in(&mut tx, ctx, ev).await?;
+    tx.commit().await?;
+    Ok(Some(row))
+}
+
+// ==== Bulk item import / export ====
+// Postgres refuses `COPY FROM` into tables with row-level security, so rows are
+// copied into a per-transaction staging table and moved into `items` with a
+// single `INSERT ... SELECT`, which the tenant policy checks like any insert.
+
+/// Rows buffered before each `COPY` round trip.
+pub const IMPORT_BATCH: usize = 1000;
+
+/// One import, all in a single transaction: dropping it without
+/// [`ItemImport::commit`] rolls every staged row back.
+pub struct ItemImport {
+    tx: Transaction<'static, Postgres>,
+    org_id: i32,
+    owner_id: Uuid,
+    buf: Vec<u8>,
+    pending: usize,
+}
+
+/// Text-format `COPY` field: backslash escapes, `\N` for NULL.
+fn push_copy_field(buf: &mut Vec<u8>, value: Option<&str>) {
+    let Some(value) = value else {
+        buf.extend_from_slice(b"\\N");
+        return;
+    };
+    for b in value.bytes() {
+        match b {
+            b'\\' => buf.extend_from_slice(b"\\\\"),
+            b'\t' => buf.extend_from_slice(b"\\t"),
+            b'\n' => buf.extend_from_slice(b"\\n"),
+            b'\r' => buf.extend_from_slice(b"\\r"),
+            _ => buf.push(b),
+        }
+    }
+}
+
+impl ItemImport {
+    pub async fn begin(t: &TenantDb, owner_id: Uuid) -> Result<Self, DbError> {
+        let mut tx = t.begin().await?;
+        sqlx::query(
+            r#"CREATE TEMP TABLE item_import (
+                   seq BIGSERIAL,
+                   title TEXT NOT NULL,
+                   description TEXT
+               ) ON COMMIT DROP"#,
+        )
+        .execute(&mut *tx)
+        .await?;
+        Ok(Self {
+            tx,
+            org_id: t.org_id(),
+            owner_id,
+            buf: Vec::new(),
+            pending: 0,
+        })
+    }
+
+    /// Stages a row; values must not contain NUL bytes.
+    pub async fn push(&mut self, title: &str, description: Option<&str>) -> Result<(), DbError> {
+        push_copy_field(&mut self.buf, Some(title));
+        self.buf.push(b'\t');
+        push_copy_field(&mut self.buf, description);
+        self.buf.push(b'\n');
+        self.pending += 1;
+        if self.pending >= IMPORT_BATCH {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<(), DbError> {
+        if self.pending == 0 {
+            return Ok(());
+        }
+        let mut copy = self
+            .tx
+            .copy_in_raw("COPY item_import (title, description) FROM STDIN")
+            .await?;
+        copy.send(self.buf.as_slice()).await?;
+        copy.finish().await?;
+        self.buf.clear();
+        self.pending = 0;
+        Ok(())
+    }
+
+    /// Inserts everything staged, in upload order, and returns the new ids.
+    pub async fn commit(mut self, ctx: &AuditContext) -> Result<Vec<Uuid>, DbError> {
+        self.flush().await?;
+        let ids = sqlx::query_scalar::<_, Uuid>(
+            r#"INSERT INTO items (owner_id, org_id, title, description)
+               SELECT $1, $2, title, description FROM item_import ORDER BY seq
+               RETURNING id"#,
+        )
+        .bind(self.owner_id)
+        .bind(self.org_id)
+        .fetch_all(&mut *self.tx)
+        .await?;
+        let ev = AuditEvent {
+            action: "import",
+            resource_type: "item",
+            resource_id: None,
+            diff: Some(serde_json::json!({
+                "org_id": self.org_id,
+                "owner_id": self.owner_id,
+                "ids": ids,
+            })),
+        };
+        audit::record_in(&mut self.tx, ctx, ev).await?;
+        self.tx.commit().await?;
+        Ok(ids)
+    }
+}
+
+/// One page of the owner's live items in creation order, after the
+/// `(created_at, id)` key of the previous page. Each page is audited.
+#[tracing::instrument(level = "debug", skip_all, fields(owner_id = %owner_id))]
+pub async fn export_items(
+    t: &TenantDb,
+    ctx: &AuditContext,
+    owner_id: Uuid,
+    after: Option<(DateTime<Utc>, Uuid)>,
+    limit: i64,
+) -> Result<Vec<ItemRow>, DbError> {
+    let mut tx = t.begin().await?;
+    let rows = sqlx::query_as::<_, ItemRow>(
+        r#"SELECT * FROM items
+           WHERE org_id = $1 AND owner_id = $2 AND deleted_at IS NULL
+             AND ($3::timestamptz IS NULL OR (created_at, id) > ($3, $4))
+           ORDER BY created_at, id
+           LIMIT $5"#,
+    )
+    .bind(t.org_id())
+    .bind(owner_id)
+    .bind(after.map(|(at, _)| at))
+    .bind(after.map(|(_, id)| id))
+    .bind(limit)
+    .fetch_all(&mut *tx)
+    .await?;
+    let ev = AuditEvent {
+        action: "export",
+        resource_type: "item",
+        resource_id: None,
+        diff: Some(serde_json::json!({
+            "org_id": t.org_id(),
+            "owner_id": owner_id,
+            "ids": rows.iter().map(|r| r.id).collect::<Vec<_>>(),
+        })),
+    };
+    tx.commit().await?;
+    audit::record(t.db(), ctx, ev).await?;
+    Ok(rows)
+}
+
+// ==== Full-text search ====
+// Sources are tenant-owned tables with a `search_tsv` column; add new ones as
+// another `UNION ALL` branch of the `sources` CTE.
+
+#[derive(sqlx::FromRow, Debug, Clone, Serialize)]
+pub struct SearchHit {
+    /// Source table, e.g. `item`.
+    pub kind: String,
+    pub id: Uuid,
+    pub title: String,
+    /// HTML-escaped excerpt with matches wrapped in `<mark>`.
+    pub snippet: String,
+    pub rank: f32,
+    pub created_at: DateTime<Utc>,
+}
+
+pub const SEARCH_SORTS: [&str; 1] = ["rank"];
+
+/// Ranked search within the tenant, `page.q` in web search syntax: quoted
+/// phrases, `or`, and `-` to exclude. Ties in rank are broken by id.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn search(
+    t: &TenantDb,
+    ctx: &AuditContext,
+    q: &str,
+    page: &PageRequest,
+) -> Result<Page<SearchHit>, DbError> {
+    let (dir, cmp) = if page.sort.desc {
+        ("DESC", "<")
+    } else {
+        ("ASC", ">")
+    };
+    let after_rank = page
+        .after
+        .as_ref()
+        .and_then(|c| c.value.parse::<f32>().ok());
+    let mut tx = t.begin().await?;
+    let hits = sqlx::query_as::<_, SearchHit>(&format!(
+        r#"WITH query AS (SELECT websearch_to_tsquery('simple', $2) AS q),
+           sources AS (
+               SELECT 'item' AS kind, id, title, coalesce(description, '') AS body,
+                      search_tsv, created_at
+               FROM items
+               WHERE org_id = $1 AND deleted_at IS NULL
+           ),
+           ranked AS (
+               SELECT s.kind, s.id, s.title, s.body, s.created_at,
+                      ts_rank_cd(s.search_tsv, query.q) AS rank
+               FROM sources s, query
+               WHERE s.search_tsv @@ query.q
+                 AND ($6::timestamptz IS NULL OR s.created_at >= $6)
+                 AND ($7::timestamptz IS NULL OR s.created_at < $7)
+           )
+           SELECT r.kind, r.id, r.title,
+                  ts_headline('simple', html_escape(r.title || E'\n' || r.body), query.q,
+                              'StartSel=<mark>, StopSel=</mark>, MaxFragments=2, MaxWords=25, MinWords=8')
+                      AS snippet,
+                  r.rank,
+                  r.created_at
+           FROM ranked r, query
+           WHERE $3::real IS NULL OR (r.rank, r.id) {cmp} ($3, $4)
+           ORDER BY r.rank {dir}, r.id {dir}
+           LIMIT $5"#
+    ))
+    .bind(t.org_id())
+    .bind(q)
+    .bind(after_rank)
+    .bind(page.after.as_ref().map(|c| c.id))
+    .bind(page.limit + 1)
+    .bind(page.created_from)
+    .bind(page.created_to)
+    .fetch_all(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    let hits = Page::from_rows(hits, page, |h| (h.rank.to_string(), h.id));
+    let ev = AuditEvent {
+        action: "search",
+        resource_type: "search",
+        resource_id: None,
+        diff: Some(serde_json::json!({
+            "org_id": t.org_id(),
+            "q": q,
+            "hits": hits.items.iter().map(|h| format!("{}:{}", h.kind, h.id)).collect::<Vec<_>>(),
+        })),
+    };
+    audit::record(t.db(), ctx, ev).await?;
+    Ok(hits)
+}
+
+// ==== Refresh tokens (rotation) ====
+
+#[derive(sqlx::FromRow, Debug, Serialize, Clone)]
+pub struct RefreshRow {
+    pub id: i64,
+    pub doctor_id: Uuid,
+    pub jti: String,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub revoked: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(doctor_id = %doctor_id))]
+pub async fn insert_refresh(
+    db: &Db,
+    doctor_id: Uuid,
+    jti: &str,
+    token_hash: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<(), DbError> {
+    sqlx::query!(
+        r#"
+        INSERT INTO refresh_tokens (doctor_id, jti, token_hash, expires_at)
+        VALUES ($1, $2, $3, $4)
+        "#,
+        doctor_id,
+        jti,
+        token_hash,
+        expires_at
+    )
+    .execute(&db.0)
+    .await?;
+    Ok(())
+}
+
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_refresh_by_jti(db: &Db, jti: &str) -> Result<Option<RefreshRow>, DbError> {
+    let row = sqlx::query_as::<_, RefreshRow>("SELECT * FROM refresh_tokens WHERE jti=$1")
+        .bind(jti)
+        .fetch_optional(&db.0)
+        .await?;
+    Ok(row)
+}
+
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn revoke_refresh(db: &Db, jti: &str) -> Result<u64, DbError> {
+    let res = sqlx::query("UPDATE refresh_tokens SET revoked=true WHERE jti=$1")
+        .bind(jti)
+        .execute(&db.0)
+        .await?;
+    Ok(res.rows_affected())
+}
+
+/// Drops refresh tokens past their expiry. Revoked but unexpired ones stay so
+/// reuse of a rotated token is still recognised as such.
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn purge_expired_refresh_tokens(db: &Db) -> Result<u64, DbError> {
+    let res = sqlx::query("DELETE FROM refresh_tokens WHERE expires_at < NOW()")
         .execute(&db.0)
         .await?;
     Ok(res.rows_affected())
@@ -0,0 +1,227 @@
+//! Blob storage for clinical documents. Only bytes live here; metadata goes to
+//! Postgres. The backend is any `object_store` store: the local filesystem, an
+//! S3-compatible service (AWS, MinIO, ...), or memory for tests.
+
+use bytes::Bytes;
+use futures_util::stream::BoxStream;
+use futures_util::{Stream, StreamExt, TryStreamExt};
+use object_store::aws::AmazonS3Builder;
+use object_store::local::LocalFileSystem;
+use object_store::memory::InMemory;
+use object_store::path::Path;
+use object_store::{ObjectStore, WriteMultipart};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::sync::Arc;
+
+/// Bytes buffered before sniffing the content type; covers every signature
+/// `infer` knows about.
+const SNIFF_LEN: usize = 8 * 1024;
+
+/// Parts uploaded concurrently by a multipart write.
+const MAX_CONCURRENT_PARTS: usize = 4;
+
+#[derive(Debug, Clone)]
+pub enum StorageConfig {
+    Local {
+        root: std::path::PathBuf,
+    },
+    S3 {
+        bucket: String,
+        region: String,
+        /// Custom endpoint for S3-compatible services, e.g. `http://localhost:9000`.
+        endpoint: Option<String>,
+        access_key_id: String,
+        secret_access_key: String,
+        allow_http: bool,
+    },
+    Memory,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum StorageError {
+    #[error("object not found")]
+    NotFound,
+
+    #[error("object store error: {0}")]
+    Store(#[source] object_store::Error),
+
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("upload exceeds {0} bytes")]
+    TooLarge(u64),
+
+    #[error("unsupported content type: {0}")]
+    UnsupportedType(String),
+
+    #[error("empty upload")]
+    Empty,
+
+    #[error("upload stream error: {0}")]
+    Source(String),
+}
+
+impl From<object_store::Error> for StorageError {
+    fn from(e: object_store::Error) -> Self {
+        match e {
+            object_store::Error::NotFound { .. } => Self::NotFound,
+            e => Self::Store(e),
+        }
+    }
+}
+
+/// What was written by [`Storage::put_stream`].
+#[derive(Debug, Clone)]
+pub struct StoredBlob {
+    pub size_bytes: u64,
+    pub sha256: String,
+    /// Sniffed from the content, never taken from the client.
+    pub content_type: String,
+}
+
+#[derive(Clone)]
+pub struct Storage {
+    store: Arc<dyn ObjectStore>,
+}
+
+impl fmt::Debug for Storage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Storage({})", self.store)
+    }
+}
+
+impl Storage {
+    pub fn new(cfg: &StorageConfig) -> Result<Self, StorageError> {
+        let store: Arc<dyn ObjectStore> = match cfg {
+            StorageConfig::Local { root } => {
+                std::fs::create_dir_all(root)?;
+                Arc::new(LocalFileSystem::new_with_prefix(root)?.with_automatic_cleanup(true))
+            }
+            StorageConfig::S3 {
+                bucket,
+                region,
+                endpoint,
+                access_key_id,
+                secret_access_key,
+                allow_http,
+            } => {
+                let mut b = AmazonS3Builder::new()
+                    .with_bucket_name(bucket)
+                    .with_region(region)
+                    .with_access_key_id(access_key_id)
+                    .with_secret_access_key(secret_access_key)
+                    .with_allow_http(*allow_http);
+                if let Some(endpoint) = endpoint {
+                    b = b.with_endpoint(endpoint);
+                }
+                Arc::new(b.build()?)
+            }
+            StorageConfig::Memory => Arc::new(InMemory::new()),
+        };
+        Ok(Self { store })
+    }
+
+    pub fn memory() -> Self {
+        Self {
+            store: Arc::new(InMemory::new()),
+        }
+    }
+
+    /// Streams `body` to `key`, hashing as it goes. The content type is sniffed
+    /// from the first bytes and must be in `allowed`; uploads over `max_bytes`
+    /// are aborted. Nothing is left behind on error.
+    pub async fn put_stream<S, E>(
+        &self,
+        key: &str,
+        body: S,
+        max_bytes: u64,
+        allowed: &[&str],
+    ) -> Result<StoredBlob, StorageError>
+    where
+        S: Stream<Item = Result<Bytes, E>> + Unpin,
+        E: fmt::Display,
+    {
+        // Fused: a body shorter than the sniff buffer has already ended when
+        // the copy loop starts, and some sources panic if polled again.
+        let mut body = body.fuse();
+        let mut hasher = Sha256::new();
+        let mut size = 0u64;
+        let mut head = Vec::with_capacity(SNIFF_LEN);
+
+        // Buffer the head so an unsupported file never reaches the store.
+        while head.len() < SNIFF_LEN {
+            match body.next().await {
+                Some(chunk) => {
+                    let chunk = chunk.map_err(|e| StorageError::Source(e.to_string()))?;
+                    size += chunk.len() as u64;
+                    if size > max_bytes {
+                        return Err(StorageError::TooLarge(max_bytes));
+                    }
+                    head.extend_from_slice(&chunk);
+                }
+                None => break,
+            }
+        }
+        if head.is_empty() {
+            return Err(StorageError::Empty);
+        }
+        let content_type = infer::get(&head)
+            .map(|t| t.mime_type())
+            .unwrap_or("application/octet-stream");
+        if !allowed.contains(&content_type) {
+            return Err(StorageError::UnsupportedType(content_type.to_string()));
+        }
+
+        let upload = self.store.put_multipart(&Path::from(key)).await?;
+        let mut writer = WriteMultipart::new(upload);
+        hasher.update(&head);
+        writer.write(&head);
+
+        let rest: Result<(), StorageError> = async {
+            while let Some(chunk) = body.next().await {
+                let chunk = chunk.map_err(|e| StorageError::Source(e.to_string()))?;
+                size += chunk.len() as u64;
+                if size > max_bytes {
+                    return Err(StorageError::TooLarge(max_bytes));
+                }
+                hasher.update(&chunk);
+                writer.wait_for_capacity(MAX_CONCURRENT_PARTS).await?;
+                writer.put(chunk);
+            }
+            Ok(())
+        }
+        .await;
+        if let Err(e) = rest {
+            let _ = writer.abort().await;
+            return Err(e);
+        }
+        writer.finish().await?;
+
+        Ok(StoredBlob {
+            size_bytes: size,
+            sha256: hex::encode(hasher.finalize()),
+            content_type: content_type.to_string(),
+        })
+    }
+
+    pub async fn get_stream(
+        &self,
+        key: &str,
+    ) -> Result<BoxStream<'static, Result<Bytes, StorageError>>, StorageError> {
+        let res = self.store.get(&Path::from(key)).await?;
+        Ok(res.into_stream().map_err(StorageError::from).boxed())
+    }
+
+    pub async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        match self
+            .store
+            .delete(&Path::from(key))
+            .await
+            .map_err(StorageError::from)
+        {
+            Ok(()) | Err(StorageError::NotFound) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! Re-encrypts patient fields under the current master key.
+//!
+//!     reencrypt [--fresh-data-keys] [--batch N] [--org ORG_ID]
+//!
+//! Rotating keys: append the new key to `MASTER_KEYS`, bump
+//! `MASTER_KEY_VERSION`, run this, and remove the old key only after it
+//! reports nothing left to rewrap.
+
+use api::state::Settings;
+use api::telemetry;
+
+#[actix_web::main]
+async fn main() {
+    dotenvy::dotenv().ok();
+    let s = Settings::from_env();
+    let _telemetry = telemetry::init(
+        s.log_format.unwrap_or_default(),
+        s.otel_exporter_otlp_endpoint.as_deref(),
+    );
+
+    let mut fresh_data_keys = false;
+    let mut batch = 500i64;
+    let mut org_id = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--fresh-data-keys" => fresh_data_keys = true,
+            "--batch" => {
+                batch = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .expect("--batch takes a number")
+            }
+            "--org" => {
+                org_id = Some(
+                    args.next()
+                        .and_then(|n| n.parse().ok())
+                        .expect("--org takes an org id"),
+                )
+            }
+            other => panic!("unknown argument {other:?}"),
+        }
+    }
+
+    let keys = s.key_ring().expect("validated by Settings::from_env");
+    let db = db::connect(s.database_url.expose(), 2).await.expect("db");
+    let report = db::reencrypt_patients(&db, &keys, org_id, batch, fresh_data_keys)
+        .await
+        .expect("reencrypt");
+    tracing::info!(
+        key_version = keys.current_version(),
+        scanned = report.scanned,
+        rewrapped = report.rewrapped,
+        resealed = report.resealed,
+        migrated_plaintext = report.migrated_plaintext,
+        "re-encryption done"
+    );
+    println!("{}", serde_json::to_string_pretty(&report).unwrap());
+}
@@ -0,0 +1,364 @@
+//! Bulk item import and export as CSV or NDJSON. Uploads are parsed
+//! incrementally as chunks arrive; only validated rows are kept.
+
+use crate::schemas::ItemIn;
+use chrono::{DateTime, Utc};
+use db::ItemRow;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+/// Longest single CSV record or NDJSON line accepted.
+pub const MAX_ROW_BYTES: usize = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Csv,
+    Ndjson,
+}
+
+impl Format {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Format::Csv => "text/csv; charset=utf-8",
+            Format::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    pub fn from_content_type(mime: &str) -> Option<Self> {
+        match mime.split(';').next()?.trim() {
+            "text/csv" => Some(Format::Csv),
+            "application/x-ndjson" | "application/jsonl" => Some(Format::Ndjson),
+            _ => None,
+        }
+    }
+}
+
+/// A validated row, ready for `ItemImport::push`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportRow {
+    pub title: String,
+    pub description: Option<String>,
+}
+
+/// Per-row outcome: 1-based row number (CSV data record or NDJSON line) and
+/// the row or why it was rejected.
+pub type Parsed = (usize, Result<ImportRow, String>);
+
+/// Problem with the upload as a whole rather than one row.
+#[derive(Debug, thiserror::Error)]
+pub enum BulkError {
+    #[error("CSV header must include a `title` column")]
+    MissingTitleColumn,
+    #[error("CSV header is not valid UTF-8")]
+    InvalidHeader,
+    #[error("row {0} exceeds {MAX_ROW_BYTES} bytes")]
+    RowTooLong(usize),
+}
+
+/// Rejected rows listed in an import report; the rest are only counted.
+pub const MAX_REPORTED_ERRORS: usize = 100;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    /// Nothing is imported unless every row is valid.
+    #[default]
+    Atomic,
+    /// Valid rows are imported, invalid ones skipped.
+    BestEffort,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RowError {
+    pub row: usize,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportReport {
+    pub mode: ImportMode,
+    pub rows: usize,
+    pub imported: usize,
+    pub failed: usize,
+    pub errors: Vec<RowError>,
+}
+
+impl ImportReport {
+    pub fn new(mode: ImportMode) -> Self {
+        Self {
+            mode,
+            rows: 0,
+            imported: 0,
+            failed: 0,
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn reject(&mut self, row: usize, error: String) {
+        self.failed += 1;
+        if self.errors.len() < MAX_REPORTED_ERRORS {
+            self.errors.push(RowError { row, error });
+        }
+    }
+
+    /// Whether valid rows should still be staged.
+    pub fn accepting(&self) -> bool {
+        self.mode == ImportMode::BestEffort || self.failed == 0
+    }
+}
+
+const EXPORT_COLUMNS: [&str; 6] = [
+    "id",
+    "title",
+    "description",
+    "created_at",
+    "updated_at",
+    "version",
+];
+
+/// Exported columns; a file in either format can be imported again.
+#[derive(Serialize)]
+struct ExportRow<'a> {
+    id: Uuid,
+    title: &'a str,
+    description: Option<&'a str>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    version: i64,
+}
+
+/// Encodes one page of an export; the CSV header goes with the first page.
+pub fn encode(format: Format, rows: &[ItemRow], first: bool) -> Result<Vec<u8>, String> {
+    let rows = rows.iter().map(|r| ExportRow {
+        id: r.id,
+        title: &r.title,
+        description: r.description.as_deref(),
+        created_at: r.created_at,
+        updated_at: r.updated_at,
+        version: r.version,
+    });
+    match format {
+        Format::Csv => {
+            let mut w = csv::WriterBuilder::new()
+                .has_headers(false)
+                .from_writer(Vec::new());
+            if first {
+                w.write_record(EXPORT_COLUMNS).map_err(|e| e.to_string())?;
+            }
+            for row in rows {
+                w.serialize(row).map_err(|e| e.to_string())?;
+            }
+            w.into_inner().map_err(|e| e.to_string())
+        }
+        Format::Ndjson => {
+            let mut out = Vec::new();
+            for row in rows {
+                serde_json::to_writer(&mut out, &row).map_err(|e| e.to_string())?;
+                out.push(b'\n');
+            }
+            Ok(out)
+        }
+    }
+}
+
+/// Rows get the same rules as `POST /items`.
+fn validate(title: Option<String>, description: Option<String>) -> Result<ImportRow, String> {
+    let title = title.map(|t| t.trim().to_string()).unwrap_or_default();
+    if title.is_empty() {
+        return Err("title is required".into());
+    }
+    let description = description.filter(|d| !d.is_empty());
+    if title.contains('\0') || description.as_deref().is_some_and(|d| d.contains('\0')) {
+        return Err("NUL bytes are not allowed".into());
+    }
+    let row = ItemIn { title, description };
+    if let Err(errors) = row.validate() {
+        let mut fields: Vec<_> = errors.field_errors().into_keys().collect();
+        fields.sort();
+        return Err(format!("invalid {}", fields.join(", ")));
+    }
+    Ok(ImportRow {
+        title: row.title,
+        description: row.description,
+    })
+}
+
+pub enum RowParser {
+    Csv(Box<CsvRows>),
+    Ndjson(NdjsonRows),
+}
+
+impl RowParser {
+    pub fn new(format: Format) -> Self {
+        match format {
+            Format::Csv => RowParser::Csv(Box::new(CsvRows::new())),
+            Format::Ndjson => RowParser::Ndjson(NdjsonRows::default()),
+        }
+    }
+
+    /// Consumes a chunk, appending every row it completes to `out`.
+    pub fn feed(&mut self, chunk: &[u8], out: &mut Vec<Parsed>) -> Result<(), BulkError> {
+        if chunk.is_empty() {
+            return Ok(());
+        }
+        match self {
+            RowParser::Csv(p) => p.read(chunk, out),
+            RowParser::Ndjson(p) => p.read(chunk, out),
+        }
+    }
+
+    /// End of upload: flushes a final row without a trailing newline.
+    pub fn finish(&mut self, out: &mut Vec<Parsed>) -> Result<(), BulkError> {
+        match self {
+            // empty input tells csv_core the stream has ended
+            RowParser::Csv(p) => p.read(&[], out),
+            RowParser::Ndjson(p) => {
+                let rest = std::mem::take(&mut p.buf);
+                p.line(&rest, out);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Column positions from the CSV header.
+#[derive(Clone, Copy)]
+struct Columns {
+    title: usize,
+    description: Option<usize>,
+}
+
+pub struct CsvRows {
+    reader: csv_core::Reader,
+    record: Vec<u8>,
+    record_len: usize,
+    ends: Vec<usize>,
+    ends_len: usize,
+    columns: Option<Columns>,
+    row: usize,
+}
+
+impl CsvRows {
+    fn new() -> Self {
+        Self {
+            reader: csv_core::Reader::new(),
+            record: vec![0; 4096],
+            record_len: 0,
+            ends: vec![0; 16],
+            ends_len: 0,
+            columns: None,
+            row: 0,
+        }
+    }
+
+    fn read(&mut self, mut input: &[u8], out: &mut Vec<Parsed>) -> Result<(), BulkError> {
+        use csv_core::ReadRecordResult::*;
+        loop {
+            let (res, nin, nout, nend) = self.reader.read_record(
+                input,
+                &mut self.record[self.record_len..],
+                &mut self.ends[self.ends_len..],
+            );
+            input = &input[nin..];
+            self.record_len += nout;
+            self.ends_len += nend;
+            match res {
+                InputEmpty | End => return Ok(()),
+                OutputFull if self.record.len() >= MAX_ROW_BYTES => {
+                    return Err(BulkError::RowTooLong(self.row + 1));
+                }
+                OutputFull => self.record.resize(self.record.len() * 2, 0),
+                OutputEndsFull => self.ends.resize(self.ends.len() * 2, 0),
+                Record => {
+                    self.record_done(out)?;
+                    self.record_len = 0;
+                    self.ends_len = 0;
+                }
+            }
+        }
+    }
+
+    fn field(&self, i: usize) -> Option<Result<String, String>> {
+        if i >= self.ends_len {
+            return None;
+        }
+        let start = if i == 0 { 0 } else { self.ends[i - 1] };
+        let bytes = &self.record[start..self.ends[i]];
+        Some(String::from_utf8(bytes.to_vec()).map_err(|_| "invalid UTF-8".to_string()))
+    }
+
+    fn record_done(&mut self, out: &mut Vec<Parsed>) -> Result<(), BulkError> {
+        let Some(columns) = self.columns else {
+            let names = (0..self.ends_len)
+                .filter_map(|i| self.field(i))
+                .map(|n| {
+                    n.map(|n| n.trim_start_matches('\u{feff}').trim().to_lowercase())
+                        .map_err(|_| BulkError::InvalidHeader)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let title = names
+                .iter()
+                .position(|n| n == "title")
+                .ok_or(BulkError::MissingTitleColumn)?;
+            let description = names.iter().position(|n| n == "description");
+            self.columns = Some(Columns { title, description });
+            return Ok(());
+        };
+        self.row += 1;
+        let parsed = (|| {
+            let title = self.field(columns.title).transpose()?;
+            let description = match columns.description {
+                Some(i) => self.field(i).transpose()?,
+                None => None,
+            };
+            validate(title, description)
+        })();
+        out.push((self.row, parsed));
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct NdjsonRows {
+    buf: Vec<u8>,
+    row: usize,
+}
+
+/// One NDJSON line; unknown fields (e.g. `id` from an export) are ignored.
+#[derive(Deserialize)]
+struct Line {
+    title: Option<String>,
+    description: Option<String>,
+}
+
+impl NdjsonRows {
+    fn read(&mut self, chunk: &[u8], out: &mut Vec<Parsed>) -> Result<(), BulkError> {
+        self.buf.extend_from_slice(chunk);
+        let mut start = 0;
+        while let Some(pos) = self.buf[start..].iter().position(|&b| b == b'\n') {
+            let end = start + pos;
+            let line = self.buf[start..end].to_vec();
+            self.line(&line, out);
+            start = end + 1;
+        }
+        self.buf.drain(..start);
+        if self.buf.len() > MAX_ROW_BYTES {
+            return Err(BulkError::RowTooLong(self.row + 1));
+        }
+        Ok(())
+    }
+
+    fn line(&mut self, line: &[u8], out: &mut Vec<Parsed>) {
+        self.row += 1;
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.iter().all(u8::is_ascii_whitespace) {
+            return;
+        }
+        let parsed = serde_json::from_slice::<Line>(line)
+            .map_err(|e| e.to_string())
+            .and_then(|l| validate(l.title, l.description));
+        out.push((self.row, parsed));
+    }
+}
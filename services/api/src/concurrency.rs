@@ -0,0 +1,66 @@
+//! Optimistic concurrency over HTTP: the `ETag` of a [`Versioned`] row is its
+//! version, and writes carry it back in `If-Match`. Resources opt in by
+//! implementing `Versioned` and taking [`IfMatch`] in their PUT/PATCH handlers.
+
+use crate::error::{HttpApiError, Problem};
+use actix_web::http::StatusCode;
+use actix_web::http::header::{self, EntityTag, Header};
+use actix_web::{FromRequest, HttpResponse, HttpResponseBuilder};
+use common::AppError;
+use db::Versioned;
+use serde::Serialize;
+use std::future::{Ready, ready};
+
+pub fn etag(version: i64) -> EntityTag {
+    EntityTag::new_strong(version.to_string())
+}
+
+/// Versions named by a request's `If-Match` header. A missing header (or `*`,
+/// which would match any version) is rejected with 428; weak or foreign tags
+/// never match, so they end in 412.
+#[derive(Debug, Clone)]
+pub struct IfMatch(pub Vec<i64>);
+
+impl FromRequest for IfMatch {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &actix_web::HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        let tags = match header::IfMatch::parse(req) {
+            Ok(header::IfMatch::Items(tags)) if !tags.is_empty() => tags,
+            Ok(_) => return ready(Err(HttpApiError::PreconditionRequired.into())),
+            Err(_) => {
+                return ready(Err(HttpApiError::App(AppError::BadRequest(
+                    "malformed If-Match header".into(),
+                ))
+                .into()));
+            }
+        };
+        let versions = tags
+            .iter()
+            .filter(|t| !t.weak)
+            .filter_map(|t| t.tag().parse().ok())
+            .collect();
+        ready(Ok(IfMatch(versions)))
+    }
+}
+
+/// JSON body with the row's `ETag`.
+pub fn versioned<T: Serialize + Versioned>(mut res: HttpResponseBuilder, row: &T) -> HttpResponse {
+    res.insert_header(header::ETag(etag(row.version())))
+        .json(row)
+}
+
+/// 412 problem naming the current version; its `ETag` is on the response, so
+/// the client can refetch, merge and retry.
+pub fn precondition_failed<T: Versioned>(current: &T) -> HttpResponse {
+    let problem = Problem::new(StatusCode::PRECONDITION_FAILED)
+        .typed("/problems/stale-version", "Stale version")
+        .detail("If-Match does not name the current version")
+        .extend(serde_json::json!({ "current_version": current.version() }));
+    let mut res = HttpResponse::from_error(HttpApiError::from(problem));
+    if let Ok(value) = etag(current.version()).to_string().parse() {
+        res.headers_mut().insert(header::ETAG, value);
+    }
+    res
+}
@@ -1,28 +1,210 @@
+use actix_web::http::StatusCode;
+use actix_web::http::header::{self, HeaderValue};
 use actix_web::{HttpResponse, ResponseError};
 use common::AppError;
+use serde::Serialize;
+use storage::StorageError;
 use thiserror::Error;
 
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// RFC 7807 error body. Every error response is one of these; the
+/// `ProblemDetails` middleware fills in `request_id`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Problem {
+    #[serde(rename = "type")]
+    pub type_uri: &'static str,
+    pub title: &'static str,
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// Problem-specific members, e.g. `fields` for validation errors.
+    #[serde(flatten)]
+    pub extensions: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Problem {
+    /// A problem identified by its status alone (`type` is `about:blank`).
+    pub fn new(status: StatusCode) -> Self {
+        Self {
+            type_uri: "about:blank",
+            title: status.canonical_reason().unwrap_or("Error"),
+            status: status.as_u16(),
+            detail: None,
+            request_id: None,
+            extensions: serde_json::Map::new(),
+        }
+    }
+
+    pub fn typed(mut self, type_uri: &'static str, title: &'static str) -> Self {
+        self.type_uri = type_uri;
+        self.title = title;
+        self
+    }
+
+    pub fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    pub fn with(mut self, key: &str, value: impl Serialize) -> Self {
+        let value = serde_json::to_value(value).unwrap_or_default();
+        self.extensions.insert(key.to_string(), value);
+        self
+    }
+
+    /// Adds every member of `value`, which must serialize to an object.
+    pub fn extend(mut self, value: impl Serialize) -> Self {
+        if let Ok(serde_json::Value::Object(members)) = serde_json::to_value(value) {
+            self.extensions.extend(members);
+        }
+        self
+    }
+
+    pub fn status(&self) -> StatusCode {
+        StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    pub fn body(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    pub fn response(&self) -> HttpResponse {
+        HttpResponse::build(self.status())
+            .insert_header((header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON)))
+            .body(self.body())
+    }
+
+    /// Problem for any error reaching the client. Errors not raised through
+    /// [`HttpApiError`] (extractor and payload errors from actix) keep their
+    /// status; their message is only shown for 4xx.
+    pub fn from_error(err: &actix_web::Error) -> Self {
+        if let Some(e) = err.as_error::<HttpApiError>() {
+            return e.problem();
+        }
+        let status = err.as_response_error().status_code();
+        let problem = Problem::new(status);
+        if status.is_client_error() {
+            problem.detail(err.to_string())
+        } else {
+            problem
+        }
+    }
+}
+
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.detail {
+            Some(detail) => write!(f, "{}: {detail}", self.title),
+            None => f.write_str(self.title),
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum HttpApiError {
     #[error("{0}")]
     App(#[from] AppError),
-    #[error("db error")]
+    #[error("db error: {0}")]
     Db(#[from] db::DbError),
-    #[error("auth error")]
+    #[error("storage error: {0}")]
+    Storage(#[from] storage::StorageError),
+    #[error("authentication required")]
     Auth,
+    #[error("If-Match header required")]
+    PreconditionRequired,
+    #[error("validation failed")]
+    Validation(#[from] validator::ValidationErrors),
+    /// Logged, never shown to the client.
+    #[error("internal error: {0}")]
+    Internal(String),
+    /// A fully described problem, for cases the variants above don't cover.
+    #[error("{0}")]
+    Problem(Problem),
 }
 
-impl ResponseError for HttpApiError {
-    fn error_response(&self) -> HttpResponse {
+impl From<Problem> for HttpApiError {
+    fn from(p: Problem) -> Self {
+        Self::Problem(p)
+    }
+}
+
+impl HttpApiError {
+    /// A plain problem: status plus a human-readable detail.
+    pub fn rejected(status: StatusCode, detail: impl Into<String>) -> Self {
+        Self::Problem(Problem::new(status).detail(detail))
+    }
+
+    pub fn problem(&self) -> Problem {
         match self {
-            Self::App(AppError::NotFound) => HttpResponse::NotFound().finish(),
-            Self::App(AppError::Conflict) => HttpResponse::Conflict().finish(),
-            Self::App(AppError::Unauthorized) => HttpResponse::Unauthorized().finish(),
-            Self::App(AppError::Forbidden) => HttpResponse::Forbidden().finish(),
+            Self::App(AppError::NotFound) => Problem::new(StatusCode::NOT_FOUND),
+            Self::App(AppError::Conflict) => Problem::new(StatusCode::CONFLICT),
+            Self::App(AppError::Unauthorized) | Self::Auth => {
+                Problem::new(StatusCode::UNAUTHORIZED)
+            }
+            Self::App(AppError::Forbidden) => Problem::new(StatusCode::FORBIDDEN),
             Self::App(AppError::BadRequest(msg)) => {
-                HttpResponse::BadRequest().json(serde_json::json!({"error":msg}))
+                Problem::new(StatusCode::BAD_REQUEST).detail(msg.clone())
+            }
+            Self::Db(db::DbError::Sqlx(sqlx::Error::Database(e))) if e.is_unique_violation() => {
+                Problem::new(StatusCode::CONFLICT)
+                    .typed("/problems/duplicate", "Duplicate record")
+                    .detail("a record with the same unique key already exists")
+            }
+            Self::Storage(e @ StorageError::TooLarge(_)) => {
+                Problem::new(StatusCode::PAYLOAD_TOO_LARGE).detail(e.to_string())
+            }
+            Self::Storage(e @ StorageError::UnsupportedType(_)) => {
+                Problem::new(StatusCode::UNSUPPORTED_MEDIA_TYPE).detail(e.to_string())
+            }
+            Self::Storage(e @ (StorageError::Empty | StorageError::Source(_))) => {
+                Problem::new(StatusCode::BAD_REQUEST).detail(e.to_string())
+            }
+            Self::Storage(StorageError::NotFound) => Problem::new(StatusCode::NOT_FOUND),
+            Self::PreconditionRequired => {
+                Problem::new(StatusCode::PRECONDITION_REQUIRED).detail(self.to_string())
+            }
+            Self::Validation(errors) => Problem::new(StatusCode::UNPROCESSABLE_ENTITY)
+                .typed("/problems/validation", "Validation failed")
+                .detail("one or more fields are invalid")
+                .with("fields", field_errors(errors)),
+            Self::Problem(p) => p.clone(),
+            Self::App(AppError::Internal) | Self::Db(_) | Self::Storage(_) | Self::Internal(_) => {
+                Problem::new(StatusCode::INTERNAL_SERVER_ERROR)
             }
-            _ => HttpResponse::InternalServerError().finish(),
         }
     }
 }
+
+impl ResponseError for HttpApiError {
+    fn status_code(&self) -> StatusCode {
+        self.problem().status()
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        self.problem().response()
+    }
+}
+
+/// `{"field": [{"code", "message", "params"}]}`. The rejected value is left
+/// out of `params` so passwords and the like are never echoed back.
+fn field_errors(errors: &validator::ValidationErrors) -> serde_json::Value {
+    errors
+        .field_errors()
+        .into_iter()
+        .map(|(field, errs)| {
+            let errs: Vec<_> = errs
+                .iter()
+                .map(|e| {
+                    let mut params = e.params.clone();
+                    params.remove("value");
+                    serde_json::json!({"code": e.code, "message": e.message, "params": params})
+                })
+                .collect();
+            (field.into_owned(), serde_json::Value::from(errs))
+        })
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
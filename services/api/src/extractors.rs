@@ -1,11 +1,24 @@
-use actix_web::{FromRequest, HttpMessage};
+use crate::error::HttpApiError;
+use crate::state::AppState;
+use actix_web::http::StatusCode;
+use actix_web::{FromRequest, HttpMessage, web};
+use common::AppError;
+use db::{AuditContext, Db, TenantDb};
+use futures_util::future::LocalBoxFuture;
+use serde::de::DeserializeOwned;
 use std::future::{Ready, ready};
+use std::ops::Deref;
 use uuid::Uuid;
+use validator::Validate;
+
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
 
 #[derive(Debug, Clone)]
 pub struct AuthUser {
     pub user_id: Uuid,
     pub role: String,
+    /// Organization from the token; all tenant data is scoped to it.
+    pub org_id: i32,
 }
 
 impl FromRequest for AuthUser {
@@ -16,15 +29,140 @@ impl FromRequest for AuthUser {
         if let Some(ext) = req.extensions().get::<AuthUser>() {
             return ready(Ok(ext.clone()));
         }
-        ready(Err(actix_web::error::ErrorUnauthorized("unauthorized")))
+        ready(Err(HttpApiError::Auth.into()))
+    }
+}
+
+/// Database handle scoped to the caller's organization. Handlers touching
+/// tenant-owned tables take this instead of `web::Data<Db>`, so they cannot
+/// forget the org filter.
+#[derive(Debug, Clone)]
+pub struct Tenant(pub TenantDb);
+
+impl FromRequest for Tenant {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &actix_web::HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        let Some(user) = req.extensions().get::<AuthUser>().cloned() else {
+            return ready(Err(HttpApiError::Auth.into()));
+        };
+        let Some(db) = req.app_data::<web::Data<Db>>() else {
+            return ready(Err(HttpApiError::Internal("db missing".into()).into()));
+        };
+        ready(Ok(Tenant(db.tenant(user.org_id))))
+    }
+}
+
+/// Actor, client IP and request id of the current request, for the audit log.
+#[derive(Debug, Clone)]
+pub struct Audit(pub AuditContext);
+
+/// The forwarded client address when the peer is a trusted proxy, otherwise
+/// the peer itself; anyone can send `X-Forwarded-For`.
+fn client_ip(req: &actix_web::HttpRequest) -> Option<String> {
+    let peer = req.peer_addr().map(|a| a.ip());
+    let trusted = req
+        .app_data::<web::Data<AppState>>()
+        .is_some_and(|s| peer.is_some_and(|ip| s.trusted_proxies.contains(&ip)));
+    if trusted {
+        req.connection_info()
+            .realip_remote_addr()
+            .map(|s| s.to_string())
+    } else {
+        peer.map(|ip| ip.to_string())
+    }
+}
+
+pub fn audit_context(req: &actix_web::HttpRequest) -> AuditContext {
+    let actor_id = req.extensions().get::<AuthUser>().map(|u| u.user_id);
+    AuditContext {
+        actor_id,
+        ip: client_ip(req),
+        request_id: req
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string()),
+    }
+}
+
+impl FromRequest for Audit {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &actix_web::HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        ready(Ok(Audit(audit_context(req))))
     }
 }
 
 pub fn require_role(req: &actix_web::HttpRequest, role: &str) -> Result<(), actix_web::Error> {
-    if let Some(user) = req.extensions().get::<AuthUser>() {
-        if user.role == role || user.role == "Admin" {
-            return Ok(());
-        }
+    if let Some(user) = req.extensions().get::<AuthUser>()
+        && (user.role == role || user.role == "Admin")
+    {
+        return Ok(());
+    }
+    Err(HttpApiError::App(AppError::Forbidden).into())
+}
+
+/// Allows the doctor themself or an admin to act on `doctor_id`'s records.
+pub fn require_self_or_admin(
+    req: &actix_web::HttpRequest,
+    doctor_id: Uuid,
+) -> Result<(), actix_web::Error> {
+    if let Some(user) = req.extensions().get::<AuthUser>()
+        && (user.user_id == doctor_id || user.role == "Admin")
+    {
+        return Ok(());
+    }
+    Err(HttpApiError::App(AppError::Forbidden).into())
+}
+
+/// Guard every prescribing action must pass first: doctors without a current
+/// license are refused with 403.
+pub async fn require_active_license(db: &Db, user: &AuthUser) -> Result<(), HttpApiError> {
+    if db::has_active_license(db, user.user_id).await? {
+        Ok(())
+    } else {
+        Err(HttpApiError::rejected(
+            StatusCode::FORBIDDEN,
+            "no current medical license",
+        ))
+    }
+}
+
+/// JSON body that has passed its `#[validate]` rules. Failures are 422 with
+/// the offending fields; malformed JSON is still a 400 from `web::Json`.
+#[derive(Debug)]
+pub struct ValidatedJson<T>(pub T);
+
+impl<T> ValidatedJson<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for ValidatedJson<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: DeserializeOwned + Validate + 'static> FromRequest for ValidatedJson<T> {
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(
+        req: &actix_web::HttpRequest,
+        payload: &mut actix_web::dev::Payload,
+    ) -> Self::Future {
+        let json = web::Json::<T>::from_request(req, payload);
+        Box::pin(async move {
+            let body = json.await?.into_inner();
+            body.validate().map_err(HttpApiError::from)?;
+            Ok(ValidatedJson(body))
+        })
     }
-    Err(actix_web::error::ErrorForbidden("forbidden"))
 }
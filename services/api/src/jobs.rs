@@ -0,0 +1,90 @@
+//! Periodic housekeeping that runs beside the HTTP server. Each job runs to
+//! completion once started; cancellation only stops the next run from
+//! starting, so shutdown never leaves a statement half done.
+
+use db::{Db, DbError};
+use std::future::Future;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::{Instant, timeout_at};
+use tokio_util::sync::CancellationToken;
+
+/// Read notifications older than this are deleted.
+const NOTIFICATION_RETENTION_DAYS: i32 = 90;
+
+pub struct Jobs {
+    cancel: CancellationToken,
+    tasks: Vec<(&'static str, JoinHandle<()>)>,
+}
+
+impl Jobs {
+    pub fn new() -> Self {
+        Self {
+            cancel: CancellationToken::new(),
+            tasks: Vec::new(),
+        }
+    }
+
+    /// Token cleanup hourly and notification pruning daily.
+    pub fn start(db: &Db) -> Self {
+        let mut jobs = Self::new();
+        let tokens = db.clone();
+        jobs.every("token_cleanup", Duration::from_secs(60 * 60), move || {
+            let db = tokens.clone();
+            async move { db::purge_expired_refresh_tokens(&db).await }
+        });
+        let notifications = db.clone();
+        jobs.every(
+            "notification_cleanup",
+            Duration::from_secs(24 * 60 * 60),
+            move || {
+                let db = notifications.clone();
+                async move { db::purge_read_notifications(&db, NOTIFICATION_RETENTION_DAYS).await }
+            },
+        );
+        jobs
+    }
+
+    /// Runs `job` now and then every `period`; it returns the rows it touched.
+    pub fn every<F, Fut>(&mut self, name: &'static str, period: Duration, mut job: F)
+    where
+        F: FnMut() -> Fut + 'static,
+        Fut: Future<Output = Result<u64, DbError>> + 'static,
+    {
+        let cancel = self.cancel.clone();
+        let task = actix_web::rt::spawn(async move {
+            let mut ticks = actix_web::rt::time::interval(period);
+            loop {
+                tokio::select! {
+                    biased;
+                    _ = cancel.cancelled() => break,
+                    _ = ticks.tick() => match job().await {
+                        Ok(rows) => tracing::debug!(job = name, rows, "job finished"),
+                        Err(e) => tracing::warn!(job = name, error = %e, "job failed"),
+                    },
+                }
+            }
+            tracing::debug!(job = name, "job stopped");
+        });
+        self.tasks.push((name, task));
+    }
+
+    /// Stops scheduling new runs and waits up to `grace` for running ones;
+    /// anything still going after that is aborted.
+    pub async fn shutdown(self, grace: Duration) {
+        self.cancel.cancel();
+        let deadline = Instant::now() + grace;
+        for (name, mut task) in self.tasks {
+            if timeout_at(deadline, &mut task).await.is_err() {
+                tracing::warn!(job = name, "job did not stop in time, aborting");
+                task.abort();
+            }
+        }
+    }
+}
+
+impl Default for Jobs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,11 +1,32 @@
+pub mod bulk;
+pub mod concurrency;
 pub mod error;
 pub mod extractors;
+pub mod jobs;
+pub mod metrics;
+pub mod middleware;
 pub mod routes;
 pub mod schemas;
 pub mod state;
+pub mod telemetry;
 
-use actix_web::{App, web};
+use actix_cors::Cors;
+use actix_governor::governor::middleware::NoOpMiddleware;
+use actix_governor::{Governor, GovernorConfig, GovernorConfigBuilder};
+use actix_web::dev::Service;
+use actix_web::middleware::Condition;
+use actix_web::{App, HttpMessage, HttpResponse, web};
+use metrics::CountingPeerIp;
 
+/// Guards only the server binary installs: CORS, per-IP rate limiting and the
+/// CSRF check. They sit inside `ProblemDetails` and `RequestTrace`, so their
+/// rejections are problem+json and traced like any other response.
+pub struct Edge {
+    pub cors: Cors,
+    pub governor: GovernorConfig<CountingPeerIp, NoOpMiddleware>,
+}
+
+/// The app as tests drive it, without the [`Edge`] guards.
 pub fn create_app(
     state: state::AppState,
 ) -> App<
@@ -17,10 +38,72 @@ pub fn create_app(
         InitError = (),
     >,
 > {
+    create_app_with(state, None)
+}
+
+pub fn create_app_with(
+    state: state::AppState,
+    edge: Option<Edge>,
+) -> App<
+    impl actix_web::dev::ServiceFactory<
+        actix_web::dev::ServiceRequest,
+        Config = (),
+        Response = actix_web::dev::ServiceResponse,
+        Error = actix_web::Error,
+        InitError = (),
+    >,
+> {
+    let db = state.db.clone();
+    let guarded = edge.is_some();
+    let (cors, governor) = match edge {
+        Some(edge) => (edge.cors, edge.governor),
+        // placeholders, never called while the conditions are off
+        None => (
+            Cors::default(),
+            GovernorConfigBuilder::default()
+                .key_extractor(CountingPeerIp)
+                .finish()
+                .expect("default quota is non-zero"),
+        ),
+    };
     App::new()
         .app_data(web::Data::new(state))
-        .service(routes::auth::register)
-        .service(routes::auth::login)
-        .service(routes::auth::refresh)
-        .service(routes::auth::logout)
+        .app_data(web::Data::new(db))
+        .configure(routes::configure)
+        .default_service(web::to(|| async {
+            Err::<HttpResponse, _>(error::HttpApiError::App(common::AppError::NotFound))
+        }))
+        .wrap(middleware::AuditTrail)
+        .wrap_fn(|req, srv| {
+            // JWT auth extractor: read Bearer or cookie, set AuthUser ext if valid
+            let jwt = req
+                .app_data::<web::Data<state::AppState>>()
+                .unwrap()
+                .jwt
+                .clone();
+            let token = req
+                .headers()
+                .get("Authorization")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|h| h.strip_prefix("Bearer "))
+                .map(|s| s.to_string())
+                .or_else(|| req.cookie("access_token").map(|c| c.value().to_string()));
+            // Tokens without an org claim predate tenant scoping; treat as anonymous.
+            if let Some(claims) = token.and_then(|tok| auth::verify(&jwt, &tok).ok())
+                && let Some(org_id) = claims.org_id
+            {
+                req.extensions_mut().insert(extractors::AuthUser {
+                    user_id: claims.sub,
+                    role: claims.role,
+                    org_id,
+                });
+            }
+            srv.call(req)
+        })
+        .wrap(Condition::new(guarded, middleware::Csrf))
+        .wrap(Condition::new(guarded, Governor::new(&governor)))
+        .wrap(Condition::new(guarded, cors))
+        .wrap(middleware::ProblemDetails)
+        .wrap(middleware::HttpMetrics)
+        .wrap(middleware::RequestTrace)
 }
@@ -1,98 +1,152 @@
 use actix_cors::Cors;
-use actix_governor::{Governor, GovernorConfigBuilder};
-use actix_web::HttpMessage;
-use actix_web::dev::Service;
-use actix_web::{App, HttpResponse, HttpServer, middleware::Logger, web};
-use tracing_subscriber::EnvFilter;
+use actix_governor::GovernorConfigBuilder;
+use actix_web::{HttpServer, web};
+use api::Edge;
+use api::jobs::Jobs;
+use api::metrics::CountingPeerIp;
+use api::state::{AppState, Settings};
+use api::telemetry;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
 
-mod error;
-mod extractors;
-mod middleware;
-mod routes;
-mod schemas;
-mod state;
-use state::{AppState, Settings};
+/// Roughly three minutes of backoff waiting for Postgres before giving up.
+const STARTUP_DB_ATTEMPTS: u32 = 12;
 
 #[actix_web::main]
-async fn main() -> std::io::Result<()> {
+async fn main() -> io::Result<()> {
     dotenvy::dotenv().ok();
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env())
-        .init();
-
     let s = Settings::from_env();
-    let db = db::connect(&s.database_url, 10).await.expect("db");
-    db::migrate(&db).await.expect("migrations");
+    let _telemetry = telemetry::init(
+        s.log_format.unwrap_or_default(),
+        s.otel_exporter_otlp_endpoint.as_deref(),
+    );
+
+    let db = db::connect_with_retry(
+        s.database_url.expose(),
+        s.db_max_connections.unwrap_or(10),
+        STARTUP_DB_ATTEMPTS,
+    )
+    .await
+    .inspect_err(|e| tracing::error!(error = %e, "database unreachable, giving up"))
+    .map_err(io::Error::other)?;
+    db::migrate(&db)
+        .await
+        .inspect_err(|e| tracing::error!(error = %e, "migrations failed"))
+        .map_err(io::Error::other)?;
+    // Both were checked by `Settings::validate`; opening local storage can
+    // still fail, e.g. on permissions.
+    let keys = s.key_ring().map_err(io::Error::other)?;
+    let storage = s
+        .storage_config()
+        .map_err(|missing| io::Error::other(missing.join(", ")))
+        .and_then(|cfg| storage::Storage::new(&cfg).map_err(io::Error::other))
+        .inspect_err(|e| tracing::error!(error = %e, "document storage unavailable"))?;
 
     let state = AppState {
         db: db.clone(),
-        jwt: auth::JwtKeys::from_secret(&s.jwt_secret),
+        jwt: auth::JwtKeys::from_secret(s.jwt_secret.expose()),
         access_ttl: s.access_ttl_seconds.unwrap_or(900),
         refresh_ttl: s.refresh_ttl_seconds.unwrap_or(60 * 60 * 24 * 7),
-        cookie_domain: s.cookie_domain.unwrap_or_else(|| "localhost".into()),
+        cookie_domain: s
+            .cookie_domain
+            .clone()
+            .unwrap_or_else(|| "localhost".into()),
         cookie_secure: s.cookie_secure.unwrap_or(false),
+        open_registration: s
+            .open_registration
+            .unwrap_or(false)
+            .then_some(s.open_registration_org_id)
+            .flatten(),
+        min_rest_minutes: s.min_rest_minutes.unwrap_or(8 * 60),
+        storage,
+        keys,
+        max_upload_bytes: s.max_upload_bytes.unwrap_or(20 * 1024 * 1024),
+        trusted_proxies: s
+            .trusted_proxies
+            .iter()
+            .flatten()
+            .filter_map(|ip| ip.parse().ok())
+            .collect(),
+        metrics_token: s.metrics_token.clone(),
     };
 
+    let bind_address = s
+        .bind_address
+        .clone()
+        .unwrap_or_else(|| "0.0.0.0:8080".into());
+    let body_limit = s.body_limit_bytes.unwrap_or(2 * 1024 * 1024);
+    let cors_origins = s.cors_allowed_origins.clone().unwrap_or_default();
+    let tls = match (&s.tls_cert_path, &s.tls_key_path) {
+        (Some(cert), Some(key)) => Some(tls_config(cert, key)?),
+        _ => None,
+    };
+
+    // validated non-zero, so `finish` cannot fail
     let governor_conf = GovernorConfigBuilder::default()
-        .burst_size(10)
+        .key_extractor(CountingPeerIp)
+        .milliseconds_per_request(s.rate_limit_replenish_ms.unwrap_or(500))
+        .burst_size(s.rate_limit_burst.unwrap_or(10))
         .finish()
         .unwrap();
 
-    HttpServer::new(move || {
-        let cors = Cors::default()
-            .allow_any_origin()
-            .allow_any_header()
-            .allow_any_method();
-        App::new()
-            .wrap(Logger::default())
-            .wrap(cors)
-            .wrap(Governor::new(&governor_conf))
-            .wrap(middleware::Csrf)
-            .app_data(web::Data::new(state.clone()))
-            .app_data(web::Data::new(state.db.clone()))
-            .service(routes::auth::register)
-            .service(routes::auth::login)
-            .service(routes::auth::refresh)
-            .service(routes::auth::logout)
-            .service(routes::items::list)
-            .service(routes::items::get)
-            .service(routes::items::create)
-            .service(routes::items::update)
-            .service(routes::items::remove)
-            .default_service(web::to(|| async { HttpResponse::NotFound().finish() }))
-            .wrap_fn(|req, srv| {
-                // JWT auth extractor: read Bearer or cookie, set AuthUser ext if valid
-                let jwt = req.app_data::<web::Data<AppState>>().unwrap().jwt.clone();
-                let req_mut = req;
-                let auth_header = req_mut
-                    .headers()
-                    .get("Authorization")
-                    .and_then(|v| v.to_str().ok())
-                    .map(|s| s.to_string());
-                let token_opt = if let Some(h) = auth_header {
-                    h.strip_prefix("Bearer ").map(|s| s.to_string())
-                } else {
-                    None
-                };
-                let token = token_opt.or_else(|| {
-                    req_mut
-                        .cookie("access_token")
-                        .map(|c| c.value().to_string())
-                });
-                if let Some(tok) = token {
-                    if let Ok(claims) = auth::verify(&jwt, &tok) {
-                        req_mut
-                            .extensions_mut()
-                            .insert(crate::extractors::AuthUser {
-                                user_id: claims.sub,
-                                role: claims.role,
-                            });
-                    }
-                }
-                srv.call(req_mut)
-            })
+    let shutdown_timeout = s.shutdown_timeout_seconds.unwrap_or(30);
+    let workers = s.workers;
+    let settings = web::Data::new(s);
+    let jobs = Jobs::start(&db);
+
+    // SIGTERM stops the listeners and lets in-flight requests finish within
+    // the timeout; SIGINT and SIGQUIT stop at once.
+    let mut server = HttpServer::new(move || {
+        let edge = Edge {
+            cors: cors(&cors_origins),
+            governor: governor_conf.clone(),
+        };
+        api::create_app_with(state.clone(), Some(edge))
+            .app_data(settings.clone())
+            .app_data(web::JsonConfig::default().limit(body_limit))
+            .app_data(web::PayloadConfig::new(body_limit))
     })
-    .bind(("0.0.0.0", 8080))?
-    .run()
-    .await
+    .shutdown_timeout(shutdown_timeout);
+    if let Some(workers) = workers {
+        server = server.workers(workers);
+    }
+    let server = match tls {
+        Some(tls) => server.bind_rustls_0_23(&bind_address, tls)?,
+        None => server.bind(&bind_address)?,
+    };
+    tracing::info!(address = %bind_address, "listening");
+    let served = server.run().await;
+
+    tracing::info!("server stopped, cancelling background jobs");
+    jobs.shutdown(Duration::from_secs(10)).await;
+    db.0.close().await;
+    tracing::info!("shutdown complete");
+    served
+}
+
+/// Only the listed origins; `*` allows any.
+fn cors(origins: &[String]) -> Cors {
+    let cors = Cors::default().allow_any_header().allow_any_method();
+    if origins.iter().any(|o| o == "*") {
+        cors.allow_any_origin()
+    } else {
+        origins
+            .iter()
+            .fold(cors, |cors, origin| cors.allowed_origin(origin))
+    }
+}
+
+fn tls_config(cert: &str, key: &str) -> io::Result<rustls::ServerConfig> {
+    let chain = CertificateDer::pem_file_iter(cert)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| io::Error::other(format!("TLS certificate {cert}: {e}")))?;
+    let key = PrivateKeyDer::from_pem_file(key)
+        .map_err(|e| io::Error::other(format!("TLS key {key}: {e}")))?;
+    rustls::ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .and_then(|builder| builder.with_no_client_auth().with_single_cert(chain, key))
+        .map_err(io::Error::other)
 }
@@ -0,0 +1,185 @@
+//! Prometheus metrics, scraped from `GET /metrics`. Collectors live in one
+//! process-wide registry so every worker, and the rate limiter wrapped around
+//! the app in `main`, reports into the same series.
+
+use crate::error::HttpApiError;
+use actix_governor::governor::NotUntil;
+use actix_governor::governor::clock::QuantaInstant;
+use actix_governor::{KeyExtractor, PeerIpKeyExtractor};
+use actix_web::dev::ServiceRequest;
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, HttpResponseBuilder};
+use db::Db;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts,
+    Registry, TextEncoder,
+};
+use std::net::IpAddr;
+use std::sync::LazyLock;
+
+pub struct Metrics {
+    registry: Registry,
+    /// `http_requests_total{method, route, status}`
+    pub http_requests: IntCounterVec,
+    /// `http_request_duration_seconds{method, route, status}`
+    pub http_duration: HistogramVec,
+    /// `auth_logins_total{outcome}`: `success`, `unknown_user`, `bad_password`
+    /// or `deactivated`.
+    pub logins: IntCounterVec,
+    /// `auth_refresh_total{outcome}`: `rotated`, `invalid`, `unknown`,
+    /// `revoked`, `mismatch` or `deactivated`. Reuse of a revoked token may mean it was stolen.
+    pub refreshes: IntCounterVec,
+    /// `http_rate_limited_total`: requests turned away by the rate limiter.
+    pub rate_limited: IntCounter,
+    db_connections: IntGaugeVec,
+    db_max_connections: IntGauge,
+}
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+pub fn metrics() -> &'static Metrics {
+    &METRICS
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new();
+        let http_labels = ["method", "route", "status"];
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests handled"),
+            &http_labels,
+        )
+        .unwrap();
+        let http_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "Time from receiving a request to sending the response head",
+            ),
+            &http_labels,
+        )
+        .unwrap();
+        let logins = IntCounterVec::new(
+            Opts::new("auth_logins_total", "Login attempts by outcome"),
+            &["outcome"],
+        )
+        .unwrap();
+        let refreshes = IntCounterVec::new(
+            Opts::new("auth_refresh_total", "Refresh token uses by outcome"),
+            &["outcome"],
+        )
+        .unwrap();
+        let rate_limited = IntCounter::new(
+            "http_rate_limited_total",
+            "Requests rejected by the rate limiter",
+        )
+        .unwrap();
+        let db_connections = IntGaugeVec::new(
+            Opts::new("db_pool_connections", "Database pool connections by state"),
+            &["state"],
+        )
+        .unwrap();
+        let db_max_connections = IntGauge::new(
+            "db_pool_max_connections",
+            "Configured size limit of the database pool",
+        )
+        .unwrap();
+
+        registry.register(Box::new(http_requests.clone())).unwrap();
+        registry.register(Box::new(http_duration.clone())).unwrap();
+        registry.register(Box::new(logins.clone())).unwrap();
+        registry.register(Box::new(refreshes.clone())).unwrap();
+        registry.register(Box::new(rate_limited.clone())).unwrap();
+        registry.register(Box::new(db_connections.clone())).unwrap();
+        registry
+            .register(Box::new(db_max_connections.clone()))
+            .unwrap();
+
+        Self {
+            registry,
+            http_requests,
+            http_duration,
+            logins,
+            refreshes,
+            rate_limited,
+            db_connections,
+            db_max_connections,
+        }
+    }
+
+    pub fn login(&self, outcome: &str) {
+        self.logins.with_label_values(&[outcome]).inc();
+    }
+
+    pub fn refresh(&self, outcome: &str) {
+        self.refreshes.with_label_values(&[outcome]).inc();
+    }
+
+    /// Text exposition format; pool gauges are sampled at scrape time.
+    pub fn render(&self, db: &Db) -> String {
+        let pool = &db.0;
+        let idle = pool.num_idle() as i64;
+        let open = i64::from(pool.size());
+        self.db_connections.with_label_values(&["idle"]).set(idle);
+        self.db_connections
+            .with_label_values(&["in_use"])
+            .set(open - idle);
+        self.db_max_connections
+            .set(i64::from(pool.options().get_max_connections()));
+
+        let mut out = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut out)
+            .expect("text encoding of gathered metrics");
+        String::from_utf8(out).expect("metrics text is UTF-8")
+    }
+}
+
+/// Peer-IP rate limiting that counts every rejection in
+/// [`Metrics::rate_limited`]. Rejections carry the error, so `ProblemDetails`
+/// renders them as problem+json. Health probes are whitelisted so a busy
+/// load balancer never sees 429 from `/healthz` or `/readyz`.
+#[derive(Debug, Clone, Copy)]
+pub struct CountingPeerIp;
+
+/// Rate-limit key: the peer IP, or [`RateKey::Probe`] for health checks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RateKey {
+    Peer(IpAddr),
+    Probe,
+}
+
+const PROBE_PATHS: [&str; 2] = ["/healthz", "/readyz"];
+
+impl KeyExtractor for CountingPeerIp {
+    type Key = RateKey;
+    type KeyExtractionError = <PeerIpKeyExtractor as KeyExtractor>::KeyExtractionError;
+
+    fn extract(&self, req: &ServiceRequest) -> Result<Self::Key, Self::KeyExtractionError> {
+        if PROBE_PATHS.contains(&req.path()) {
+            return Ok(RateKey::Probe);
+        }
+        PeerIpKeyExtractor.extract(req).map(RateKey::Peer)
+    }
+
+    fn whitelisted_keys(&self) -> Vec<Self::Key> {
+        vec![RateKey::Probe]
+    }
+
+    fn exceed_rate_limit_response(
+        &self,
+        _negative: &NotUntil<QuantaInstant>,
+        mut response: HttpResponseBuilder,
+    ) -> HttpResponse {
+        metrics().rate_limited.inc();
+        let mut res = HttpResponse::from_error(HttpApiError::rejected(
+            StatusCode::TOO_MANY_REQUESTS,
+            "too many requests",
+        ));
+        // keep the Retry-After headers governor set
+        let limited = response.finish();
+        for (name, value) in limited.headers() {
+            res.headers_mut().insert(name.clone(), value.clone());
+        }
+        res
+    }
+}
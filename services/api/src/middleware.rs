@@ -1,7 +1,17 @@
-use actix_web::Error;
+use crate::error::{PROBLEM_JSON, Problem};
+use crate::extractors::{REQUEST_ID_HEADER, audit_context};
+use crate::metrics::metrics;
+use actix_web::body::{BoxBody, MessageBody};
 use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::Method;
+use actix_web::http::header::{self, HeaderName, HeaderValue};
+use actix_web::{Error, web};
+use db::AuditEvent;
 use futures_util::future::{LocalBoxFuture, Ready, ok};
 use std::rc::Rc;
+use std::time::Instant;
+use tracing::Instrument;
+use uuid::Uuid;
 
 // Middleware struct
 pub struct Csrf;
@@ -57,3 +67,343 @@ where
         })
     }
 }
+
+/// Writes every mutating request (POST/PUT/PATCH/DELETE) to the audit log with
+/// the actor, path and response status. Must run inside the JWT auth layer so
+/// the actor is known.
+pub struct AuditTrail;
+
+impl<S, B> Transform<S, ServiceRequest> for AuditTrail
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = AuditTrailMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(AuditTrailMiddleware {
+            service: Rc::new(service),
+        })
+    }
+}
+
+pub struct AuditTrailMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for AuditTrailMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &self,
+        ctx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(ctx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let method = req.method().clone();
+        if !matches!(
+            method,
+            Method::POST | Method::PUT | Method::PATCH | Method::DELETE
+        ) {
+            return Box::pin(self.service.call(req));
+        }
+
+        let db = req.app_data::<web::Data<db::Db>>().cloned();
+        let ctx = audit_context(req.request());
+        let path = req.path().to_string();
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let res = fut.await;
+            let status = match &res {
+                Ok(r) => r.status(),
+                Err(e) => e.as_response_error().status_code(),
+            };
+            if let Some(db) = db {
+                let action = format!("http.{}", method.as_str().to_ascii_lowercase());
+                let ev = AuditEvent {
+                    action: &action,
+                    resource_type: "http",
+                    resource_id: Some(path),
+                    diff: Some(serde_json::json!({ "status": status.as_u16() })),
+                };
+                if let Err(e) = db::audit::record(&db, &ctx, ev).await {
+                    tracing::error!(error = %e, "failed to write audit log");
+                }
+            }
+            res
+        })
+    }
+}
+
+/// Renders every error response as `application/problem+json`, including
+/// actix's own extractor and payload errors, and adds the request id. Server
+/// errors are logged here with their cause, which never reaches the body.
+/// Wraps everything but [`RequestTrace`], which assigns the id.
+pub struct ProblemDetails;
+
+impl<S, B> Transform<S, ServiceRequest> for ProblemDetails
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Transform = ProblemDetailsMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(ProblemDetailsMiddleware {
+            service: Rc::new(service),
+        })
+    }
+}
+
+pub struct ProblemDetailsMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for ProblemDetailsMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &self,
+        ctx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(ctx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            // handler errors arrive as responses; an `Err` here is rendered by
+            // its own `error_response`
+            let res = fut.await?.map_into_boxed_body();
+            let Some(err) = res.response().error() else {
+                return Ok(res);
+            };
+            let mut problem = Problem::from_error(err);
+            if problem.status().is_server_error() {
+                tracing::error!(error = ?err, path = %res.request().path(), "request failed");
+            }
+            problem.request_id = res
+                .request()
+                .headers()
+                .get(REQUEST_ID_HEADER)
+                .and_then(|v| v.to_str().ok())
+                .map(|s| s.to_string());
+            let body = problem.body();
+            Ok(res.map_body(|head, _| {
+                head.headers_mut()
+                    .insert(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+                BoxBody::new(body)
+            }))
+        })
+    }
+}
+
+/// Gives every request an id and a tracing span. A client-supplied
+/// `X-Request-Id` is kept if it is short and plain ASCII, otherwise a new UUID
+/// replaces it; either way the id is put back on the request (for the audit
+/// log and error bodies) and echoed on the response. The `http.request` span
+/// carries method, path, matched route, status and latency, so every event
+/// logged while handling the request is tied to its id. Only the path is
+/// recorded: query strings can hold codes and tokens. Must be the outermost
+/// layer of the app.
+pub struct RequestTrace;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestTrace
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequestTraceMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RequestTraceMiddleware {
+            service: Rc::new(service),
+        })
+    }
+}
+
+pub struct RequestTraceMiddleware<S> {
+    service: Rc<S>,
+}
+
+fn acceptable_request_id(id: &str) -> bool {
+    (1..=128).contains(&id.len())
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b':'))
+}
+
+impl<S, B> Service<ServiceRequest> for RequestTraceMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &self,
+        ctx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(ctx)
+    }
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let request_id = req
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .filter(|id| acceptable_request_id(id))
+            .map(str::to_owned)
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+        let header_value =
+            HeaderValue::from_str(&request_id).expect("request ids are visible ASCII");
+        req.headers_mut().insert(
+            HeaderName::from_static("x-request-id"),
+            header_value.clone(),
+        );
+
+        let span = tracing::info_span!(
+            "http.request",
+            method = %req.method(),
+            path = %req.path(),
+            request_id = %request_id,
+            route = tracing::field::Empty,
+            status = tracing::field::Empty,
+            latency_ms = tracing::field::Empty,
+        );
+        #[cfg(feature = "otel")]
+        crate::telemetry::continue_remote_trace(&span, req.headers());
+        let started = Instant::now();
+        let fut = span.in_scope(|| self.service.call(req));
+        Box::pin(
+            async move {
+                let res = fut.await;
+                let span = tracing::Span::current();
+                let status = match &res {
+                    Ok(r) => {
+                        if let Some(route) = r.request().match_pattern() {
+                            span.record("route", route.as_str());
+                        }
+                        r.status()
+                    }
+                    Err(e) => e.as_response_error().status_code(),
+                };
+                span.record("status", status.as_u16());
+                span.record("latency_ms", started.elapsed().as_millis() as u64);
+                tracing::info!("request completed");
+                res.map(|mut r| {
+                    r.headers_mut()
+                        .insert(HeaderName::from_static("x-request-id"), header_value);
+                    r
+                })
+            }
+            .instrument(span),
+        )
+    }
+}
+
+/// Counts requests and observes their latency per method, matched route
+/// pattern and final status. Wrapped outside `ProblemDetails` so rejected
+/// requests are recorded with the status the client actually sees.
+pub struct HttpMetrics;
+
+impl<S, B> Transform<S, ServiceRequest> for HttpMetrics
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = HttpMetricsMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(HttpMetricsMiddleware {
+            service: Rc::new(service),
+        })
+    }
+}
+
+pub struct HttpMetricsMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for HttpMetricsMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &self,
+        ctx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(ctx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let method = req.method().to_string();
+        let started = Instant::now();
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let res = fut.await;
+            // Raw paths would give every id its own series.
+            let (route, status) = match &res {
+                Ok(r) => (
+                    r.request()
+                        .match_pattern()
+                        .unwrap_or_else(|| "unmatched".into()),
+                    r.status(),
+                ),
+                Err(e) => ("unmatched".into(), e.as_response_error().status_code()),
+            };
+            let labels = [method.as_str(), route.as_str(), status.as_str()];
+            let m = metrics();
+            m.http_requests.with_label_values(&labels).inc();
+            m.http_duration
+                .with_label_values(&labels)
+                .observe(started.elapsed().as_secs_f64());
+            res
+        })
+    }
+}
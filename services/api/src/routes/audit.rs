@@ -0,0 +1,54 @@
+use crate::error::HttpApiError;
+use crate::extractors::require_role;
+use crate::schemas::AuditQuery;
+use actix_web::{HttpRequest, HttpResponse, get, web};
+use common::{AppError, ListParams};
+use db::Db;
+use db::audit::{AUDIT_SORTS, AuditFilter, search, verify_chain};
+
+/// Admin audit search, newest first, paginated like `/items`;
+/// `created_from` / `created_to` bound when the event happened.
+#[get("/audit")]
+pub async fn list(
+    data: web::Data<Db>,
+    params: web::Query<ListParams>,
+    query: web::Query<AuditQuery>,
+    req: HttpRequest,
+) -> actix_web::Result<HttpResponse> {
+    require_role(&req, "Admin")?;
+    let page = params
+        .validate(&AUDIT_SORTS, "-id")
+        .map_err(HttpApiError::from)?;
+    let bad_request = |msg: &str| HttpApiError::App(AppError::BadRequest(msg.into()));
+    if page.q.is_some() {
+        return Err(bad_request("q is not supported on /audit").into());
+    }
+    if page
+        .after
+        .as_ref()
+        .is_some_and(|c| c.value.parse::<i64>().is_err())
+    {
+        return Err(bad_request("invalid cursor").into());
+    }
+    let rows = search(
+        &data,
+        &AuditFilter {
+            actor_id: query.actor_id,
+            action: query.action.as_deref(),
+            resource_type: query.resource_type.as_deref(),
+            resource_id: query.resource_id.as_deref(),
+        },
+        &page,
+    )
+    .await
+    .map_err(HttpApiError::from)?;
+    Ok(HttpResponse::Ok().json(rows))
+}
+
+/// Recomputes the hash chain and reports the first tampered row, if any.
+#[get("/audit/verify")]
+pub async fn verify(data: web::Data<Db>, req: HttpRequest) -> actix_web::Result<HttpResponse> {
+    require_role(&req, "Admin")?;
+    let report = verify_chain(&data).await.map_err(HttpApiError::from)?;
+    Ok(HttpResponse::Ok().json(report))
+}
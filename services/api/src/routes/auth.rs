@@ -1,92 +1,120 @@
 use crate::error::HttpApiError;
+use crate::metrics::metrics;
 use crate::{
+    extractors::ValidatedJson,
     schemas::{LoginInput, RegisterInput},
     state::AppState,
 };
+use actix_web::http::StatusCode;
 use actix_web::{HttpRequest, HttpResponse, post, web};
 use auth::{hash_password, sha256_hex, sign_access, sign_refresh, verify_password};
 use chrono::{Duration, Utc};
+use common::DoctorUserRow;
 use db::{
-    find_doctor_by_reg_no, get_refresh_by_jti, insert_doctor_user, insert_refresh, revoke_refresh,
+    Db, NewDoctor, doctor_roll_name, find_doctor_by_reg_no, get_doctor, get_refresh_by_jti,
+    insert_doctor_user, insert_refresh, register_with_invitation, revoke_refresh,
 };
 use serde_json::json;
 
 const ACCESS_COOKIE: &str = "access_token";
 const REFRESH_COOKIE: &str = "refresh_token";
 const CSRF_COOKIE: &str = "csrf_token";
+
+/// JWT role for a doctor, derived from their `doctor_rolls` assignment.
+async fn role_for(db: &Db, doctor: &DoctorUserRow) -> Result<&'static str, HttpApiError> {
+    let roll = match doctor.doctor_roll {
+        Some(id) => doctor_roll_name(db, id).await?,
+        None => None,
+    };
+    Ok(match roll.as_deref() {
+        Some("admin") => "Admin",
+        Some("privacy_officer") => "PrivacyOfficer",
+        Some("department_head") => "DepartmentHead",
+        _ => "Doctor",
+    })
+}
+
 #[post("/auth/register")]
+#[tracing::instrument(skip_all, fields(doctor_id = tracing::field::Empty))]
 pub async fn register(
     data: web::Data<AppState>,
-    payload: web::Json<RegisterInput>,
+    payload: ValidatedJson<RegisterInput>,
 ) -> actix_web::Result<HttpResponse> {
     let payload = payload.into_inner();
-    println!("➡️ REGISTER STARTED with reg_no={}", payload.reg_no);
-
     // 1️⃣ давхцсан doctor шалгах
     if find_doctor_by_reg_no(&data.db, &payload.reg_no)
         .await
-        .map_err(|e| {
-            println!("❌ DB FIND ERROR: {:?}", e);
-            actix_web::error::ErrorInternalServerError("db error")
-        })?
+        .map_err(HttpApiError::from)?
         .is_some()
     {
-        println!("⚠️ REG_NO already exists");
-        return Err(actix_web::error::ErrorConflict("reg_no already exists"));
+        tracing::info!("registration rejected: reg_no already exists");
+        return Err(HttpApiError::rejected(StatusCode::CONFLICT, "reg_no already exists").into());
     }
 
     // 2️⃣ password hash үүсгэх
-    let hash = match hash_password(&payload.password) {
-        Ok(h) => h,
-        Err(e) => {
-            println!("❌ HASH ERROR: {:?}", e);
-            return Err(actix_web::error::ErrorInternalServerError("hash error"));
+    let hash = hash_password(payload.password.expose())
+        .map_err(|e| HttpApiError::Internal(format!("hash password: {e}")))?;
+
+    // 3️⃣ doctor_user insert (invitation-аар эсвэл нээлттэй бүртгэл)
+    let new_doctor = NewDoctor {
+        reg_no: &payload.reg_no,
+        first_name: &payload.first_name,
+        last_name: &payload.last_name,
+        rank_name: payload.rank_name.as_deref(),
+        org_name: payload.org_name.as_deref(),
+        org_id: data.open_registration.unwrap_or_default(),
+        position: payload.position.as_deref(),
+        birth_date: payload.birth_date,
+        gender: payload.gender.as_deref(),
+        doctor_roll: None,
+        department: None,
+        password_hash: &hash,
+    };
+    let inserted = match &payload.invitation_code {
+        Some(code) => {
+            let code_hash = format!("sha256:{}", sha256_hex(code.expose()));
+            register_with_invitation(&data.db, &code_hash, new_doctor).await
+        }
+        None if data.open_registration.is_some() => {
+            insert_doctor_user(&data.db, &new_doctor).await.map(Some)
+        }
+        None => {
+            return Err(HttpApiError::rejected(
+                StatusCode::FORBIDDEN,
+                "registration requires an invitation",
+            )
+            .into());
         }
     };
-
-    // 3️⃣ doctor_user insert
-    let doctor = match insert_doctor_user(
-        &data.db,
-        &payload.reg_no,
-        &payload.first_name,
-        &payload.last_name,
-        payload.rank_name.as_deref(),
-        payload.org_name.as_deref(),
-        payload.org_id,
-        payload.position.as_deref(),
-        payload.birth_date,
-        payload.gender.as_deref(),
-        payload.doctor_roll,
-        &hash,
-    )
-    .await
-    {
-        Ok(d) => d,
-        Err(e) => {
-            println!("❌ INSERT ERROR: {:?}", e);
-            return Err(actix_web::error::ErrorInternalServerError("insert error"));
+    let doctor = match inserted {
+        Ok(Some(d)) => d,
+        Ok(None) => {
+            return Err(HttpApiError::rejected(
+                StatusCode::FORBIDDEN,
+                "invalid or expired invitation",
+            )
+            .into());
         }
+        Err(e) => return Err(HttpApiError::from(e).into()),
     };
-
-    println!("✅ INSERT SUCCESS id={}", doctor.id);
+    tracing::Span::current().record("doctor_id", tracing::field::display(doctor.id));
+    tracing::info!(org_id = doctor.org_id, "doctor registered");
 
     // 4️⃣ JWT үүсгэх
+    let role = role_for(&data.db, &doctor).await?;
     let keys = &data.jwt;
-    let access = sign_access(keys, doctor.id, "Doctor", data.access_ttl)
-        .map_err(|_| actix_web::error::ErrorInternalServerError("sign access"))?;
-    let (refresh_token, claims) = sign_refresh(keys, doctor.id, "Doctor", data.refresh_ttl)
-        .map_err(|_| actix_web::error::ErrorInternalServerError("sign refresh"))?;
+    let access = sign_access(keys, doctor.id, role, doctor.org_id, data.access_ttl)
+        .map_err(|e| HttpApiError::Internal(format!("sign access: {e}")))?;
+    let (refresh_token, claims) =
+        sign_refresh(keys, doctor.id, role, doctor.org_id, data.refresh_ttl)
+            .map_err(|e| HttpApiError::Internal(format!("sign refresh: {e}")))?;
 
     // 5️⃣ Refresh DB
     let token_hash = format!("sha256:{}", sha256_hex(&refresh_token));
     let expires_at = Utc::now() + chrono::Duration::seconds(data.refresh_ttl);
-    if let Err(e) = insert_refresh(&data.db, doctor.id, &claims.jti, &token_hash, expires_at).await
-    {
-        println!("❌ REFRESH INSERT ERROR: {:?}", e);
-        return Err(actix_web::error::ErrorInternalServerError("insert refresh"));
-    }
-
-    println!("✅ GISTER DONE for {}", payload.reg_no);
+    insert_refresh(&data.db, doctor.id, &claims.jti, &token_hash, expires_at)
+        .await
+        .map_err(HttpApiError::from)?;
 
     Ok(HttpResponse::Created().json(json!({
         "doctor": {
@@ -105,41 +133,53 @@ pub async fn register(
 
 /// 🧠 Login doctor
 #[post("/auth/login")]
+#[tracing::instrument(skip_all, fields(doctor_id = tracing::field::Empty))]
 pub async fn login(
     data: web::Data<AppState>,
-    payload: web::Json<LoginInput>,
+    payload: ValidatedJson<LoginInput>,
 ) -> actix_web::Result<HttpResponse> {
     let payload = payload.into_inner();
 
     // 1️⃣ reg_no-гоор doctor хайх
     let doctor = find_doctor_by_reg_no(&data.db, &payload.reg_no)
         .await
-        .map_err(|_| actix_web::error::ErrorInternalServerError("db error"))?;
+        .map_err(HttpApiError::from)?;
 
     let doctor = if let Some(doc) = doctor {
         doc
     } else {
-        return Err(actix_web::error::ErrorUnauthorized("invalid credentials"));
+        metrics().login("unknown_user");
+        return Err(HttpApiError::rejected(StatusCode::UNAUTHORIZED, "invalid credentials").into());
     };
 
     // 2️⃣ password verify хийх
-    if !verify_password(&payload.password, &doctor.password_hash) {
-        return Err(actix_web::error::ErrorUnauthorized("invalid password"));
+    tracing::Span::current().record("doctor_id", tracing::field::display(doctor.id));
+    if !verify_password(payload.password.expose(), &doctor.password_hash) {
+        tracing::info!("login rejected: wrong password");
+        metrics().login("bad_password");
+        return Err(HttpApiError::rejected(StatusCode::UNAUTHORIZED, "invalid credentials").into());
+    }
+    if !doctor.is_active {
+        metrics().login("deactivated");
+        return Err(HttpApiError::rejected(StatusCode::FORBIDDEN, "account deactivated").into());
     }
 
     // 3️⃣ JWT үүсгэх
+    let role = role_for(&data.db, &doctor).await?;
     let keys = &data.jwt;
-    let access = sign_access(keys, doctor.id, "Doctor", data.access_ttl)
-        .map_err(|_| actix_web::error::ErrorInternalServerError("sign access"))?;
-    let (refresh_token, claims) = sign_refresh(keys, doctor.id, "Doctor", data.refresh_ttl)
-        .map_err(|_| actix_web::error::ErrorInternalServerError("sign refresh"))?;
+    let access = sign_access(keys, doctor.id, role, doctor.org_id, data.access_ttl)
+        .map_err(|e| HttpApiError::Internal(format!("sign access: {e}")))?;
+    let (refresh_token, claims) =
+        sign_refresh(keys, doctor.id, role, doctor.org_id, data.refresh_ttl)
+            .map_err(|e| HttpApiError::Internal(format!("sign refresh: {e}")))?;
 
     // 4️⃣ Refresh токен DB-д хадгалах
     let token_hash = format!("sha256:{}", sha256_hex(&refresh_token));
     let expires_at = Utc::now() + chrono::Duration::seconds(data.refresh_ttl);
     insert_refresh(&data.db, doctor.id, &claims.jti, &token_hash, expires_at)
         .await
-        .map_err(|_| actix_web::error::ErrorInternalServerError("insert refresh"))?;
+        .map_err(HttpApiError::from)?;
+    metrics().login("success");
 
     // ✅ Response
     Ok(HttpResponse::Ok().json(json!({
@@ -158,21 +198,20 @@ pub async fn login(
 }
 
 #[post("/auth/refresh")]
+#[tracing::instrument(skip_all, fields(doctor_id = tracing::field::Empty))]
 pub async fn refresh(
     req: HttpRequest,
     data: web::Data<AppState>,
 ) -> actix_web::Result<HttpResponse> {
-    let refresh_cookie = req
-        .cookie(REFRESH_COOKIE)
-        .ok_or_else(|| actix_web::error::ErrorUnauthorized("no refresh"))?;
+    let rejected = || {
+        metrics().refresh("invalid");
+        HttpApiError::Auth
+    };
+    let refresh_cookie = req.cookie(REFRESH_COOKIE).ok_or_else(rejected)?;
     let token = refresh_cookie.value().to_string();
-    let claims = auth::verify(&data.jwt, &token)
-        .map_err(|_| actix_web::error::ErrorUnauthorized("bad refresh"))?;
+    let claims = auth::verify(&data.jwt, &token).map_err(|_| rejected())?;
 
-    println!(
-        "➡️ REFRESH STARTED for sub={} jti={}",
-        claims.sub, claims.jti
-    );
+    tracing::Span::current().record("doctor_id", tracing::field::display(claims.sub));
 
     // 🔍 DB check
     if let Some(row) = get_refresh_by_jti(&data.db, &claims.jti)
@@ -180,36 +219,50 @@ pub async fn refresh(
         .map_err(HttpApiError::from)?
     {
         if row.revoked {
-            println!("❌ REFRESH: token revoked");
-            return Err(actix_web::error::ErrorUnauthorized("revoked"));
+            tracing::warn!("refresh rejected: token already revoked");
+            metrics().refresh("revoked");
+            return Err(HttpApiError::Auth.into());
         }
         let given_hash = format!("sha256:{}", sha256_hex(&token));
         if given_hash != row.token_hash {
-            println!("❌ REFRESH: token mismatch");
-            return Err(actix_web::error::ErrorUnauthorized("mismatch"));
+            tracing::warn!("refresh rejected: token hash mismatch");
+            metrics().refresh("mismatch");
+            return Err(HttpApiError::Auth.into());
         }
     } else {
-        println!("❌ REFRESH: missing refresh token in DB");
-        return Err(actix_web::error::ErrorUnauthorized("missing"));
+        tracing::warn!("refresh rejected: unknown token");
+        metrics().refresh("unknown");
+        return Err(HttpApiError::Auth.into());
     }
 
-    println!("✅ REFRESH: passed validation, rotating...");
-
     revoke_refresh(&data.db, &claims.jti)
         .await
         .map_err(crate::error::HttpApiError::from)?;
 
-    let access = auth::sign_access(&data.jwt, claims.sub, &claims.role, data.access_ttl)
-        .map_err(|_| HttpApiError::Auth)?;
+    // Role and org come from the current row, never the old token, so role
+    // changes, org moves and deactivation take effect on the next refresh.
+    let doctor = get_doctor(&data.db, claims.sub)
+        .await
+        .map_err(HttpApiError::from)?
+        .ok_or_else(|| {
+            metrics().refresh("unknown");
+            HttpApiError::Auth
+        })?;
+    if !doctor.is_active {
+        tracing::info!("refresh rejected: account deactivated");
+        metrics().refresh("deactivated");
+        return Err(HttpApiError::rejected(StatusCode::FORBIDDEN, "account deactivated").into());
+    }
+    let role = role_for(&data.db, &doctor).await?;
+    let access = auth::sign_access(&data.jwt, doctor.id, role, doctor.org_id, data.access_ttl)
+        .map_err(|e| HttpApiError::Internal(format!("sign access: {e}")))?;
     let (refresh_new, claims_new) =
-        auth::sign_refresh(&data.jwt, claims.sub, &claims.role, data.refresh_ttl)
-            .map_err(|_| HttpApiError::Auth)?;
-
-    println!("✅ REFRESH: generated new access and refresh");
+        auth::sign_refresh(&data.jwt, doctor.id, role, doctor.org_id, data.refresh_ttl)
+            .map_err(|e| HttpApiError::Internal(format!("sign refresh: {e}")))?;
 
     let token_hash = format!("sha256:{}", sha256_hex(&refresh_new));
     let expires_at = Utc::now() + Duration::seconds(data.refresh_ttl);
-    let _ = insert_refresh(
+    insert_refresh(
         &data.db,
         claims.sub,
         &claims_new.jti,
@@ -219,8 +272,6 @@ pub async fn refresh(
     .await
     .map_err(HttpApiError::from)?;
 
-    println!("✅ REFRESH: inserted new refresh record");
-
     let c = actix_web::cookie::Cookie::build(REFRESH_COOKIE, refresh_new)
         .domain(data.cookie_domain.clone())
         .secure(data.cookie_secure)
@@ -228,14 +279,13 @@ pub async fn refresh(
         .path("/")
         .finish();
 
-    println!("✅ REFRESH: cookie built successfully");
-
     let mut resp = HttpResponse::Ok().json(json!({
         "access_token": access
     }));
 
     resp.add_cookie(&c).ok();
-    println!("✅ REFRESH: response ready");
+    tracing::debug!("refresh token rotated");
+    metrics().refresh("rotated");
     Ok(resp)
 }
 
@@ -244,12 +294,12 @@ pub async fn logout(
     req: HttpRequest,
     data: web::Data<AppState>,
 ) -> actix_web::Result<HttpResponse> {
-    if let Some(c) = req.cookie(REFRESH_COOKIE) {
-        if let Ok(claims) = auth::verify(&data.jwt, c.value()) {
-            let _ = revoke_refresh(&data.db, &claims.jti)
-                .await
-                .map_err(HttpApiError::from)?;
-        }
+    if let Some(c) = req.cookie(REFRESH_COOKIE)
+        && let Ok(claims) = auth::verify(&data.jwt, c.value())
+    {
+        revoke_refresh(&data.db, &claims.jti)
+            .await
+            .map_err(HttpApiError::from)?;
     }
     let clear = |name: &'static str| {
         actix_web::cookie::Cookie::build(name, "")
@@ -0,0 +1,126 @@
+use crate::error::HttpApiError;
+use crate::extractors::{AuthUser, ValidatedJson, require_role, require_self_or_admin};
+use crate::schemas::{CredentialIn, ExpiringQuery};
+use actix_web::{HttpRequest, HttpResponse, delete, get, post, web};
+use common::AppError;
+use db::{
+    Db, NewCredential, delete_credential, expiring_credentials, get_doctor, has_active_license,
+    insert_credential, list_credentials,
+};
+use uuid::Uuid;
+
+const CREDENTIAL_KINDS: [&str; 3] = ["license", "specialty", "certification"];
+const DEFAULT_EXPIRY_WINDOW_DAYS: i32 = 30;
+
+/// 404 unless `doctor_id` is a doctor of the caller's own org.
+async fn require_colleague(db: &Db, user: &AuthUser, doctor_id: Uuid) -> Result<(), HttpApiError> {
+    match get_doctor(db, doctor_id).await? {
+        Some(doctor) if doctor.org_id == user.org_id => Ok(()),
+        _ => Err(HttpApiError::App(AppError::NotFound)),
+    }
+}
+
+/// A doctor's credentials plus whether they may currently prescribe.
+#[get("/doctors/{id}/credentials")]
+pub async fn list(
+    data: web::Data<Db>,
+    path: web::Path<Uuid>,
+    user: AuthUser,
+    req: HttpRequest,
+) -> actix_web::Result<HttpResponse> {
+    let doctor_id = path.into_inner();
+    require_self_or_admin(&req, doctor_id)?;
+    require_colleague(&data, &user, doctor_id).await?;
+    let rows = list_credentials(&data, doctor_id)
+        .await
+        .map_err(HttpApiError::from)?;
+    let can_prescribe = has_active_license(&data, doctor_id)
+        .await
+        .map_err(HttpApiError::from)?;
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "credentials": rows,
+        "can_prescribe": can_prescribe,
+    })))
+}
+
+#[post("/doctors/{id}/credentials")]
+pub async fn create(
+    data: web::Data<Db>,
+    path: web::Path<Uuid>,
+    body: ValidatedJson<CredentialIn>,
+    user: AuthUser,
+    req: HttpRequest,
+) -> actix_web::Result<HttpResponse> {
+    require_role(&req, "Admin")?;
+    let doctor_id = path.into_inner();
+    if !CREDENTIAL_KINDS.contains(&body.kind.as_str()) {
+        return Err(HttpApiError::App(AppError::BadRequest(format!(
+            "kind must be one of {}",
+            CREDENTIAL_KINDS.join(", ")
+        )))
+        .into());
+    }
+    if let (Some(issued), Some(expires)) = (body.issued_on, body.expires_on)
+        && expires < issued
+    {
+        return Err(HttpApiError::App(AppError::BadRequest(
+            "expires_on is before issued_on".into(),
+        ))
+        .into());
+    }
+    require_colleague(&data, &user, doctor_id).await?;
+
+    let row = insert_credential(
+        &data,
+        &NewCredential {
+            doctor_id,
+            kind: &body.kind,
+            title: &body.title,
+            number: body.number.as_deref(),
+            issuer: body.issuer.as_deref(),
+            issued_on: body.issued_on,
+            expires_on: body.expires_on,
+        },
+    )
+    .await
+    .map_err(HttpApiError::from)?;
+    Ok(HttpResponse::Created().json(row))
+}
+
+#[delete("/doctors/{id}/credentials/{credential_id}")]
+pub async fn remove(
+    data: web::Data<Db>,
+    path: web::Path<(Uuid, Uuid)>,
+    user: AuthUser,
+    req: HttpRequest,
+) -> actix_web::Result<HttpResponse> {
+    require_role(&req, "Admin")?;
+    let (doctor_id, credential_id) = path.into_inner();
+    require_colleague(&data, &user, doctor_id).await?;
+    let affected = delete_credential(&data, doctor_id, credential_id)
+        .await
+        .map_err(HttpApiError::from)?;
+    if affected == 0 {
+        return Err(HttpApiError::App(AppError::NotFound).into());
+    }
+    Ok(HttpResponse::Ok().json(serde_json::json!({"deleted": affected})))
+}
+
+/// HR alert list: credentials expiring within `days` in the admin's org.
+#[get("/credentials/expiring")]
+pub async fn expiring(
+    data: web::Data<Db>,
+    query: web::Query<ExpiringQuery>,
+    user: AuthUser,
+    req: HttpRequest,
+) -> actix_web::Result<HttpResponse> {
+    require_role(&req, "Admin")?;
+    let days = query.days.unwrap_or(DEFAULT_EXPIRY_WINDOW_DAYS);
+    if days < 0 {
+        return Err(HttpApiError::App(AppError::BadRequest("days must be >= 0".into())).into());
+    }
+    let rows = expiring_credentials(&data, user.org_id, days)
+        .await
+        .map_err(HttpApiError::from)?;
+    Ok(HttpResponse::Ok().json(rows))
+}
@@ -0,0 +1,127 @@
+use crate::error::HttpApiError;
+use crate::extractors::{Audit, AuthUser, ValidatedJson, require_role};
+use crate::schemas::{DoctorAdminUpdateInput, DoctorSearchQuery, ProfileUpdateInput};
+use actix_web::{HttpRequest, HttpResponse, delete, get, patch, web};
+use common::{AppError, ListParams};
+use db::{
+    DOCTOR_SORTS, Db, DoctorAdminUpdate, DoctorProfileUpdate, DoctorSearch, admin_update_doctor,
+    deactivate_doctor, get_doctor, search_doctors, update_doctor_profile,
+};
+use uuid::Uuid;
+
+/// Profile of the logged-in doctor.
+#[get("/me")]
+pub async fn me(data: web::Data<Db>, user: AuthUser) -> actix_web::Result<HttpResponse> {
+    let doctor = get_doctor(&data, user.user_id)
+        .await
+        .map_err(HttpApiError::from)?
+        .ok_or(HttpApiError::App(AppError::NotFound))?;
+    Ok(HttpResponse::Ok().json(doctor))
+}
+
+/// Merge patch (RFC 7396): absent fields stay, `null` clears an optional one.
+#[patch("/me")]
+pub async fn update_me(
+    data: web::Data<Db>,
+    body: ValidatedJson<ProfileUpdateInput>,
+    user: AuthUser,
+) -> actix_web::Result<HttpResponse> {
+    let upd = DoctorProfileUpdate {
+        first_name: body
+            .first_name
+            .as_deref()
+            .required("first_name")
+            .map_err(HttpApiError::from)?,
+        last_name: body
+            .last_name
+            .as_deref()
+            .required("last_name")
+            .map_err(HttpApiError::from)?,
+        rank_name: body.rank_name.as_deref().into_update(),
+        position: body.position.as_deref().into_update(),
+        phone: body.phone.as_deref().into_update(),
+        email: body.email.as_deref().into_update(),
+    };
+    let doctor = update_doctor_profile(&data, user.user_id, &upd)
+        .await
+        .map_err(HttpApiError::from)?
+        .ok_or(HttpApiError::App(AppError::NotFound))?;
+    Ok(HttpResponse::Ok().json(doctor))
+}
+
+/// Admin directory of the admin's own org, paginated like `/items`: `?q=`
+/// searches name, reg_no and org name, plus a `department` filter.
+#[get("/doctors")]
+pub async fn list(
+    data: web::Data<Db>,
+    params: web::Query<ListParams>,
+    query: web::Query<DoctorSearchQuery>,
+    user: AuthUser,
+    req: HttpRequest,
+) -> actix_web::Result<HttpResponse> {
+    require_role(&req, "Admin")?;
+    let page = params
+        .validate(&DOCTOR_SORTS, "last_name")
+        .map_err(HttpApiError::from)?;
+    let search = DoctorSearch {
+        org_id: user.org_id,
+        department: query.department.as_deref(),
+    };
+    let rows = search_doctors(&data, &search, &page)
+        .await
+        .map_err(HttpApiError::from)?;
+    Ok(HttpResponse::Ok().json(rows))
+}
+
+/// Admin-only role / department changes and reactivation within the admin's
+/// own org. Doctors elsewhere are 404; moving one to another org is refused.
+#[patch("/doctors/{id}")]
+pub async fn update(
+    data: web::Data<Db>,
+    path: web::Path<Uuid>,
+    body: ValidatedJson<DoctorAdminUpdateInput>,
+    user: AuthUser,
+    req: HttpRequest,
+) -> actix_web::Result<HttpResponse> {
+    require_role(&req, "Admin")?;
+    if body.org_id.is_some_and(|org_id| org_id != user.org_id) {
+        return Err(HttpApiError::App(AppError::Forbidden).into());
+    }
+    let upd = DoctorAdminUpdate {
+        org_id: body.org_id,
+        org_name: body.org_name.as_deref(),
+        department: body.department.as_deref(),
+        doctor_roll: body.doctor_roll,
+        is_active: body.is_active,
+    };
+    let doctor = admin_update_doctor(&data, path.into_inner(), user.org_id, &upd)
+        .await
+        .map_err(HttpApiError::from)?
+        .ok_or(HttpApiError::App(AppError::NotFound))?;
+    Ok(HttpResponse::Ok().json(doctor))
+}
+
+/// Admin-only, within the admin's own org. Doctors are deactivated rather than
+/// deleted, so their records stay attributable; `PATCH /doctors/{id}` with
+/// `is_active: true` reverses it.
+#[delete("/doctors/{id}")]
+pub async fn deactivate(
+    data: web::Data<Db>,
+    path: web::Path<Uuid>,
+    req: HttpRequest,
+    user: AuthUser,
+    audit: Audit,
+) -> actix_web::Result<HttpResponse> {
+    require_role(&req, "Admin")?;
+    let id = path.into_inner();
+    if id == user.user_id {
+        return Err(
+            HttpApiError::App(AppError::BadRequest("cannot deactivate yourself".into())).into(),
+        );
+    }
+    let doctor = deactivate_doctor(&data, &audit.0, id, user.org_id, user.user_id)
+        .await
+        .map_err(HttpApiError::from)?
+        .ok_or(HttpApiError::App(AppError::NotFound))?;
+    Ok(HttpResponse::Ok().json(doctor))
+}
@@ -0,0 +1,230 @@
+use crate::error::HttpApiError;
+use crate::extractors::{Audit, AuthUser};
+use crate::routes::patients::{authorize_owner, authorize_read};
+use crate::state::AppState;
+use actix_multipart::Multipart;
+use actix_web::http::header::{self, ContentDisposition, DispositionParam, DispositionType};
+use actix_web::{HttpResponse, delete, get, post, web};
+use common::AppError;
+use db::{
+    NewDocument, delete_document, get_document, insert_document, list_documents, restore_document,
+};
+use futures_util::StreamExt;
+use storage::StoredBlob;
+use uuid::Uuid;
+
+const DOCUMENT_KINDS: [&str; 5] = ["referral", "consent_form", "image", "lab_report", "other"];
+
+/// Sniffed content types accepted for upload: scans, photos and DICOM.
+const ALLOWED_TYPES: [&str; 6] = [
+    "application/pdf",
+    "image/jpeg",
+    "image/png",
+    "image/tiff",
+    "image/webp",
+    "application/dicom",
+];
+
+fn bad_request(msg: &str) -> HttpApiError {
+    HttpApiError::App(AppError::BadRequest(msg.into()))
+}
+
+/// Keeps the last path segment of a client-supplied name.
+fn clean_file_name(raw: Option<&str>) -> String {
+    let name = raw
+        .and_then(|n| n.rsplit(['/', '\\']).next())
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .unwrap_or("document");
+    name.chars().take(255).collect()
+}
+
+/// Multipart upload: a `file` part and an optional `kind` part. The bytes are
+/// streamed to blob storage; the content type is sniffed, not trusted.
+#[post("/patients/{id}/documents")]
+pub async fn upload(
+    data: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    mut payload: Multipart,
+    user: AuthUser,
+    audit: Audit,
+) -> actix_web::Result<HttpResponse> {
+    let patient_id = path.into_inner();
+    let t = authorize_owner(&data.db, &user, patient_id).await?;
+
+    let id = Uuid::new_v4();
+    let key = format!("documents/{}/{patient_id}/{id}", t.org_id());
+    let mut stored: Option<(String, StoredBlob)> = None;
+
+    let result: actix_web::Result<HttpResponse> = async {
+        let kind = read_parts(&data, &key, &mut payload, &mut stored).await?;
+        let Some((file_name, blob)) = &stored else {
+            return Err(bad_request("file part is required").into());
+        };
+        if !DOCUMENT_KINDS.contains(&kind.as_str()) {
+            return Err(bad_request(&format!(
+                "kind must be one of {}",
+                DOCUMENT_KINDS.join(", ")
+            ))
+            .into());
+        }
+        let row = insert_document(
+            &t,
+            &audit.0,
+            &NewDocument {
+                id,
+                patient_id,
+                kind: &kind,
+                file_name,
+                content_type: &blob.content_type,
+                size_bytes: blob.size_bytes as i64,
+                sha256: &blob.sha256,
+                storage_key: &key,
+                uploaded_by: user.user_id,
+            },
+        )
+        .await
+        .map_err(HttpApiError::from)?;
+        Ok(HttpResponse::Created().json(row))
+    }
+    .await;
+
+    // Whatever failed after the bytes were stored, don't leave them orphaned.
+    if result.is_err()
+        && stored.is_some()
+        && let Err(e) = data.storage.delete(&key).await
+    {
+        tracing::warn!(error = %e, key, "could not delete blob of failed upload");
+    }
+    result
+}
+
+/// Reads the multipart fields, streaming the `file` part to `key`. `stored` is
+/// set as soon as the blob exists, so the caller can delete it on any later
+/// error. Returns the `kind` part.
+async fn read_parts(
+    data: &AppState,
+    key: &str,
+    payload: &mut Multipart,
+    stored: &mut Option<(String, StoredBlob)>,
+) -> actix_web::Result<String> {
+    let mut kind = String::from("other");
+    while let Some(field) = payload.next().await {
+        let mut field = field?;
+        match field.name() {
+            Some("kind") => {
+                let mut buf = Vec::new();
+                while let Some(chunk) = field.next().await {
+                    buf.extend_from_slice(&chunk?);
+                    if buf.len() > 32 {
+                        return Err(bad_request("invalid kind").into());
+                    }
+                }
+                kind = String::from_utf8(buf).map_err(|_| bad_request("invalid kind"))?;
+            }
+            Some("file") => {
+                if stored.is_some() {
+                    return Err(bad_request("one file per upload").into());
+                }
+                let file_name =
+                    clean_file_name(field.content_disposition().and_then(|cd| cd.get_filename()));
+                let blob = data
+                    .storage
+                    .put_stream(key, &mut field, data.max_upload_bytes, &ALLOWED_TYPES)
+                    .await
+                    .map_err(HttpApiError::from)?;
+                *stored = Some((file_name, blob));
+            }
+            _ => {
+                // Drain unknown parts so the stream can advance.
+                while let Some(chunk) = field.next().await {
+                    chunk?;
+                }
+            }
+        }
+    }
+    Ok(kind)
+}
+
+#[get("/patients/{id}/documents")]
+pub async fn list(
+    data: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    user: AuthUser,
+) -> actix_web::Result<HttpResponse> {
+    let patient_id = path.into_inner();
+    let t = authorize_read(&data.db, &user, patient_id).await?;
+    let rows = list_documents(&t, patient_id)
+        .await
+        .map_err(HttpApiError::from)?;
+    Ok(HttpResponse::Ok().json(rows))
+}
+
+/// Streams the stored bytes back with the sniffed content type.
+#[get("/patients/{id}/documents/{document_id}")]
+pub async fn download(
+    data: web::Data<AppState>,
+    path: web::Path<(Uuid, Uuid)>,
+    user: AuthUser,
+    audit: Audit,
+) -> actix_web::Result<HttpResponse> {
+    let (patient_id, document_id) = path.into_inner();
+    let t = authorize_read(&data.db, &user, patient_id).await?;
+    let doc = get_document(&t, &audit.0, patient_id, document_id)
+        .await
+        .map_err(HttpApiError::from)?
+        .ok_or(HttpApiError::App(AppError::NotFound))?;
+    let body = data
+        .storage
+        .get_stream(&doc.storage_key)
+        .await
+        .map_err(HttpApiError::from)?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(doc.content_type.as_str())
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(doc.file_name.clone())],
+        })
+        .insert_header((header::ETAG, format!("\"{}\"", doc.sha256)))
+        .insert_header((header::X_CONTENT_TYPE_OPTIONS, "nosniff"))
+        .no_chunking(doc.size_bytes as u64)
+        .streaming(body))
+}
+
+/// Soft delete: the document disappears from listings, the blob stays in storage.
+#[delete("/patients/{id}/documents/{document_id}")]
+pub async fn remove(
+    data: web::Data<AppState>,
+    path: web::Path<(Uuid, Uuid)>,
+    user: AuthUser,
+    audit: Audit,
+) -> actix_web::Result<HttpResponse> {
+    let (patient_id, document_id) = path.into_inner();
+    let t = authorize_owner(&data.db, &user, patient_id).await?;
+    delete_document(&t, &audit.0, patient_id, document_id, user.user_id)
+        .await
+        .map_err(HttpApiError::from)?
+        .ok_or(HttpApiError::App(AppError::NotFound))?;
+    Ok(HttpResponse::Ok().json(serde_json::json!({"deleted": 1})))
+}
+
+/// Admin only; the blob was kept on delete, so the document is whole again.
+#[post("/patients/{id}/documents/{document_id}/restore")]
+pub async fn restore(
+    data: web::Data<AppState>,
+    path: web::Path<(Uuid, Uuid)>,
+    user: AuthUser,
+    audit: Audit,
+) -> actix_web::Result<HttpResponse> {
+    if user.role != "Admin" {
+        return Err(HttpApiError::App(AppError::Forbidden).into());
+    }
+    let (patient_id, document_id) = path.into_inner();
+    let t = authorize_owner(&data.db, &user, patient_id).await?;
+    let doc = restore_document(&t, &audit.0, patient_id, document_id)
+        .await
+        .map_err(HttpApiError::from)?
+        .ok_or(HttpApiError::App(AppError::NotFound))?;
+    Ok(HttpResponse::Ok().json(doc))
+}
@@ -0,0 +1,98 @@
+use crate::error::HttpApiError;
+use crate::extractors::{Audit, AuthUser, ValidatedJson};
+use crate::schemas::{EmergencyAccessIn, EmergencyAccessQuery};
+use actix_web::{HttpResponse, delete, get, post, web};
+use chrono::{Duration, Utc};
+use common::AppError;
+use db::{
+    Db, NewEmergencyAccess, grant_emergency_access, list_emergency_access, revoke_emergency_access,
+};
+use uuid::Uuid;
+
+const DEFAULT_DURATION_MINUTES: i64 = 60;
+const MAX_DURATION_MINUTES: i64 = 240;
+const MIN_JUSTIFICATION_LEN: usize = 20;
+
+fn is_reviewer(user: &AuthUser) -> bool {
+    user.role == "Admin" || user.role == "PrivacyOfficer"
+}
+
+/// Break the glass: opens records outside the caller's scope for a limited time.
+/// The grant is flagged in the audit trail and the target org's privacy officers
+/// are notified.
+#[post("/emergency-access")]
+pub async fn grant(
+    data: web::Data<Db>,
+    body: ValidatedJson<EmergencyAccessIn>,
+    user: AuthUser,
+    audit: Audit,
+) -> actix_web::Result<HttpResponse> {
+    let justification = body.justification.trim();
+    if justification.chars().count() < MIN_JUSTIFICATION_LEN {
+        return Err(HttpApiError::App(AppError::BadRequest(format!(
+            "justification must be at least {MIN_JUSTIFICATION_LEN} characters"
+        )))
+        .into());
+    }
+    if body.resource_type.is_some() != body.resource_id.is_some() {
+        return Err(HttpApiError::App(AppError::BadRequest(
+            "resource_type and resource_id go together".into(),
+        ))
+        .into());
+    }
+    let minutes = body
+        .duration_minutes
+        .unwrap_or(DEFAULT_DURATION_MINUTES)
+        .clamp(1, MAX_DURATION_MINUTES);
+
+    let row = grant_emergency_access(
+        &data,
+        &audit.0,
+        &NewEmergencyAccess {
+            doctor_id: user.user_id,
+            org_id: body.org_id,
+            resource_type: body.resource_type.as_deref(),
+            resource_id: body.resource_id.as_deref(),
+            justification,
+            expires_at: Utc::now() + Duration::minutes(minutes),
+        },
+    )
+    .await
+    .map_err(HttpApiError::from)?;
+    Ok(HttpResponse::Created().json(row))
+}
+
+/// Privacy officers see their org's grants, admins any, doctors only their own.
+#[get("/emergency-access")]
+pub async fn list(
+    data: web::Data<Db>,
+    query: web::Query<EmergencyAccessQuery>,
+    user: AuthUser,
+) -> actix_web::Result<HttpResponse> {
+    let (org_id, doctor_id) = match user.role.as_str() {
+        "Admin" => (query.org_id, query.doctor_id),
+        "PrivacyOfficer" => (Some(user.org_id), query.doctor_id),
+        _ => (query.org_id, Some(user.user_id)),
+    };
+    let rows = list_emergency_access(&data, org_id, doctor_id)
+        .await
+        .map_err(HttpApiError::from)?;
+    Ok(HttpResponse::Ok().json(rows))
+}
+
+/// Ends a grant early; allowed for the grantee and for reviewers of the
+/// target org.
+#[delete("/emergency-access/{id}")]
+pub async fn revoke(
+    data: web::Data<Db>,
+    path: web::Path<Uuid>,
+    user: AuthUser,
+    audit: Audit,
+) -> actix_web::Result<HttpResponse> {
+    let org_id = is_reviewer(&user).then_some(user.org_id);
+    let row = revoke_emergency_access(&data, &audit.0, path.into_inner(), user.user_id, org_id)
+        .await
+        .map_err(HttpApiError::from)?
+        .ok_or(HttpApiError::App(AppError::NotFound))?;
+    Ok(HttpResponse::Ok().json(row))
+}
@@ -0,0 +1,84 @@
+use crate::state::AppState;
+use actix_web::rt::time::timeout;
+use actix_web::{HttpResponse, get, web};
+use serde_json::{Value, json};
+use std::time::{Duration, Instant};
+
+/// Liveness: the process is up and serving. Never touches dependencies, so a
+/// database outage does not get the pod restarted.
+#[get("/healthz")]
+pub async fn healthz() -> HttpResponse {
+    HttpResponse::Ok().json(json!({ "status": "ok" }))
+}
+
+/// Readiness: database reachable, every embedded migration applied and the
+/// signing and encryption keys usable. 503 with the failing check otherwise.
+#[get("/readyz")]
+pub async fn readyz(data: web::Data<AppState>) -> HttpResponse {
+    let database = database(&data).await;
+    let migrations = if database["status"] == "ok" {
+        migrations(&data).await
+    } else {
+        json!({ "status": "unknown" })
+    };
+    let keys = keys(&data);
+
+    let ready = [&database, &migrations, &keys]
+        .iter()
+        .all(|c| c["status"] == "ok");
+    let body = json!({
+        "status": if ready { "ready" } else { "unavailable" },
+        "checks": { "database": database, "migrations": migrations, "keys": keys }
+    });
+    if ready {
+        HttpResponse::Ok().json(body)
+    } else {
+        HttpResponse::ServiceUnavailable().json(body)
+    }
+}
+
+async fn database(data: &AppState) -> Value {
+    let started = Instant::now();
+    match timeout(Duration::from_secs(2), db::ping(&data.db)).await {
+        Ok(Ok(())) => json!({
+            "status": "ok",
+            "latency_ms": started.elapsed().as_millis() as u64
+        }),
+        Ok(Err(e)) => {
+            tracing::warn!(error = %e, "readiness: database unreachable");
+            json!({ "status": "error", "detail": "database unreachable" })
+        }
+        Err(_) => json!({ "status": "error", "detail": "database timed out" }),
+    }
+}
+
+async fn migrations(data: &AppState) -> Value {
+    match db::pending_migrations(&data.db).await {
+        Ok(pending) if pending.is_empty() => json!({ "status": "ok" }),
+        Ok(pending) => json!({ "status": "pending", "pending": pending }),
+        Err(e) => {
+            tracing::warn!(error = %e, "readiness: migration state unreadable");
+            json!({ "status": "error", "detail": "migration state unreadable" })
+        }
+    }
+}
+
+/// Signs and verifies a throwaway token, and wraps and unwraps a fresh data
+/// key under the current master key.
+fn keys(data: &AppState) -> Value {
+    let jwt = auth::sign_access(&data.jwt, uuid::Uuid::nil(), "probe", 0, 60)
+        .ok()
+        .and_then(|token| auth::verify(&data.jwt, &token).ok())
+        .is_some();
+    let version = data.keys.current_version();
+    let encryption = data
+        .keys
+        .new_data_key("readyz")
+        .and_then(|(_, wrapped)| data.keys.unwrap(version, &wrapped, "readyz"))
+        .is_ok();
+    if jwt && encryption {
+        json!({ "status": "ok", "key_version": version })
+    } else {
+        json!({ "status": "error", "jwt": jwt, "encryption": encryption })
+    }
+}
@@ -0,0 +1,87 @@
+use crate::error::HttpApiError;
+use crate::extractors::{AuthUser, ValidatedJson, require_role};
+use crate::schemas::{InvitationFilter, InvitationIn};
+use actix_web::{HttpRequest, HttpResponse, delete, get, post, web};
+use auth::{new_invitation_code, sha256_hex};
+use chrono::{Duration, Utc};
+use common::AppError;
+use db::{Db, NewInvitation, insert_invitation, list_invitations, revoke_invitation};
+use uuid::Uuid;
+
+const DEFAULT_TTL_HOURS: i64 = 72;
+
+/// Issues a single-use invitation bound to the admin's own org and a role.
+/// Naming any other org is refused. The plain code is only returned here; the
+/// DB keeps its hash.
+#[post("/invitations")]
+pub async fn create(
+    data: web::Data<Db>,
+    body: ValidatedJson<InvitationIn>,
+    user: AuthUser,
+    req: HttpRequest,
+) -> actix_web::Result<HttpResponse> {
+    require_role(&req, "Admin")?;
+    if body.org_id.is_some_and(|org_id| org_id != user.org_id) {
+        return Err(HttpApiError::App(AppError::Forbidden).into());
+    }
+    let ttl_hours = body.expires_in_hours.unwrap_or(DEFAULT_TTL_HOURS);
+    if ttl_hours <= 0 {
+        return Err(HttpApiError::App(AppError::BadRequest(
+            "expires_in_hours must be positive".into(),
+        ))
+        .into());
+    }
+
+    let code = new_invitation_code();
+    let code_hash = format!("sha256:{}", sha256_hex(&code));
+    let row = insert_invitation(
+        &data,
+        &NewInvitation {
+            code_hash: &code_hash,
+            org_id: user.org_id,
+            org_name: body.org_name.as_deref(),
+            doctor_roll: body.doctor_roll,
+            department: body.department.as_deref(),
+            created_by: user.user_id,
+            expires_at: Utc::now() + Duration::hours(ttl_hours),
+        },
+    )
+    .await
+    .map_err(HttpApiError::from)?;
+
+    Ok(HttpResponse::Created().json(serde_json::json!({
+        "invitation": row,
+        "code": code,
+    })))
+}
+
+#[get("/invitations")]
+pub async fn list(
+    data: web::Data<Db>,
+    filter: web::Query<InvitationFilter>,
+    user: AuthUser,
+    req: HttpRequest,
+) -> actix_web::Result<HttpResponse> {
+    require_role(&req, "Admin")?;
+    let rows = list_invitations(&data, user.org_id, filter.pending)
+        .await
+        .map_err(HttpApiError::from)?;
+    Ok(HttpResponse::Ok().json(rows))
+}
+
+#[delete("/invitations/{id}")]
+pub async fn revoke(
+    data: web::Data<Db>,
+    path: web::Path<Uuid>,
+    user: AuthUser,
+    req: HttpRequest,
+) -> actix_web::Result<HttpResponse> {
+    require_role(&req, "Admin")?;
+    let affected = revoke_invitation(&data, path.into_inner(), user.org_id)
+        .await
+        .map_err(HttpApiError::from)?;
+    if affected == 0 {
+        return Err(HttpApiError::App(AppError::NotFound).into());
+    }
+    Ok(HttpResponse::Ok().json(serde_json::json!({"revoked": affected})))
+}
@@ -1,84 +1,359 @@
-use crate::{extractors::require_role, schemas::ItemIn};
-use actix_web::{HttpRequest, HttpResponse, delete, get, post, put, web};
-use db::{Db, delete_item, get_item, insert_item, list_items, update_item};
+use crate::{
+    bulk::{self, Format, ImportMode, ImportReport, RowParser},
+    concurrency::{IfMatch, precondition_failed, versioned},
+    error::{HttpApiError, Problem},
+    extractors::{Audit, AuthUser, Tenant, ValidatedJson},
+    schemas::{ItemIn, ItemPatch},
+    state::AppState,
+};
+use actix_web::http::StatusCode;
+use actix_web::http::header::{self, ContentDisposition, DispositionParam, DispositionType};
+use actix_web::web::Bytes;
+use actix_web::{HttpRequest, HttpResponse, delete, get, patch, post, put, web};
+use common::{AppError, ListParams};
+use db::{
+    ITEM_SORTS, ItemChanges, ItemEditor, ItemImport, ItemWrite, delete_item, export_items,
+    get_item, insert_item, list_items, patch_item, restore_item, update_item,
+};
+use futures_util::StreamExt;
 use uuid::Uuid;
 
+/// Items fetched per export page.
+const EXPORT_PAGE: i64 = 500;
+
+/// Paginated: `?limit=&cursor=&sort=&q=&created_from=&created_to=&owner_id=`.
+/// `deleted=true` lists the trash instead (admin only).
 #[get("/items")]
 pub async fn list(
-    data: web::Data<Db>,
-    who: Option<web::Query<OwnerFilter>>,
+    tenant: Tenant,
+    params: web::Query<ListParams>,
+    filter: web::Query<ItemFilter>,
+    user: AuthUser,
+    audit: Audit,
 ) -> actix_web::Result<HttpResponse> {
-    let owner = who.and_then(|q| q.owner_id);
-    let rows = list_items(&data, owner)
+    let deleted = filter.deleted.unwrap_or(false);
+    if deleted && user.role != "Admin" {
+        return Err(HttpApiError::App(AppError::Forbidden).into());
+    }
+    let page = params
+        .validate(&ITEM_SORTS, "-created_at")
+        .map_err(HttpApiError::from)?;
+    let rows = list_items(&tenant.0, &audit.0, filter.owner_id, deleted, &page)
         .await
-        .map_err(crate::error::HttpApiError::from)?;
+        .map_err(HttpApiError::from)?;
     Ok(HttpResponse::Ok().json(rows))
 }
 
 #[derive(serde::Deserialize)]
-pub struct OwnerFilter {
+pub struct ItemFilter {
     pub owner_id: Option<Uuid>,
+    pub deleted: Option<bool>,
 }
 
 #[get("/items/{id}")]
-pub async fn get(data: web::Data<Db>, path: web::Path<Uuid>) -> actix_web::Result<HttpResponse> {
+pub async fn get(
+    tenant: Tenant,
+    path: web::Path<Uuid>,
+    audit: Audit,
+) -> actix_web::Result<HttpResponse> {
     let id = path.into_inner();
-    if let Some(row) = get_item(&data, id)
+    if let Some(row) = get_item(&tenant.0, &audit.0, id)
         .await
-        .map_err(crate::error::HttpApiError::from)?
+        .map_err(HttpApiError::from)?
     {
-        Ok(HttpResponse::Ok().json(row))
+        Ok(versioned(HttpResponse::Ok(), &row))
     } else {
-        Err(actix_web::error::ErrorNotFound("not found"))
+        Err(HttpApiError::App(AppError::NotFound).into())
     }
 }
 
 #[post("/items")]
 pub async fn create(
-    data: web::Data<Db>,
-    body: web::Json<ItemIn>,
+    tenant: Tenant,
+    body: ValidatedJson<ItemIn>,
     user: crate::extractors::AuthUser,
+    audit: Audit,
 ) -> actix_web::Result<HttpResponse> {
     let row = insert_item(
-        &data,
+        &tenant.0,
+        &audit.0,
         user.user_id,
         &body.title,
         body.description.as_deref(),
     )
     .await
     .map_err(crate::error::HttpApiError::from)?;
-    Ok(HttpResponse::Created().json(row))
+    Ok(versioned(HttpResponse::Created(), &row))
 }
 
+fn editor(user: &AuthUser) -> ItemEditor {
+    ItemEditor {
+        user_id: user.user_id,
+        is_admin: user.role == "Admin",
+        is_department_head: user.role == "DepartmentHead",
+    }
+}
+
+/// Owner, head of the owner's department, or admin. Items of other orgs are
+/// invisible through the tenant handle, so they come back as 404. Requires
+/// `If-Match` with the item's current `ETag`.
 #[put("/items/{id}")]
 pub async fn update(
-    data: web::Data<Db>,
+    tenant: Tenant,
     path: web::Path<Uuid>,
-    body: web::Json<ItemIn>,
-    req: actix_web::HttpRequest,
+    body: ValidatedJson<ItemIn>,
+    if_match: IfMatch,
+    user: AuthUser,
+    audit: Audit,
 ) -> actix_web::Result<HttpResponse> {
-    crate::extractors::require_role(&req, "User")?;
     let id = path.into_inner();
-    if let Some(row) = update_item(&data, id, &body.title, body.description.as_deref())
-        .await
-        .map_err(crate::error::HttpApiError::from)?
+    match update_item(
+        &tenant.0,
+        &audit.0,
+        &editor(&user),
+        id,
+        &if_match.0,
+        &body.title,
+        body.description.as_deref(),
+    )
+    .await
+    .map_err(HttpApiError::from)?
     {
-        Ok(HttpResponse::Ok().json(row))
-    } else {
-        Err(actix_web::error::ErrorNotFound("not found"))
+        ItemWrite::Done(row) => Ok(versioned(HttpResponse::Ok(), &row)),
+        ItemWrite::Stale(current) => Ok(precondition_failed(&current)),
+        ItemWrite::NotFound => Err(HttpApiError::App(AppError::NotFound).into()),
+        ItemWrite::Forbidden => Err(HttpApiError::App(AppError::Forbidden).into()),
     }
 }
 
+/// JSON merge patch (RFC 7396), e.g. `{"description": null}` clears the
+/// description and leaves the title. Same access and `If-Match` rules as PUT.
+#[patch("/items/{id}")]
+pub async fn patch(
+    tenant: Tenant,
+    path: web::Path<Uuid>,
+    body: ValidatedJson<ItemPatch>,
+    if_match: IfMatch,
+    user: AuthUser,
+    audit: Audit,
+) -> actix_web::Result<HttpResponse> {
+    let id = path.into_inner();
+    let changes = ItemChanges {
+        title: body
+            .title
+            .as_deref()
+            .required("title")
+            .map_err(HttpApiError::from)?,
+        description: body.description.as_deref().into_update(),
+    };
+    match patch_item(
+        &tenant.0,
+        &audit.0,
+        &editor(&user),
+        id,
+        &if_match.0,
+        &changes,
+    )
+    .await
+    .map_err(HttpApiError::from)?
+    {
+        ItemWrite::Done(row) => Ok(versioned(HttpResponse::Ok(), &row)),
+        ItemWrite::Stale(current) => Ok(precondition_failed(&current)),
+        ItemWrite::NotFound => Err(HttpApiError::App(AppError::NotFound).into()),
+        ItemWrite::Forbidden => Err(HttpApiError::App(AppError::Forbidden).into()),
+    }
+}
+
+/// Soft delete; an admin can restore the item.
 #[delete("/items/{id}")]
 pub async fn remove(
-    data: web::Data<Db>,
+    tenant: Tenant,
     path: web::Path<Uuid>,
-    req: HttpRequest,
+    user: AuthUser,
+    audit: Audit,
 ) -> actix_web::Result<HttpResponse> {
-    require_role(&req, "Admin")?; // only admin can delete
     let id = path.into_inner();
-    let affected = delete_item(&data, id)
+    match delete_item(&tenant.0, &audit.0, &editor(&user), id)
+        .await
+        .map_err(HttpApiError::from)?
+    {
+        ItemWrite::Done(_) => Ok(HttpResponse::Ok().json(serde_json::json!({"deleted": 1}))),
+        ItemWrite::Stale(current) => Ok(precondition_failed(&current)),
+        ItemWrite::NotFound => Err(HttpApiError::App(AppError::NotFound).into()),
+        ItemWrite::Forbidden => Err(HttpApiError::App(AppError::Forbidden).into()),
+    }
+}
+
+#[post("/items/{id}/restore")]
+pub async fn restore(
+    tenant: Tenant,
+    path: web::Path<Uuid>,
+    user: AuthUser,
+    audit: Audit,
+) -> actix_web::Result<HttpResponse> {
+    if user.role != "Admin" {
+        return Err(HttpApiError::App(AppError::Forbidden).into());
+    }
+    match restore_item(&tenant.0, &audit.0, path.into_inner())
         .await
-        .map_err(crate::error::HttpApiError::from)?;
-    Ok(HttpResponse::Ok().json(serde_json::json!({"deleted": affected})))
+        .map_err(HttpApiError::from)?
+    {
+        Some(row) => Ok(versioned(HttpResponse::Ok(), &row)),
+        None => Err(HttpApiError::App(AppError::NotFound).into()),
+    }
+}
+
+#[derive(serde::Deserialize)]
+pub struct ImportQuery {
+    /// Defaults to the request's `Content-Type`.
+    pub format: Option<Format>,
+    #[serde(default)]
+    pub mode: ImportMode,
+}
+
+/// Streams a CSV (with a `title` and optional `description` column) or NDJSON
+/// upload into the caller's items. In `atomic` mode (the default) nothing is
+/// imported if any row is invalid and the report comes back as 422;
+/// `best_effort` skips invalid rows. The report lists rejected rows either way.
+/// Uploads are capped at `max_upload_bytes` and parsed in full before the
+/// import transaction opens.
+#[post("/items/import")]
+pub async fn import(
+    data: web::Data<AppState>,
+    tenant: Tenant,
+    req: HttpRequest,
+    query: web::Query<ImportQuery>,
+    mut payload: web::Payload,
+    user: AuthUser,
+    audit: Audit,
+) -> actix_web::Result<HttpResponse> {
+    let format = query
+        .format
+        .or_else(|| {
+            req.headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(Format::from_content_type)
+        })
+        .ok_or_else(|| {
+            HttpApiError::App(AppError::BadRequest("format must be csv or ndjson".into()))
+        })?;
+    let bad_upload = |e: bulk::BulkError| HttpApiError::App(AppError::BadRequest(e.to_string()));
+
+    let mut parser = RowParser::new(format);
+    let mut report = ImportReport::new(query.mode);
+    let mut accepted = Vec::new();
+    let mut received = 0u64;
+    let mut rows = Vec::new();
+    loop {
+        let done = match payload.next().await {
+            Some(chunk) => {
+                let chunk = chunk?;
+                received += chunk.len() as u64;
+                if received > data.max_upload_bytes {
+                    return Err(HttpApiError::rejected(
+                        StatusCode::PAYLOAD_TOO_LARGE,
+                        format!("upload exceeds {} bytes", data.max_upload_bytes),
+                    )
+                    .into());
+                }
+                parser.feed(&chunk, &mut rows).map_err(bad_upload)?;
+                false
+            }
+            None => {
+                parser.finish(&mut rows).map_err(bad_upload)?;
+                true
+            }
+        };
+        for (n, row) in rows.drain(..) {
+            report.rows += 1;
+            match row {
+                Ok(row) if report.accepting() => accepted.push(row),
+                Ok(_) => {}
+                Err(e) => report.reject(n, e),
+            }
+        }
+        if done {
+            break;
+        }
+    }
+
+    if !report.accepting() {
+        let problem = Problem::new(StatusCode::UNPROCESSABLE_ENTITY)
+            .typed("/problems/import-rejected", "Import rejected")
+            .detail(format!("{} invalid rows, nothing imported", report.failed))
+            .extend(&report);
+        return Err(HttpApiError::from(problem).into());
+    }
+    let mut staged = ItemImport::begin(&tenant.0, user.user_id)
+        .await
+        .map_err(HttpApiError::from)?;
+    for row in &accepted {
+        staged
+            .push(&row.title, row.description.as_deref())
+            .await
+            .map_err(HttpApiError::from)?;
+    }
+    let ids = staged.commit(&audit.0).await.map_err(HttpApiError::from)?;
+    report.imported = ids.len();
+    Ok(HttpResponse::Ok().json(report))
+}
+
+/// Key of the page after `rows`, unless `rows` was the last one.
+fn next_page(rows: &[db::ItemRow]) -> Option<(chrono::DateTime<chrono::Utc>, Uuid)> {
+    if (rows.len() as i64) < EXPORT_PAGE {
+        return None;
+    }
+    rows.last().map(|r| (r.created_at, r.id))
+}
+
+#[derive(serde::Deserialize)]
+pub struct ExportQuery {
+    pub format: Option<Format>,
+}
+
+/// The caller's live items, streamed page by page (CSV by default).
+#[get("/items/export")]
+pub async fn export(
+    tenant: Tenant,
+    query: web::Query<ExportQuery>,
+    user: AuthUser,
+    audit: Audit,
+) -> actix_web::Result<HttpResponse> {
+    let format = query.format.unwrap_or(Format::Csv);
+    let owner = user.user_id;
+    let (tenant, ctx) = (tenant.0, audit.0);
+    // the first page is fetched up front so that errors still get a status code
+    let rows = export_items(&tenant, &ctx, owner, None, EXPORT_PAGE)
+        .await
+        .map_err(HttpApiError::from)?;
+    let first = bulk::encode(format, &rows, true).map_err(HttpApiError::Internal)?;
+    let after = next_page(&rows);
+
+    let rest = futures_util::stream::try_unfold(after, move |after| {
+        let (tenant, ctx) = (tenant.clone(), ctx.clone());
+        async move {
+            let Some(after) = after else {
+                return Ok(None);
+            };
+            let rows = export_items(&tenant, &ctx, owner, Some(after), EXPORT_PAGE)
+                .await
+                .map_err(HttpApiError::from)?;
+            let chunk = bulk::encode(format, &rows, false).map_err(HttpApiError::Internal)?;
+            Ok::<_, actix_web::Error>(Some((Bytes::from(chunk), next_page(&rows))))
+        }
+    });
+    let body = futures_util::stream::once(async move { Ok(Bytes::from(first)) }).chain(rest);
+
+    let extension = match format {
+        Format::Csv => "csv",
+        Format::Ndjson => "ndjson",
+    };
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(format!("items.{extension}"))],
+        })
+        .streaming(body))
 }
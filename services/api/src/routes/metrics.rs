@@ -0,0 +1,30 @@
+use crate::error::HttpApiError;
+use crate::metrics::metrics;
+use crate::state::AppState;
+use actix_web::{HttpRequest, HttpResponse, get, web};
+use auth::sha256_hex;
+use common::AppError;
+
+/// Prometheus scrape endpoint, behind its own bearer token (`METRICS_TOKEN`)
+/// rather than a user login; 404 when no token is configured.
+#[get("/metrics")]
+pub async fn scrape(
+    data: web::Data<AppState>,
+    req: HttpRequest,
+) -> actix_web::Result<HttpResponse> {
+    let Some(expected) = &data.metrics_token else {
+        return Err(HttpApiError::App(AppError::NotFound).into());
+    };
+    let given = req
+        .headers()
+        .get("Authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+    // digests, so the comparison time says nothing about the token
+    if given.map(sha256_hex) != Some(sha256_hex(expected.expose())) {
+        return Err(HttpApiError::Auth.into());
+    }
+    Ok(HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4; charset=utf-8")
+        .body(metrics().render(&data.db)))
+}
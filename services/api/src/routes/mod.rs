@@ -1,2 +1,75 @@
+pub mod audit;
 pub mod auth;
+pub mod credentials;
+pub mod doctors;
+pub mod documents;
+pub mod emergency;
+pub mod health;
+pub mod invitations;
 pub mod items;
+pub mod metrics;
+pub mod notifications;
+pub mod patients;
+pub mod roster;
+pub mod search;
+pub mod settings;
+
+use actix_web::web;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(auth::register)
+        .service(auth::login)
+        .service(auth::refresh)
+        .service(auth::logout)
+        .service(health::healthz)
+        .service(health::readyz)
+        .service(metrics::scrape)
+        .service(settings::show)
+        .service(audit::list)
+        .service(audit::verify)
+        .service(doctors::me)
+        .service(doctors::update_me)
+        .service(doctors::list)
+        .service(doctors::update)
+        .service(doctors::deactivate)
+        .service(credentials::expiring)
+        .service(credentials::list)
+        .service(credentials::create)
+        .service(credentials::remove)
+        .service(emergency::grant)
+        .service(emergency::list)
+        .service(emergency::revoke)
+        .service(notifications::list)
+        .service(notifications::mark_read)
+        .service(patients::create)
+        .service(patients::list)
+        .service(patients::get)
+        .service(patients::consents)
+        .service(patients::record_consent)
+        .service(patients::withdraw)
+        .service(documents::upload)
+        .service(documents::list)
+        .service(documents::download)
+        .service(documents::remove)
+        .service(documents::restore)
+        .service(invitations::create)
+        .service(invitations::list)
+        .service(invitations::revoke)
+        .service(roster::create_definition)
+        .service(roster::list_definitions)
+        .service(roster::assign)
+        .service(roster::roster)
+        .service(roster::remove)
+        .service(roster::on_call)
+        .service(search::search)
+        .service(items::list)
+        // before `items::get`, which would take `export` for an id
+        .service(items::export)
+        .service(items::import)
+        .service(items::get)
+        .service(items::create)
+        .service(items::update)
+        .service(items::patch)
+        .service(items::remove)
+        .service(items::restore);
+}
@@ -0,0 +1,33 @@
+use crate::error::HttpApiError;
+use crate::extractors::AuthUser;
+use crate::schemas::NotificationQuery;
+use actix_web::{HttpResponse, get, post, web};
+use common::AppError;
+use db::{Db, list_notifications, mark_notification_read};
+
+#[get("/notifications")]
+pub async fn list(
+    data: web::Data<Db>,
+    query: web::Query<NotificationQuery>,
+    user: AuthUser,
+) -> actix_web::Result<HttpResponse> {
+    let rows = list_notifications(&data, user.user_id, query.unread)
+        .await
+        .map_err(HttpApiError::from)?;
+    Ok(HttpResponse::Ok().json(rows))
+}
+
+#[post("/notifications/{id}/read")]
+pub async fn mark_read(
+    data: web::Data<Db>,
+    path: web::Path<i64>,
+    user: AuthUser,
+) -> actix_web::Result<HttpResponse> {
+    let affected = mark_notification_read(&data, user.user_id, path.into_inner())
+        .await
+        .map_err(HttpApiError::from)?;
+    if affected == 0 {
+        return Err(HttpApiError::App(AppError::NotFound).into());
+    }
+    Ok(HttpResponse::Ok().json(serde_json::json!({"read": affected})))
+}
@@ -0,0 +1,207 @@
+use crate::error::HttpApiError;
+use crate::extractors::{Audit, AuthUser, Tenant, ValidatedJson};
+use crate::schemas::{ConsentIn, ConsentWithdrawIn, PatientIn, PatientQuery};
+use crate::state::AppState;
+use actix_web::{HttpResponse, get, post, web};
+use common::{AppError, ListParams};
+use db::{
+    Db, NewConsent, NewPatient, PATIENT_SORTS, TenantDb, active_emergency_grant, get_patient,
+    has_active_consent, insert_consent, insert_patient, list_consents, list_patients, patient_org,
+    withdraw_consent,
+};
+use uuid::Uuid;
+
+const CONSENT_KINDS: [&str; 3] = ["treatment", "data_sharing", "research"];
+
+/// Own organization, or across `org_id` boundaries only with an active
+/// data-sharing consent covering the caller's org (or a break-the-glass grant).
+/// Returns a handle scoped to the patient's organization for the read.
+pub(crate) async fn authorize_read(
+    db: &Db,
+    user: &AuthUser,
+    patient_id: Uuid,
+) -> Result<TenantDb, HttpApiError> {
+    let owner = patient_org(db, patient_id)
+        .await?
+        .ok_or(HttpApiError::App(AppError::NotFound))?;
+    let t = db.tenant(owner);
+    if user.org_id == owner
+        || has_active_consent(&t, patient_id, "data_sharing", Some(user.org_id)).await?
+    {
+        return Ok(t);
+    }
+    let grant =
+        active_emergency_grant(db, user.user_id, owner, "patient", &patient_id.to_string()).await?;
+    if grant.is_some() {
+        return Ok(t);
+    }
+    Err(HttpApiError::App(AppError::Forbidden))
+}
+
+/// Consents and documents are managed by the patient's own organization only,
+/// whatever the caller's role. Returns a handle scoped to that organization.
+pub(crate) async fn authorize_owner(
+    db: &Db,
+    user: &AuthUser,
+    patient_id: Uuid,
+) -> Result<TenantDb, HttpApiError> {
+    let owner = patient_org(db, patient_id)
+        .await?
+        .ok_or(HttpApiError::App(AppError::NotFound))?;
+    if user.org_id != owner {
+        return Err(HttpApiError::App(AppError::Forbidden));
+    }
+    Ok(db.tenant(owner))
+}
+
+#[post("/patients")]
+pub async fn create(
+    data: web::Data<AppState>,
+    body: ValidatedJson<PatientIn>,
+    user: AuthUser,
+    tenant: Tenant,
+    audit: Audit,
+) -> actix_web::Result<HttpResponse> {
+    let row = insert_patient(
+        &tenant.0,
+        &data.keys,
+        &audit.0,
+        &NewPatient {
+            first_name: &body.first_name,
+            last_name: &body.last_name,
+            national_id: body.national_id.as_deref(),
+            birth_date: body.birth_date,
+            gender: body.gender.as_deref(),
+            phone: body.phone.as_deref(),
+            diagnoses: body.diagnoses.as_deref(),
+            notes: body.notes.as_deref(),
+            created_by: user.user_id,
+        },
+    )
+    .await
+    .map_err(HttpApiError::from)?;
+    Ok(HttpResponse::Created().json(row))
+}
+
+/// Patients of the caller's organization, paginated like `/items`, optionally
+/// by exact national ID.
+#[get("/patients")]
+pub async fn list(
+    data: web::Data<AppState>,
+    params: web::Query<ListParams>,
+    query: web::Query<PatientQuery>,
+    tenant: Tenant,
+    audit: Audit,
+) -> actix_web::Result<HttpResponse> {
+    let page = params
+        .validate(&PATIENT_SORTS, "last_name")
+        .map_err(HttpApiError::from)?;
+    let rows = list_patients(
+        &tenant.0,
+        &data.keys,
+        &audit.0,
+        query.national_id.as_deref(),
+        &page,
+    )
+    .await
+    .map_err(HttpApiError::from)?;
+    Ok(HttpResponse::Ok().json(rows))
+}
+
+#[get("/patients/{id}")]
+pub async fn get(
+    data: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    user: AuthUser,
+    audit: Audit,
+) -> actix_web::Result<HttpResponse> {
+    let id = path.into_inner();
+    let t = authorize_read(&data.db, &user, id).await?;
+    match get_patient(&t, &data.keys, &audit.0, id)
+        .await
+        .map_err(HttpApiError::from)?
+    {
+        Some(row) => Ok(HttpResponse::Ok().json(row)),
+        None => Err(HttpApiError::App(AppError::NotFound).into()),
+    }
+}
+
+#[get("/patients/{id}/consents")]
+pub async fn consents(
+    data: web::Data<Db>,
+    path: web::Path<Uuid>,
+    user: AuthUser,
+) -> actix_web::Result<HttpResponse> {
+    let id = path.into_inner();
+    let t = authorize_read(&data, &user, id).await?;
+    let rows = list_consents(&t, id).await.map_err(HttpApiError::from)?;
+    Ok(HttpResponse::Ok().json(rows))
+}
+
+#[post("/patients/{id}/consents")]
+pub async fn record_consent(
+    data: web::Data<Db>,
+    path: web::Path<Uuid>,
+    body: ValidatedJson<ConsentIn>,
+    user: AuthUser,
+    audit: Audit,
+) -> actix_web::Result<HttpResponse> {
+    let id = path.into_inner();
+    if !CONSENT_KINDS.contains(&body.kind.as_str()) {
+        return Err(HttpApiError::App(AppError::BadRequest(format!(
+            "kind must be one of {}",
+            CONSENT_KINDS.join(", ")
+        )))
+        .into());
+    }
+    if body.scope_org_id.is_some() && body.kind != "data_sharing" {
+        return Err(HttpApiError::App(AppError::BadRequest(
+            "scope_org_id only applies to data_sharing".into(),
+        ))
+        .into());
+    }
+    if body.signed_by.trim().is_empty() || body.signature.trim().is_empty() {
+        return Err(HttpApiError::App(AppError::BadRequest(
+            "signed_by and signature are required".into(),
+        ))
+        .into());
+    }
+    let t = authorize_owner(&data, &user, id).await?;
+
+    let row = insert_consent(
+        &t,
+        &audit.0,
+        &NewConsent {
+            patient_id: id,
+            kind: &body.kind,
+            scope_org_id: body.scope_org_id,
+            form_version: &body.form_version,
+            signed_by: body.signed_by.trim(),
+            signature: &body.signature,
+            expires_at: body.expires_at,
+            recorded_by: user.user_id,
+        },
+    )
+    .await
+    .map_err(HttpApiError::from)?;
+    Ok(HttpResponse::Created().json(row))
+}
+
+#[post("/patients/{id}/consents/{consent_id}/withdraw")]
+pub async fn withdraw(
+    data: web::Data<Db>,
+    path: web::Path<(Uuid, Uuid)>,
+    body: ValidatedJson<ConsentWithdrawIn>,
+    user: AuthUser,
+    audit: Audit,
+) -> actix_web::Result<HttpResponse> {
+    let (id, consent_id) = path.into_inner();
+    let t = authorize_owner(&data, &user, id).await?;
+    match withdraw_consent(&t, &audit.0, id, consent_id, body.reason.as_deref())
+        .await
+        .map_err(HttpApiError::from)?
+    {
+        Some(row) => Ok(HttpResponse::Ok().json(row)),
+        None => Err(HttpApiError::App(AppError::NotFound).into()),
+    }
+}
@@ -0,0 +1,199 @@
+use crate::error::{HttpApiError, Problem};
+use crate::extractors::{AuthUser, ValidatedJson, require_role};
+use crate::schemas::{OnCallQuery, RosterQuery, ShiftDefinitionIn, ShiftIn};
+use crate::state::AppState;
+use actix_web::http::StatusCode;
+use actix_web::{HttpRequest, HttpResponse, delete, get, post, web};
+use chrono::Utc;
+use common::AppError;
+use db::{
+    AssignShift, Db, NewShift, NewShiftDefinition, ShiftFilter, assign_shift, delete_shift,
+    get_doctor, insert_shift_definition, list_shift_definitions, list_shifts, on_call_at,
+    shift_definition_window,
+};
+use uuid::Uuid;
+
+fn bad_request(msg: &str) -> actix_web::Error {
+    HttpApiError::App(AppError::BadRequest(msg.into())).into()
+}
+
+/// Defines a shift template for the admin's own org.
+#[post("/shift-definitions")]
+pub async fn create_definition(
+    data: web::Data<Db>,
+    body: ValidatedJson<ShiftDefinitionIn>,
+    user: AuthUser,
+    req: HttpRequest,
+) -> actix_web::Result<HttpResponse> {
+    require_role(&req, "Admin")?;
+    if body.org_id.is_some_and(|org_id| org_id != user.org_id) {
+        return Err(HttpApiError::App(AppError::Forbidden).into());
+    }
+    if body.duration_minutes <= 0 || body.duration_minutes > 48 * 60 {
+        return Err(bad_request("duration_minutes must be between 1 and 2880"));
+    }
+    let row = insert_shift_definition(
+        &data,
+        &NewShiftDefinition {
+            org_id: user.org_id,
+            name: &body.name,
+            start_time: body.start_time,
+            duration_minutes: body.duration_minutes,
+            timezone: body.timezone.as_deref(),
+            on_call: body.on_call,
+        },
+    )
+    .await
+    .map_err(HttpApiError::from)?;
+    Ok(HttpResponse::Created().json(row))
+}
+
+#[get("/shift-definitions")]
+pub async fn list_definitions(
+    data: web::Data<Db>,
+    user: AuthUser,
+) -> actix_web::Result<HttpResponse> {
+    let rows = list_shift_definitions(&data, user.org_id)
+        .await
+        .map_err(HttpApiError::from)?;
+    Ok(HttpResponse::Ok().json(rows))
+}
+
+/// Assigns a doctor of the admin's own org to a shift. Responds 409 with the
+/// colliding shifts when the new one overlaps or violates the minimum rest
+/// period.
+#[post("/shifts")]
+pub async fn assign(
+    data: web::Data<AppState>,
+    body: ValidatedJson<ShiftIn>,
+    user: AuthUser,
+    req: HttpRequest,
+) -> actix_web::Result<HttpResponse> {
+    require_role(&req, "Admin")?;
+    let doctor = get_doctor(&data.db, body.doctor_id)
+        .await
+        .map_err(HttpApiError::from)?
+        .filter(|doctor| doctor.org_id == user.org_id)
+        .ok_or(HttpApiError::App(AppError::NotFound))?;
+
+    let (starts_at, ends_at, definition_on_call) = match (
+        body.shift_definition_id,
+        body.date,
+        body.starts_at,
+        body.ends_at,
+    ) {
+        (Some(def_id), Some(date), None, None) => {
+            let (def, starts_at, ends_at) = shift_definition_window(&data.db, def_id, date)
+                .await
+                .map_err(HttpApiError::from)?
+                .ok_or(HttpApiError::App(AppError::NotFound))?;
+            if def.org_id != doctor.org_id {
+                return Err(bad_request("shift definition belongs to another org"));
+            }
+            (starts_at, ends_at, def.on_call)
+        }
+        (None, None, Some(starts_at), Some(ends_at)) if ends_at > starts_at => {
+            (starts_at, ends_at, false)
+        }
+        (None, None, Some(_), Some(_)) => {
+            return Err(bad_request("ends_at must be after starts_at"));
+        }
+        _ => {
+            return Err(bad_request(
+                "provide either shift_definition_id and date, or starts_at and ends_at",
+            ));
+        }
+    };
+    let department = body
+        .department
+        .as_deref()
+        .or(doctor.department.as_deref())
+        .ok_or_else(|| bad_request("department is required"))?;
+
+    let outcome = assign_shift(
+        &data.db,
+        &NewShift {
+            doctor_id: doctor.id,
+            org_id: doctor.org_id,
+            department,
+            shift_definition_id: body.shift_definition_id,
+            starts_at,
+            ends_at,
+            on_call: body.on_call.unwrap_or(definition_on_call),
+            created_by: user.user_id,
+        },
+        data.min_rest_minutes,
+    )
+    .await
+    .map_err(HttpApiError::from)?;
+
+    match outcome {
+        AssignShift::Created(row) => Ok(HttpResponse::Created().json(row)),
+        AssignShift::Conflicts(conflicts) => Err(HttpApiError::from(
+            Problem::new(StatusCode::CONFLICT)
+                .typed("/problems/shift-conflict", "Shift conflict")
+                .detail("shift conflicts with existing assignments")
+                .with("min_rest_minutes", data.min_rest_minutes)
+                .with("conflicts", conflicts),
+        )
+        .into()),
+    }
+}
+
+/// The caller's own org roster; other orgs are reachable only via `/on-call`.
+#[get("/shifts")]
+pub async fn roster(
+    data: web::Data<Db>,
+    query: web::Query<RosterQuery>,
+    user: AuthUser,
+) -> actix_web::Result<HttpResponse> {
+    let rows = list_shifts(
+        &data,
+        &ShiftFilter {
+            org_id: user.org_id,
+            department: query.department.as_deref(),
+            doctor_id: query.doctor_id,
+            from: query.from,
+            to: query.to,
+        },
+    )
+    .await
+    .map_err(HttpApiError::from)?;
+    Ok(HttpResponse::Ok().json(rows))
+}
+
+#[delete("/shifts/{id}")]
+pub async fn remove(
+    data: web::Data<Db>,
+    path: web::Path<Uuid>,
+    user: AuthUser,
+    req: HttpRequest,
+) -> actix_web::Result<HttpResponse> {
+    require_role(&req, "Admin")?;
+    let affected = delete_shift(&data, path.into_inner(), user.org_id)
+        .await
+        .map_err(HttpApiError::from)?;
+    if affected == 0 {
+        return Err(HttpApiError::App(AppError::NotFound).into());
+    }
+    Ok(HttpResponse::Ok().json(serde_json::json!({"deleted": affected})))
+}
+
+/// "Who is on call now for cardiology at org X" — used by the ER.
+#[get("/on-call")]
+pub async fn on_call(
+    data: web::Data<Db>,
+    query: web::Query<OnCallQuery>,
+    _user: AuthUser,
+) -> actix_web::Result<HttpResponse> {
+    let at = query.at.unwrap_or_else(Utc::now);
+    let rows = on_call_at(&data, query.org_id, &query.department, at)
+        .await
+        .map_err(HttpApiError::from)?;
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "org_id": query.org_id,
+        "department": query.department,
+        "at": at,
+        "on_call": rows,
+    })))
+}
@@ -0,0 +1,26 @@
+use crate::error::HttpApiError;
+use crate::extractors::{Audit, Tenant};
+use actix_web::{HttpResponse, get, web};
+use common::{AppError, ListParams};
+use db::SEARCH_SORTS;
+
+/// Full-text search over the caller's organization, best matches first.
+/// Paginated like the list endpoints: `?q=&limit=&cursor=`.
+#[get("/search")]
+pub async fn search(
+    tenant: Tenant,
+    params: web::Query<ListParams>,
+    audit: Audit,
+) -> actix_web::Result<HttpResponse> {
+    let page = params
+        .validate(&SEARCH_SORTS, "-rank")
+        .map_err(HttpApiError::from)?;
+    let q = page
+        .q
+        .clone()
+        .ok_or_else(|| HttpApiError::App(AppError::BadRequest("q is required".into())))?;
+    let hits = db::search(&tenant.0, &audit.0, &q, &page)
+        .await
+        .map_err(HttpApiError::from)?;
+    Ok(HttpResponse::Ok().json(hits))
+}
@@ -0,0 +1,14 @@
+use crate::extractors::require_role;
+use crate::state::Settings;
+use actix_web::{HttpRequest, HttpResponse, get, web};
+
+/// Effective settings for admins. Secrets print as `[REDACTED]`; `null`
+/// means the built-in default is in use.
+#[get("/admin/config")]
+pub async fn show(
+    settings: web::Data<Settings>,
+    req: HttpRequest,
+) -> actix_web::Result<HttpResponse> {
+    require_role(&req, "Admin")?;
+    Ok(HttpResponse::Ok().json(settings.as_ref()))
+}
@@ -1,29 +1,317 @@
+use common::{Patch, Secret};
 use serde::Deserialize;
+use validator::{Validate, ValidationError};
 
-#[derive(Deserialize)]
+/// Registry numbers: 3–64 letters (any script), digits, `-`, `_` or `/`,
+/// starting with a letter or digit.
+fn reg_no(value: &str) -> Result<(), ValidationError> {
+    let len = value.chars().count();
+    let ok = (3..=64).contains(&len)
+        && value.chars().next().is_some_and(char::is_alphanumeric)
+        && value
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '/'));
+    if ok {
+        Ok(())
+    } else {
+        Err(ValidationError::new("reg_no").with_message("invalid registry number".into()))
+    }
+}
+
+fn not_blank(value: &str) -> Result<(), ValidationError> {
+    if value.trim().is_empty() {
+        Err(ValidationError::new("blank").with_message("must not be blank".into()))
+    } else {
+        Ok(())
+    }
+}
+
+/// Deactivation goes through `DELETE /doctors/{id}`, which also stamps who
+/// did it and revokes the doctor's sessions.
+fn reactivate_only(value: &bool) -> Result<(), ValidationError> {
+    if *value {
+        Ok(())
+    } else {
+        Err(ValidationError::new("reactivate_only")
+            .with_message("deactivate with DELETE /doctors/{id}".into()))
+    }
+}
+
+/// IANA zone names as understood by `chrono-tz`, e.g. `Asia/Ulaanbaatar`.
+fn iana_timezone(value: &str) -> Result<(), ValidationError> {
+    match value.parse::<chrono_tz::Tz>() {
+        Ok(_) => Ok(()),
+        Err(_) => {
+            Err(ValidationError::new("timezone").with_message("unknown IANA timezone".into()))
+        }
+    }
+}
+
+/// `null` is left to [`Patch::required`]; only a set value must not be blank.
+fn patch_not_blank(value: &Patch<String>) -> Result<(), ValidationError> {
+    match value {
+        Patch::Set(v) => not_blank(v),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
 pub struct RegisterInput {
+    #[validate(custom(function = "reg_no"))]
     pub reg_no: String,
+    #[validate(length(min = 1, max = 100), custom(function = "not_blank"))]
     pub first_name: String,
+    #[validate(length(min = 1, max = 100), custom(function = "not_blank"))]
     pub last_name: String,
+    #[validate(length(max = 100))]
     pub rank_name: Option<String>,
+    #[validate(length(max = 200))]
     pub org_name: Option<String>,
-    pub org_id: i32,
+    #[validate(length(max = 100))]
     pub position: Option<String>,
     pub birth_date: Option<chrono::NaiveDate>,
+    #[validate(length(max = 16))]
     pub gender: Option<String>,
-    pub doctor_roll: Option<i32>,
-    pub password: String,
+    #[validate(length(min = 8, max = 128))]
+    pub password: Secret,
+    /// Org and role come from the invitation, or from the server's open
+    /// registration setting; clients cannot choose them.
+    #[validate(length(max = 128))]
+    pub invitation_code: Option<Secret>,
 }
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct LoginInput {
+    #[validate(length(min = 1, max = 64))]
     pub reg_no: String,
-    pub password: String,
+    #[validate(length(min = 1, max = 128))]
+    pub password: Secret,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 #[serde(deny_unknown_fields)]
 pub struct ItemIn {
+    #[validate(length(max = 200), custom(function = "not_blank"))]
     pub title: String,
     #[serde(alias = "description")]
+    #[validate(length(max = 10_000))]
     pub description: Option<String>,
 }
+
+/// Merge patch for `PATCH /items/{id}`: absent fields stay, `null` clears.
+#[derive(Debug, Default, Deserialize, Validate)]
+#[serde(default, deny_unknown_fields)]
+pub struct ItemPatch {
+    #[validate(length(max = 200), custom(function = "patch_not_blank"))]
+    pub title: Patch<String>,
+    #[validate(length(max = 10_000))]
+    pub description: Patch<String>,
+}
+
+/// Merge patch of the caller's own profile; names cannot be cleared.
+#[derive(Debug, Default, Deserialize, Validate)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProfileUpdateInput {
+    #[validate(length(min = 1, max = 100))]
+    pub first_name: Patch<String>,
+    #[validate(length(min = 1, max = 100))]
+    pub last_name: Patch<String>,
+    #[validate(length(max = 100))]
+    pub rank_name: Patch<String>,
+    #[validate(length(max = 100))]
+    pub position: Patch<String>,
+    #[validate(length(max = 32))]
+    pub phone: Patch<String>,
+    #[validate(email, length(max = 254))]
+    pub email: Patch<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct DoctorAdminUpdateInput {
+    /// Only the admin's own org is accepted.
+    pub org_id: Option<i32>,
+    #[validate(length(max = 200))]
+    pub org_name: Option<String>,
+    #[validate(length(max = 100))]
+    pub department: Option<String>,
+    pub doctor_roll: Option<i32>,
+    #[validate(custom(function = "reactivate_only"))]
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DoctorSearchQuery {
+    pub department: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct InvitationIn {
+    /// Optional; must be the admin's own org when given.
+    pub org_id: Option<i32>,
+    #[validate(length(max = 200))]
+    pub org_name: Option<String>,
+    pub doctor_roll: Option<i32>,
+    #[validate(length(max = 100))]
+    pub department: Option<String>,
+    /// Defaults to 72 hours.
+    pub expires_in_hours: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InvitationFilter {
+    #[serde(default)]
+    pub pending: bool,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CredentialIn {
+    /// `license`, `specialty` or `certification`.
+    pub kind: String,
+    #[validate(length(min = 1, max = 200), custom(function = "not_blank"))]
+    pub title: String,
+    #[validate(length(max = 100))]
+    pub number: Option<String>,
+    #[validate(length(max = 200))]
+    pub issuer: Option<String>,
+    pub issued_on: Option<chrono::NaiveDate>,
+    pub expires_on: Option<chrono::NaiveDate>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExpiringQuery {
+    /// Look-ahead window, defaults to 30 days.
+    pub days: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct ShiftDefinitionIn {
+    /// Optional; must be the admin's own org when given.
+    pub org_id: Option<i32>,
+    #[validate(length(min = 1, max = 100), custom(function = "not_blank"))]
+    pub name: String,
+    pub start_time: chrono::NaiveTime,
+    pub duration_minutes: i32,
+    /// IANA zone the start time is expressed in, defaults to Asia/Ulaanbaatar.
+    #[validate(length(max = 64), custom(function = "iana_timezone"))]
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub on_call: bool,
+}
+
+/// Either `shift_definition_id` + `date`, or an explicit `starts_at` / `ends_at` window.
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct ShiftIn {
+    pub doctor_id: uuid::Uuid,
+    /// Defaults to the doctor's own department.
+    #[validate(length(max = 100))]
+    pub department: Option<String>,
+    pub shift_definition_id: Option<uuid::Uuid>,
+    pub date: Option<chrono::NaiveDate>,
+    pub starts_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub ends_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Overrides the definition's on-call flag.
+    pub on_call: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RosterQuery {
+    pub department: Option<String>,
+    pub doctor_id: Option<uuid::Uuid>,
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OnCallQuery {
+    pub org_id: i32,
+    pub department: String,
+    /// Defaults to now.
+    pub at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditQuery {
+    pub actor_id: Option<uuid::Uuid>,
+    pub action: Option<String>,
+    pub resource_type: Option<String>,
+    pub resource_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct EmergencyAccessIn {
+    /// Organization whose records are needed.
+    pub org_id: i32,
+    #[validate(length(max = 64))]
+    pub resource_type: Option<String>,
+    #[validate(length(max = 128))]
+    pub resource_id: Option<String>,
+    #[validate(length(max = 2_000))]
+    pub justification: String,
+    /// Defaults to 60, capped at 240.
+    pub duration_minutes: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EmergencyAccessQuery {
+    pub org_id: Option<i32>,
+    pub doctor_id: Option<uuid::Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NotificationQuery {
+    #[serde(default)]
+    pub unread: bool,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct PatientIn {
+    #[validate(length(min = 1, max = 100), custom(function = "not_blank"))]
+    pub first_name: String,
+    #[validate(length(min = 1, max = 100), custom(function = "not_blank"))]
+    pub last_name: String,
+    #[validate(length(max = 32))]
+    pub national_id: Option<String>,
+    pub birth_date: Option<chrono::NaiveDate>,
+    #[validate(length(max = 16))]
+    pub gender: Option<String>,
+    #[validate(length(max = 32))]
+    pub phone: Option<String>,
+    #[validate(length(max = 10_000))]
+    pub diagnoses: Option<String>,
+    #[validate(length(max = 10_000))]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PatientQuery {
+    pub national_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct ConsentIn {
+    /// `treatment`, `data_sharing` or `research`.
+    pub kind: String,
+    /// Only for `data_sharing`: the receiving organization, omitted for any.
+    pub scope_org_id: Option<i32>,
+    #[validate(length(min = 1, max = 32))]
+    pub form_version: String,
+    #[validate(length(min = 1, max = 200), custom(function = "not_blank"))]
+    pub signed_by: String,
+    #[validate(length(min = 1, max = 100_000))]
+    pub signature: String,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct ConsentWithdrawIn {
+    #[validate(length(max = 2_000))]
+    pub reason: Option<String>,
+}
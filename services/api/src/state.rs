@@ -1,6 +1,13 @@
 use auth::JwtKeys;
-use db::Db;
-use serde::Deserialize;
+use common::Secret;
+use db::crypto::CryptoError;
+use db::{Db, KeyRing};
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, ToSocketAddrs};
+use std::path::Path;
+use storage::{Storage, StorageConfig};
+
+use crate::telemetry::LogFormat;
 
 #[derive(Clone)]
 pub struct AppState {
@@ -10,32 +17,286 @@ pub struct AppState {
     pub refresh_ttl: i64,
     pub cookie_domain: String,
     pub cookie_secure: bool,
+    /// Org that self-registered doctors join, as plain doctors. `None` means
+    /// `/auth/register` only accepts admin-issued invitation codes.
+    pub open_registration: Option<i32>,
+    /// Minimum rest between two shifts of the same doctor.
+    pub min_rest_minutes: i32,
+    /// Blob backend for patient documents.
+    pub storage: Storage,
+    pub max_upload_bytes: u64,
+    /// Master keys for patient field encryption.
+    pub keys: KeyRing,
+    /// Reverse proxies whose `X-Forwarded-For` / `Forwarded` headers name the
+    /// client. Empty means the socket peer is the client.
+    pub trusted_proxies: Vec<IpAddr>,
+    /// Bearer token `/metrics` is scraped with; `None` turns the endpoint off.
+    pub metrics_token: Option<Secret>,
 }
 
-#[derive(Debug, Deserialize)]
+/// Read from an optional TOML file, then overridden by the environment.
+/// Keys are the field names, upper-cased in the environment.
+#[derive(Debug, Deserialize, Serialize)]
 pub struct Settings {
-    pub database_url: String,
-    pub jwt_secret: String,
+    pub database_url: Secret,
+    pub jwt_secret: Secret,
     pub access_ttl_seconds: Option<i64>,
     pub refresh_ttl_seconds: Option<i64>,
     pub cookie_domain: Option<String>,
     pub cookie_secure: Option<bool>,
+    pub open_registration: Option<bool>,
+    /// Org that open registration signs doctors up to; required with it.
+    pub open_registration_org_id: Option<i32>,
+    pub min_rest_minutes: Option<i32>,
+    /// `local` (default), `s3` or `memory`.
+    pub storage_backend: Option<String>,
+    pub storage_path: Option<String>,
+    pub s3_bucket: Option<String>,
+    pub s3_region: Option<String>,
+    pub s3_endpoint: Option<String>,
+    pub s3_access_key_id: Option<String>,
+    pub s3_secret_access_key: Option<Secret>,
+    pub s3_allow_http: Option<bool>,
+    pub max_upload_bytes: Option<u64>,
+    /// `version:base64key[,version:base64key...]`, 32-byte keys.
+    pub master_keys: Secret,
+    pub master_key_version: i32,
+    pub blind_index_key: Secret,
+    /// `text` (default) or `json`.
+    pub log_format: Option<LogFormat>,
+    /// OTLP/HTTP collector base URL; trace export is off when unset. Needs
+    /// the `otel` feature.
+    pub otel_exporter_otlp_endpoint: Option<String>,
+    /// How long a SIGTERM waits for in-flight requests, default 30.
+    pub shutdown_timeout_seconds: Option<u64>,
+    /// `host:port` to listen on, default `0.0.0.0:8080`.
+    pub bind_address: Option<String>,
+    /// HTTP worker threads, default one per CPU core.
+    pub workers: Option<usize>,
+    /// Postgres pool size, default 10.
+    pub db_max_connections: Option<u32>,
+    /// Requests a client may make in a burst before being limited, default 10.
+    pub rate_limit_burst: Option<u32>,
+    /// One request of the burst comes back every this many ms, default 500.
+    pub rate_limit_replenish_ms: Option<u64>,
+    /// Limit for JSON and other buffered bodies, default 2 MiB. Streamed
+    /// imports and document uploads are not buffered.
+    pub body_limit_bytes: Option<usize>,
+    /// PEM certificate chain and private key; HTTPS when both are set.
+    pub tls_cert_path: Option<String>,
+    pub tls_key_path: Option<String>,
+    /// Comma-separated in the environment. Unset or empty refuses every
+    /// cross-origin request; a lone `*` opts in to any origin.
+    pub cors_allowed_origins: Option<Vec<String>>,
+    /// IPs of the reverse proxies in front of the service, comma-separated in
+    /// the environment. Forwarded client addresses are ignored from anyone else.
+    pub trusted_proxies: Option<Vec<String>>,
+    /// Bearer token for `/metrics`, at least 16 characters. Unset disables
+    /// the endpoint.
+    pub metrics_token: Option<Secret>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SettingsError {
+    #[error("configuration: {0}")]
+    Load(#[from] config::ConfigError),
+
+    #[error("invalid configuration:\n  {}", .0.join("\n  "))]
+    Invalid(Vec<String>),
 }
 
 impl Settings {
+    /// [`Settings::load`] for binaries: prints what is wrong and exits.
     pub fn from_env() -> Self {
+        Self::load().unwrap_or_else(|e| {
+            eprintln!("{e}");
+            std::process::exit(2)
+        })
+    }
+
+    /// `.env`, then `CONFIG_FILE` (or `config.toml` if present), then the
+    /// process environment, validated.
+    pub fn load() -> Result<Self, SettingsError> {
         let _ = dotenvy::dotenv();
+        let file = std::env::var("CONFIG_FILE").ok();
+        Self::load_with(file.as_deref(), config::Environment::default())
+    }
 
-        let cfg = config::Config::builder()
+    /// `file` must exist when given. `env` takes precedence over it.
+    pub fn load_with(file: Option<&str>, env: config::Environment) -> Result<Self, SettingsError> {
+        let file = match file {
+            Some(path) => config::File::new(path, config::FileFormat::Toml),
+            None => config::File::new("config.toml", config::FileFormat::Toml).required(false),
+        };
+        let settings: Settings = config::Config::builder()
+            .add_source(file)
             .add_source(
-                config::Environment::default()
+                env
                     // .separator("_")  // <= ҮҮНИЙГ БҮҮ АШИГЛА
-                    .try_parsing(true),
+                    .try_parsing(true)
+                    .list_separator(",")
+                    .with_list_parse_key("cors_allowed_origins")
+                    .with_list_parse_key("trusted_proxies"),
             )
-            .build()
-            .expect("config");
+            .build()?
+            .try_deserialize()?;
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// Everything that would otherwise fail later, reported at once.
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        let mut problems = Vec::new();
+
+        if let Some(addr) = &self.bind_address
+            && addr.to_socket_addrs().is_err()
+        {
+            problems.push(format!("bind_address {addr:?} is not host:port"));
+        }
+        for (name, value) in [
+            ("workers", self.workers.map(|n| n as u64)),
+            ("db_max_connections", self.db_max_connections.map(u64::from)),
+            ("rate_limit_burst", self.rate_limit_burst.map(u64::from)),
+            ("rate_limit_replenish_ms", self.rate_limit_replenish_ms),
+            ("body_limit_bytes", self.body_limit_bytes.map(|n| n as u64)),
+        ] {
+            if value == Some(0) {
+                problems.push(format!("{name} must be at least 1"));
+            }
+        }
+        for (name, value) in [
+            ("access_ttl_seconds", self.access_ttl_seconds),
+            ("refresh_ttl_seconds", self.refresh_ttl_seconds),
+        ] {
+            if value.is_some_and(|v| v <= 0) {
+                problems.push(format!("{name} must be positive"));
+            }
+        }
+
+        if self.open_registration == Some(true) && self.open_registration_org_id.is_none() {
+            problems.push("open_registration_org_id is required with open_registration".into());
+        }
+
+        match (&self.tls_cert_path, &self.tls_key_path) {
+            (Some(cert), Some(key)) => {
+                for path in [cert, key] {
+                    if !Path::new(path).is_file() {
+                        problems.push(format!("TLS file {path:?} not found"));
+                    }
+                }
+            }
+            (None, None) => {}
+            _ => problems.push("tls_cert_path and tls_key_path must be set together".into()),
+        }
 
-        cfg.try_deserialize::<Settings>()
-            .expect("deserialize settings")
+        let origins = self.cors_allowed_origins.as_deref().unwrap_or_default();
+        if origins.len() > 1 && origins.iter().any(|o| o == "*") {
+            problems.push("CORS origin \"*\" cannot be combined with other origins".into());
+        }
+        for origin in origins {
+            if origin != "*" && !valid_origin(origin) {
+                problems.push(format!(
+                    "CORS origin {origin:?} must look like https://host[:port]"
+                ));
+            }
+        }
+
+        if self
+            .metrics_token
+            .as_ref()
+            .is_some_and(|t| t.expose().chars().count() < 16)
+        {
+            problems.push("metrics_token must be at least 16 characters".into());
+        }
+
+        for proxy in self.trusted_proxies.iter().flatten() {
+            if proxy.parse::<IpAddr>().is_err() {
+                problems.push(format!("trusted proxy {proxy:?} is not an IP address"));
+            }
+        }
+
+        if let Err(e) = self.key_ring() {
+            problems.push(format!(
+                "MASTER_KEYS / MASTER_KEY_VERSION / BLIND_INDEX_KEY: {e}"
+            ));
+        }
+
+        match self.storage_config() {
+            Ok(StorageConfig::Local { root }) => {
+                if root.exists() && !root.is_dir() {
+                    problems.push(format!("storage_path {root:?} is not a directory"));
+                }
+            }
+            Ok(cfg @ StorageConfig::S3 { .. }) => {
+                if let Err(e) = Storage::new(&cfg) {
+                    problems.push(format!("s3 storage: {e}"));
+                }
+            }
+            Ok(StorageConfig::Memory) => {}
+            Err(missing) => problems.extend(missing),
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(SettingsError::Invalid(problems))
+        }
     }
+
+    pub fn key_ring(&self) -> Result<KeyRing, CryptoError> {
+        KeyRing::parse(
+            self.master_keys.expose(),
+            self.master_key_version,
+            self.blind_index_key.expose(),
+        )
+    }
+
+    /// The configured blob backend, or what is missing for it.
+    pub fn storage_config(&self) -> Result<StorageConfig, Vec<String>> {
+        match self.storage_backend.as_deref().unwrap_or("local") {
+            "s3" => match (
+                &self.s3_bucket,
+                &self.s3_access_key_id,
+                &self.s3_secret_access_key,
+            ) {
+                (Some(bucket), Some(access_key_id), Some(secret_access_key)) => {
+                    Ok(StorageConfig::S3 {
+                        bucket: bucket.clone(),
+                        region: self.s3_region.clone().unwrap_or_else(|| "us-east-1".into()),
+                        endpoint: self.s3_endpoint.clone(),
+                        access_key_id: access_key_id.clone(),
+                        secret_access_key: secret_access_key.clone().into_inner(),
+                        allow_http: self.s3_allow_http.unwrap_or(false),
+                    })
+                }
+                (bucket, access_key_id, secret_access_key) => Err([
+                    ("s3_bucket", bucket.is_some()),
+                    ("s3_access_key_id", access_key_id.is_some()),
+                    ("s3_secret_access_key", secret_access_key.is_some()),
+                ]
+                .into_iter()
+                .filter(|(_, set)| !set)
+                .map(|(name, _)| format!("{name} is required for the s3 backend"))
+                .collect()),
+            },
+            "memory" => Ok(StorageConfig::Memory),
+            "local" => Ok(StorageConfig::Local {
+                root: self
+                    .storage_path
+                    .clone()
+                    .unwrap_or_else(|| "./data/documents".into())
+                    .into(),
+            }),
+            other => Err(vec![format!(
+                "storage_backend {other:?} is not local, s3 or memory"
+            )]),
+        }
+    }
+}
+
+/// `scheme://host[:port]` with an http(s) scheme and nothing after the host.
+fn valid_origin(origin: &str) -> bool {
+    origin.split_once("://").is_some_and(|(scheme, rest)| {
+        matches!(scheme, "http" | "https") && !rest.is_empty() && !rest.contains('/')
+    })
 }
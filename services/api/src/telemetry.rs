@@ -0,0 +1,148 @@
+//! Log output. `LOG_FORMAT=json` writes one JSON object per event with the
+//! enclosing spans attached, so every line carries the request id; the
+//! default is human-readable text. Verbosity comes from `RUST_LOG`.
+//!
+//! Built with the `otel` feature and given `OTEL_EXPORTER_OTLP_ENDPOINT`,
+//! request spans and the `db` query spans beneath them are also exported over
+//! OTLP/HTTP, continuing any W3C `traceparent` the caller sent.
+
+use serde::{Deserialize, Serialize};
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::Layer;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// Keep alive for the life of the process: dropping it flushes spans still
+/// waiting in the export batch.
+#[must_use = "dropping the guard stops trace export"]
+pub struct Telemetry {
+    #[cfg(feature = "otel")]
+    provider: Option<opentelemetry_sdk::trace::SdkTracerProvider>,
+}
+
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        #[cfg(feature = "otel")]
+        if let Some(provider) = self.provider.take()
+            && let Err(e) = provider.shutdown()
+        {
+            eprintln!("trace export shutdown: {e}");
+        }
+    }
+}
+
+/// `otlp_endpoint` is the collector base URL, e.g. `http://localhost:4318`.
+pub fn init(format: LogFormat, otlp_endpoint: Option<&str>) -> Telemetry {
+    let fmt = match format {
+        LogFormat::Text => tracing_subscriber::fmt::layer().boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .flatten_event(true)
+            .with_current_span(false)
+            .with_span_list(true)
+            .boxed(),
+    };
+    let registry =
+        tracing_subscriber::registry().with(fmt.with_filter(EnvFilter::from_default_env()));
+
+    #[cfg(feature = "otel")]
+    {
+        let (layer, provider) = match otlp_endpoint.map(otel::pipeline) {
+            Some(Ok((layer, provider))) => (Some(layer), Some(provider)),
+            Some(Err(e)) => {
+                eprintln!("trace export disabled: {e}");
+                (None, None)
+            }
+            None => (None, None),
+        };
+        registry.with(layer).init();
+        Telemetry { provider }
+    }
+
+    #[cfg(not(feature = "otel"))]
+    {
+        registry.init();
+        if otlp_endpoint.is_some() {
+            tracing::warn!("OTEL_EXPORTER_OTLP_ENDPOINT ignored: built without the `otel` feature");
+        }
+        Telemetry {}
+    }
+}
+
+/// Makes `span` a child of the trace described by the request's
+/// `traceparent`/`tracestate` headers. Without them the span starts a new trace.
+#[cfg(feature = "otel")]
+pub fn continue_remote_trace(span: &tracing::Span, headers: &actix_web::http::header::HeaderMap) {
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    let cx = opentelemetry::global::get_text_map_propagator(|p| p.extract(&otel::Headers(headers)));
+    // Fails only when no OpenTelemetry layer is installed.
+    let _ = span.set_parent(cx);
+}
+
+#[cfg(feature = "otel")]
+mod otel {
+    use actix_web::http::header::HeaderMap;
+    use opentelemetry::propagation::Extractor;
+    use opentelemetry::trace::TracerProvider;
+    use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+    use opentelemetry_sdk::Resource;
+    use opentelemetry_sdk::propagation::TraceContextPropagator;
+    use opentelemetry_sdk::trace::SdkTracerProvider;
+    use tracing::Level;
+    use tracing_subscriber::Layer;
+    use tracing_subscriber::filter::Targets;
+    use tracing_subscriber::registry::LookupSpan;
+
+    /// Request spans come from this crate; query spans from `db` at debug.
+    fn exported() -> Targets {
+        Targets::new()
+            .with_target(env!("CARGO_CRATE_NAME"), Level::INFO)
+            .with_target("db", Level::DEBUG)
+    }
+
+    pub(super) fn pipeline<S>(
+        endpoint: &str,
+    ) -> Result<(impl Layer<S>, SdkTracerProvider), opentelemetry_otlp::ExporterBuildError>
+    where
+        S: tracing::Subscriber + for<'a> LookupSpan<'a>,
+    {
+        let exporter = SpanExporter::builder()
+            .with_http()
+            .with_endpoint(format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+            .build()?;
+        let mut resource = Resource::builder();
+        if std::env::var_os("OTEL_SERVICE_NAME").is_none() {
+            resource = resource.with_service_name(env!("CARGO_PKG_NAME"));
+        }
+        let provider = SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(resource.build())
+            .build();
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+        let layer = tracing_opentelemetry::layer()
+            .with_tracer(provider.tracer(env!("CARGO_CRATE_NAME")))
+            .with_filter(exported());
+        Ok((layer, provider))
+    }
+
+    pub(super) struct Headers<'a>(pub &'a HeaderMap);
+
+    impl Extractor for Headers<'_> {
+        fn get(&self, key: &str) -> Option<&str> {
+            self.0.get(key).and_then(|v| v.to_str().ok())
+        }
+
+        fn keys(&self) -> Vec<&str> {
+            self.0.keys().map(|k| k.as_str()).collect()
+        }
+    }
+}
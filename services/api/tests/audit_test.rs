@@ -0,0 +1,180 @@
+use actix_web::test;
+use api::create_app;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+mod common;
+
+#[actix_web::test]
+async fn test_audit_trail_for_item_lifecycle() {
+    let state = common::test_state().await;
+    let db = state.db.clone();
+    let app = test::init_service(create_app(state)).await;
+
+    let body = common::sign_up(
+        &app,
+        &db,
+        json!({
+            "reg_no": format!("ADM-{}", Uuid::new_v4()),
+            "first_name": "Audit",
+            "last_name": "Admin",
+            "org_id": 1,
+            "doctor_roll": 1,
+            "password": "supersecret"
+        }),
+    )
+    .await;
+    let admin_id = body["doctor"]["id"].as_str().unwrap().to_string();
+    let bearer = (
+        "Authorization",
+        format!("Bearer {}", body["tokens"]["access"].as_str().unwrap()),
+    );
+    let request_id = Uuid::new_v4().to_string();
+
+    // ==========================================
+    // ✅ 1. Item create → read → update → delete
+    // ==========================================
+    let req = test::TestRequest::post()
+        .uri("/items")
+        .insert_header(bearer.clone())
+        .insert_header(("X-Request-Id", request_id.clone()))
+        .set_json(json!({"title": "Chest X-ray", "description": "left lung"}))
+        .to_request();
+    let item: Value = test::call_and_read_body_json(&app, req).await;
+    let item_id = item["id"].as_str().unwrap().to_string();
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/items/{item_id}"))
+        .insert_header(bearer.clone())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/items/{item_id}"))
+        .insert_header(bearer.clone())
+        .insert_header(("If-Match", "\"1\""))
+        .set_json(json!({"title": "Chest X-ray", "description": "both lungs"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/items/{item_id}"))
+        .insert_header(bearer.clone())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+
+    // ==========================================
+    // ✅ 2. db-layer entries with before/after diff
+    // ==========================================
+    let req = test::TestRequest::get()
+        .uri(&format!("/audit?resource_type=item&resource_id={item_id}"))
+        .insert_header(bearer.clone())
+        .to_request();
+    let page: Value = test::call_and_read_body_json(&app, req).await;
+    let rows = page["items"].as_array().unwrap();
+    let actions: Vec<_> = rows.iter().map(|r| r["action"].as_str().unwrap()).collect();
+    assert_eq!(actions, vec!["delete", "update", "read", "create"]);
+    assert!(rows.iter().all(|r| r["actor_id"] == admin_id.as_str()));
+    assert_eq!(rows[1]["diff"]["before"]["description"], "left lung");
+    assert_eq!(rows[1]["diff"]["after"]["description"], "both lungs");
+    assert_eq!(rows[3]["request_id"], request_id.as_str());
+
+    let req = test::TestRequest::get()
+        .uri(&format!(
+            "/audit?resource_type=item&resource_id={item_id}&limit=2"
+        ))
+        .insert_header(bearer.clone())
+        .to_request();
+    let first: Value = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get()
+        .uri(&format!(
+            "/audit?resource_type=item&resource_id={item_id}&limit=2&cursor={}",
+            first["next_cursor"].as_str().unwrap()
+        ))
+        .insert_header(bearer.clone())
+        .to_request();
+    let second: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(second["items"][0]["action"], "read");
+    assert!(second["next_cursor"].is_null());
+
+    let req = test::TestRequest::get()
+        .uri("/audit?limit=5000")
+        .insert_header(bearer.clone())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 400);
+
+    // ==========================================
+    // ✅ 3. Middleware entries for mutating requests
+    // ==========================================
+    let req = test::TestRequest::get()
+        .uri(&format!("/audit?actor_id={admin_id}&action=http.delete"))
+        .insert_header(bearer.clone())
+        .to_request();
+    let page: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(page["items"][0]["resource_id"], format!("/items/{item_id}"));
+    assert_eq!(page["items"][0]["diff"]["status"], 200);
+
+    // ==========================================
+    // ✅ 4. Hash chain is intact
+    // ==========================================
+    let req = test::TestRequest::get()
+        .uri("/audit/verify")
+        .insert_header(bearer)
+        .to_request();
+    let report: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(report["valid"], true, "chain broken: {report}");
+}
+
+#[actix_web::test]
+async fn test_forwarded_client_ip_only_from_trusted_proxy() {
+    let mut state = common::test_state().await;
+    state.trusted_proxies = vec!["10.0.0.2".parse().unwrap()];
+    let db = state.db.clone();
+    let app = test::init_service(create_app(state)).await;
+
+    let body = common::sign_up(
+        &app,
+        &db,
+        json!({
+            "reg_no": format!("ADM-{}", Uuid::new_v4()),
+            "first_name": "Audit",
+            "last_name": "Proxy",
+            "doctor_roll": 1
+        }),
+    )
+    .await;
+    let bearer = (
+        "Authorization",
+        format!("Bearer {}", body["tokens"]["access"].as_str().unwrap()),
+    );
+
+    // ==========================================
+    // ✅ 1. X-Forwarded-For нь зөвхөн proxy-оос итгэгдэнэ
+    // ==========================================
+    for (peer, expected) in [
+        ("203.0.113.9:5000", "203.0.113.9"),
+        ("10.0.0.2:5000", "198.51.100.7"),
+    ] {
+        let req = test::TestRequest::post()
+            .uri("/items")
+            .peer_addr(peer.parse().unwrap())
+            .insert_header(bearer.clone())
+            .insert_header(("X-Forwarded-For", "198.51.100.7"))
+            .set_json(json!({"title": "Forwarded"}))
+            .to_request();
+        let item: Value = test::call_and_read_body_json(&app, req).await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!(
+                "/audit?resource_type=item&resource_id={}",
+                item["id"].as_str().unwrap()
+            ))
+            .insert_header(bearer.clone())
+            .to_request();
+        let page: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(page["items"][0]["ip"], expected, "peer {peer}");
+    }
+}
@@ -1,34 +1,14 @@
 use actix_web::test;
 use api::create_app; // 👈 lib.rs доторх create_app-г ашиглана
-use api::state::AppState;
-use auth::JwtKeys;
-use db::connect;
 use serde_json::json;
-use std::env;
 use uuid::Uuid;
 
+mod common;
+
 #[actix_web::test] // 👈 actix_rt::test биш, actix_web::test хэрэглэнэ
 async fn test_auth_flow_register_login_refresh_logout() {
-    dotenvy::dotenv().ok();
-
-    // ⚙️ Тест DB холболт
-    let db_url = env::var("TEST_DATABASE_URL")
-        .or_else(|_| env::var("DATABASE_URL"))
-        .expect("❌ DATABASE_URL тохируулагдаагүй байна");
-    let db = connect(&db_url, 5).await.expect("❌ DB холбогдсонгүй");
-
-    // ⚙️ JWT key
-    let keys = JwtKeys::from_secret("test_secret_key");
-
-    // ⚙️ AppState mock
-    let state = AppState {
-        db,
-        jwt: keys,
-        access_ttl: 3600,              // 1 цаг
-        refresh_ttl: 60 * 60 * 24 * 7, // 7 хоног
-        cookie_domain: "localhost".into(),
-        cookie_secure: false,
-    };
+    // ⚙️ AppState mock (tests/common)
+    let state = common::test_state().await;
 
     // ⚙️ App үүсгэх (lib.rs доторхи create_app ашиглана)
     let app = test::init_service(create_app(state.clone())).await;
@@ -43,7 +23,7 @@ async fn test_auth_flow_register_login_refresh_logout() {
         "last_name": "Bat",
         "rank_name": "Surgeon",
         "org_name": "UB Hospital",
-        "org_id": 1,
+        "org_id": 7,
         "position": "Cardio",
         "birth_date": "1990-05-12",
         "gender": "male",
@@ -68,6 +48,10 @@ async fn test_auth_flow_register_login_refresh_logout() {
     let access_token = body["tokens"]["access"].as_str().unwrap().to_string();
     let refresh_token = body["tokens"]["refresh"].as_str().unwrap().to_string();
 
+    // ⚙️ Клиентийн илгээсэн org_id, doctor_roll тооцогдохгүй
+    let claims = auth::verify(&state.jwt, &access_token).unwrap();
+    assert_eq!(claims.role, "Doctor");
+    assert_eq!(claims.org_id, Some(1));
     println!("✅ Registered doctor_id={doctor_id}");
     println!("Access token (first 40 chars): {}", &access_token[..40]);
     println!("Refresh token (first 40 chars): {}", &refresh_token[..40]);
@@ -143,3 +127,71 @@ async fn test_auth_flow_register_login_refresh_logout() {
 
     println!("✅ Logout successful");
 }
+
+#[actix_web::test]
+async fn test_refresh_reloads_role_and_rejects_deactivated() {
+    let state = common::test_state().await;
+    let app = test::init_service(create_app(state.clone())).await;
+
+    let req = test::TestRequest::post()
+        .uri("/auth/register")
+        .set_json(json!({
+            "reg_no": format!("DOC-{}", Uuid::new_v4()),
+            "first_name": "Refresh",
+            "last_name": "Check",
+            "org_id": 1,
+            "password": "supersecret"
+        }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let doctor_id: Uuid = body["doctor"]["id"].as_str().unwrap().parse().unwrap();
+    let refresh = |token: String| {
+        test::TestRequest::post()
+            .uri("/auth/refresh")
+            .cookie(
+                actix_web::cookie::Cookie::build("refresh_token", token)
+                    .path("/")
+                    .finish(),
+            )
+            .to_request()
+    };
+
+    // ==========================================
+    // ✅ 1. Эрх өөрчлөгдвөл refresh шинэ эрхийг авна
+    // ==========================================
+    sqlx::query(
+        "UPDATE doctor_user SET doctor_roll = \
+         (SELECT roll_id FROM doctor_rolls WHERE roll_name = 'admin') WHERE id = $1",
+    )
+    .bind(doctor_id)
+    .execute(&state.db.0)
+    .await
+    .unwrap();
+    let resp = test::call_service(
+        &app,
+        refresh(body["tokens"]["refresh"].as_str().unwrap().to_string()),
+    )
+    .await;
+    assert_eq!(resp.status(), 200);
+    let rotated = resp
+        .response()
+        .cookies()
+        .find(|c| c.name() == "refresh_token")
+        .unwrap()
+        .value()
+        .to_string();
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let claims = auth::verify(&state.jwt, body["access_token"].as_str().unwrap()).unwrap();
+    assert_eq!(claims.role, "Admin");
+
+    // ==========================================
+    // ✅ 2. Идэвхгүй болсон эмч refresh хийж чадахгүй
+    // ==========================================
+    sqlx::query("UPDATE doctor_user SET is_active = false WHERE id = $1")
+        .bind(doctor_id)
+        .execute(&state.db.0)
+        .await
+        .unwrap();
+    let resp = test::call_service(&app, refresh(rotated)).await;
+    assert_eq!(resp.status(), 403);
+}
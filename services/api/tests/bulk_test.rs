@@ -0,0 +1,136 @@
+use actix_web::test;
+use api::create_app;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+mod common;
+
+#[actix_web::test]
+async fn test_bulk_import_and_streaming_export() {
+    let state = common::test_state().await;
+    let db = state.db.clone();
+    let app = test::init_service(create_app(state)).await;
+    let org_id = 1000 + (Uuid::new_v4().as_u128() % 100_000) as i32;
+    let body = common::sign_up(
+        &app,
+        &db,
+        json!({
+            "reg_no": format!("BK-{}", Uuid::new_v4()),
+            "first_name": "Anu",
+            "last_name": "Bayar",
+            "org_id": org_id,
+            "password": "supersecret"
+        }),
+    )
+    .await;
+    let bearer = (
+        "Authorization",
+        format!("Bearer {}", body["tokens"]["access"].as_str().unwrap()),
+    );
+    let upload = |query: &str, content_type: &str, body: Vec<u8>| {
+        test::TestRequest::post()
+            .uri(&format!("/items/import{query}"))
+            .insert_header(bearer.clone())
+            .insert_header(("Content-Type", content_type.to_string()))
+            .set_payload(body)
+            .to_request()
+    };
+    let export = |format: &str| {
+        test::TestRequest::get()
+            .uri(&format!("/items/export?format={format}"))
+            .insert_header(bearer.clone())
+            .to_request()
+    };
+
+    // ==========================================
+    // ✅ 1. Atomic: нэг буруу мөр → юу ч орохгүй, 422
+    // ==========================================
+    let csv = "title,description\nECG,normal\n,missing title\nCBC,\n";
+    let resp = test::call_service(&app, upload("", "text/csv", csv.into())).await;
+    assert_eq!(resp.status(), 422);
+    let report: Value = test::read_body_json(resp).await;
+    assert_eq!(report["rows"], 3);
+    assert_eq!(report["imported"], 0);
+    assert_eq!(report["errors"][0]["row"], 2);
+    let body = test::call_and_read_body(&app, export("ndjson")).await;
+    assert!(body.is_empty());
+
+    // ==========================================
+    // ✅ 2. Best effort: BOM, CRLF, олон мөрт талбар, tab/backslash
+    // ==========================================
+    let csv =
+        "\u{feff}Description,Title\r\n\"line 1\nline 2\",Эхо\r\n\"a\tb\\c\",MRI\r\nno title,\r\n";
+    let resp = test::call_service(&app, upload("?mode=best_effort", "text/csv", csv.into())).await;
+    assert_eq!(resp.status(), 200);
+    let report: Value = test::read_body_json(resp).await;
+    assert_eq!(report["imported"], 2);
+    assert_eq!(report["failed"], 1);
+    assert_eq!(report["errors"][0]["row"], 3);
+
+    // ==========================================
+    // ✅ 3. NDJSON: багцаас олон мөр (COPY хэд хэдэн удаа)
+    // ==========================================
+    let mut ndjson = String::new();
+    for i in 0..1200 {
+        ndjson.push_str(&json!({"title": format!("Vitals #{i}")}).to_string());
+        ndjson.push('\n');
+    }
+    ndjson.push_str(r#"{"title": "last", "description": "no newline"}"#);
+    let resp = test::call_service(&app, upload("", "application/x-ndjson", ndjson.into())).await;
+    let report: Value = test::read_body_json(resp).await;
+    assert_eq!(report["imported"], 1201);
+
+    // ==========================================
+    // ✅ 4. Export: хуудаслан урсгана, дахин импортлох боломжтой
+    // ==========================================
+    let body = test::call_and_read_body(&app, export("ndjson")).await;
+    let lines: Vec<Value> = std::str::from_utf8(&body)
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 1203);
+    let ids: std::collections::HashSet<_> = lines.iter().map(|l| l["id"].clone()).collect();
+    assert_eq!(ids.len(), 1203);
+    let echo = lines.iter().find(|l| l["title"] == "Эхо").unwrap();
+    assert_eq!(echo["description"], "line 1\nline 2");
+    let mri = lines.iter().find(|l| l["title"] == "MRI").unwrap();
+    assert_eq!(mri["description"], "a\tb\\c");
+
+    let resp = test::call_service(&app, export("csv")).await;
+    assert_eq!(
+        resp.headers().get("Content-Type").unwrap(),
+        "text/csv; charset=utf-8"
+    );
+    let csv = test::read_body(resp).await;
+    assert!(csv.starts_with(b"id,title,description,created_at,updated_at,version\n"));
+
+    let resp = test::call_service(&app, upload("?format=csv", "text/plain", csv.to_vec())).await;
+    let report: Value = test::read_body_json(resp).await;
+    assert_eq!(report["imported"], 1203);
+
+    // ==========================================
+    // ✅ 5. Бүхэлдээ буруу файл → 400
+    // ==========================================
+    let resp = test::call_service(&app, upload("", "text/plain", b"title\nx\n".to_vec())).await;
+    assert_eq!(resp.status(), 400);
+    let resp = test::call_service(&app, upload("", "text/csv", b"name\nx\n".to_vec())).await;
+    assert_eq!(resp.status(), 400);
+    let resp =
+        test::call_service(&app, upload("", "text/csv", b"title,\xff\nx,y\n".to_vec())).await;
+    assert_eq!(resp.status(), 400);
+
+    // max_upload_bytes (1 MiB in tests) is enforced while reading
+    let mut big = String::new();
+    while big.len() <= 1024 * 1024 {
+        big.push_str(&json!({"title": "Too many vitals"}).to_string());
+        big.push('\n');
+    }
+    let resp = test::call_service(&app, upload("", "application/x-ndjson", big.into())).await;
+    assert_eq!(resp.status(), 413);
+    let body = test::call_and_read_body(&app, export("ndjson")).await;
+    assert_eq!(
+        std::str::from_utf8(&body).unwrap().lines().count(),
+        1203 * 2
+    );
+}
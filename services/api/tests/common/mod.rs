@@ -0,0 +1,79 @@
+use actix_http::Request;
+use actix_web::body::MessageBody;
+use actix_web::dev::{Service, ServiceResponse};
+use actix_web::test;
+use api::state::AppState;
+use auth::{JwtKeys, hash_password};
+use db::{Db, NewDoctor, connect, insert_doctor_user};
+use serde_json::{Value, json};
+use std::env;
+
+/// Bearer token `/metrics` accepts in [`test_state`].
+#[allow(dead_code)]
+pub const METRICS_TOKEN: &str = "test-metrics-token";
+
+/// App state against the test database with fixed keys and in-memory storage.
+/// Tests tweak individual fields on the returned value as needed.
+pub async fn test_state() -> AppState {
+    dotenvy::dotenv().ok();
+    let db_url = env::var("TEST_DATABASE_URL")
+        .or_else(|_| env::var("DATABASE_URL"))
+        .expect("❌ DATABASE_URL тохируулагдаагүй байна");
+    let db = connect(&db_url, 5).await.expect("❌ DB холбогдсонгүй");
+    AppState {
+        db,
+        jwt: JwtKeys::from_secret("test_secret_key"),
+        access_ttl: 3600,
+        refresh_ttl: 60 * 60 * 24 * 7,
+        cookie_domain: "localhost".into(),
+        cookie_secure: false,
+        open_registration: Some(1),
+        min_rest_minutes: 8 * 60,
+        storage: storage::Storage::memory(),
+        max_upload_bytes: 1024 * 1024,
+        keys: db::KeyRing::new(1, [(1, [7; 32])], [9; 32]).unwrap(),
+        trusted_proxies: Vec::new(),
+        metrics_token: Some(METRICS_TOKEN.to_string().into()),
+    }
+}
+
+/// Creates a doctor straight in the database, the way an admin or an
+/// invitation would, and logs in. `doctor` takes the `/auth/register` fields
+/// plus `org_id` (default 1) and `doctor_roll`, which clients cannot set
+/// themselves. Returns the login body.
+#[allow(dead_code)]
+pub async fn sign_up<S, B>(app: &S, db: &Db, doctor: Value) -> Value
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let text = |key: &str| doctor[key].as_str();
+    let reg_no = text("reg_no").expect("reg_no");
+    let password = text("password").unwrap_or("supersecret");
+    let hash = hash_password(password).unwrap();
+    insert_doctor_user(
+        db,
+        &NewDoctor {
+            reg_no,
+            first_name: text("first_name").unwrap_or("Test"),
+            last_name: text("last_name").unwrap_or("Doctor"),
+            rank_name: text("rank_name"),
+            org_name: text("org_name"),
+            org_id: doctor["org_id"].as_i64().map_or(1, |id| id as i32),
+            position: text("position"),
+            birth_date: None,
+            gender: text("gender"),
+            doctor_roll: doctor["doctor_roll"].as_i64().map(|id| id as i32),
+            department: text("department"),
+            password_hash: &hash,
+        },
+    )
+    .await
+    .expect("❌ doctor insert");
+
+    let req = test::TestRequest::post()
+        .uri("/auth/login")
+        .set_json(json!({"reg_no": reg_no, "password": password}))
+        .to_request();
+    test::call_and_read_body_json(app, req).await
+}
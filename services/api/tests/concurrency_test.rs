@@ -0,0 +1,104 @@
+use actix_web::test;
+use api::create_app;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+mod common;
+
+#[actix_web::test]
+async fn test_item_updates_require_current_etag() {
+    let state = common::test_state().await;
+    let db = state.db.clone();
+    let app = test::init_service(create_app(state)).await;
+    let org_id = 1000 + (Uuid::new_v4().as_u128() % 100_000) as i32;
+    let body = common::sign_up(
+        &app,
+        &db,
+        json!({
+            "reg_no": format!("CC-{}", Uuid::new_v4()),
+            "first_name": "Oyun",
+            "last_name": "Bat",
+            "org_id": org_id,
+            "password": "supersecret"
+        }),
+    )
+    .await;
+    let bearer = (
+        "Authorization",
+        format!("Bearer {}", body["tokens"]["access"].as_str().unwrap()),
+    );
+
+    let req = test::TestRequest::post()
+        .uri("/items")
+        .insert_header(bearer.clone())
+        .set_json(json!({"title": "Medication chart", "description": null}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.headers().get("ETag").unwrap(), "\"1\"");
+    let item: Value = test::read_body_json(resp).await;
+    let uri = format!("/items/{}", item["id"].as_str().unwrap());
+
+    // ==========================================
+    // ✅ 1. GET нь ETag буцаана
+    // ==========================================
+    let req = test::TestRequest::get()
+        .uri(&uri)
+        .insert_header(bearer.clone())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let etag = resp
+        .headers()
+        .get("ETag")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert_eq!(etag, "\"1\"");
+
+    // ==========================================
+    // ✅ 2. If-Match байхгүй → 428
+    // ==========================================
+    let put = |if_match: Option<&str>, title: &str| {
+        let mut req = test::TestRequest::put()
+            .uri(&uri)
+            .insert_header(bearer.clone())
+            .set_json(json!({"title": title, "description": null}));
+        if let Some(tag) = if_match {
+            req = req.insert_header(("If-Match", tag.to_string()));
+        }
+        req.to_request()
+    };
+    let resp = test::call_service(&app, put(None, "no tag")).await;
+    assert_eq!(resp.status(), 428);
+    let resp = test::call_service(&app, put(Some("*"), "any")).await;
+    assert_eq!(resp.status(), 428);
+
+    // ==========================================
+    // ✅ 3. Эхний сувилагч амжилттай, хоёр дахь нь 412
+    // ==========================================
+    let resp = test::call_service(&app, put(Some(&etag), "nurse A")).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers().get("ETag").unwrap(), "\"2\"");
+
+    let resp = test::call_service(&app, put(Some(&etag), "nurse B")).await;
+    assert_eq!(resp.status(), 412);
+    assert_eq!(resp.headers().get("ETag").unwrap(), "\"2\"");
+    assert_eq!(
+        resp.headers().get("Content-Type").unwrap(),
+        "application/problem+json"
+    );
+    let problem: Value = test::read_body_json(resp).await;
+    assert_eq!(problem["status"], 412);
+    assert_eq!(problem["current_version"], 2);
+
+    // weak tags never match
+    let resp = test::call_service(&app, put(Some("W/\"2\""), "weak")).await;
+    assert_eq!(resp.status(), 412);
+
+    // retry with the fresh tag
+    let resp = test::call_service(&app, put(Some("\"2\""), "nurse B")).await;
+    assert_eq!(resp.status(), 200);
+    let row: Value = test::read_body_json(resp).await;
+    assert_eq!(row["title"], "nurse B");
+    assert_eq!(row["version"], 3);
+}
@@ -0,0 +1,297 @@
+use actix_web::ResponseError;
+use actix_web::test;
+use api::create_app;
+use api::extractors::{AuthUser, require_active_license};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+mod common;
+
+fn register_payload(reg_no: &str, doctor_roll: i32) -> Value {
+    json!({
+        "reg_no": reg_no,
+        "first_name": "Saraa",
+        "last_name": "Dorj",
+        "org_name": "UB Hospital",
+        "org_id": 1,
+        "doctor_roll": doctor_roll,
+        "password": "supersecret"
+    })
+}
+
+#[actix_web::test]
+async fn test_doctor_profile_and_admin_directory() {
+    let state = common::test_state().await;
+    let db = state.db.clone();
+    let app = test::init_service(create_app(state)).await;
+
+    // ==========================================
+    // ✅ 1. Doctor reads and edits own profile
+    // ==========================================
+    let reg_no = format!("DOC-{}", Uuid::new_v4());
+    let body = common::sign_up(&app, &db, register_payload(&reg_no, 3)).await;
+    let doctor_id = body["doctor"]["id"].as_str().unwrap().to_string();
+    let access = body["tokens"]["access"].as_str().unwrap().to_string();
+    let bearer = ("Authorization", format!("Bearer {access}"));
+
+    let req = test::TestRequest::get()
+        .uri("/me")
+        .insert_header(bearer.clone())
+        .to_request();
+    let me: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(me["reg_no"], reg_no);
+    assert!(me.get("password_hash").is_none(), "password_hash leaked");
+
+    let req = test::TestRequest::patch()
+        .uri("/me")
+        .insert_header(bearer.clone())
+        .set_json(json!({"phone": "99112233", "position": "Cardiologist"}))
+        .to_request();
+    let me: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(me["phone"], "99112233");
+    assert_eq!(me["position"], "Cardiologist");
+    assert_eq!(me["first_name"], "Saraa", "untouched fields must stay");
+
+    // org_id is not self-service
+    let req = test::TestRequest::patch()
+        .uri("/me")
+        .insert_header(bearer.clone())
+        .set_json(json!({"org_id": 99}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+
+    let req = test::TestRequest::get()
+        .uri("/doctors")
+        .insert_header(bearer)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 403, "non-admin must not list doctors");
+
+    // ==========================================
+    // ✅ 2. Admin searches and reassigns
+    // ==========================================
+    let admin_reg_no = format!("ADM-{}", Uuid::new_v4());
+    let body = common::sign_up(&app, &db, register_payload(&admin_reg_no, 1)).await;
+    let admin_bearer = (
+        "Authorization",
+        format!("Bearer {}", body["tokens"]["access"].as_str().unwrap()),
+    );
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/doctors?q={reg_no}&limit=5"))
+        .insert_header(admin_bearer.clone())
+        .to_request();
+    let page: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(page["items"].as_array().unwrap().len(), 1);
+    assert!(page["next_cursor"].is_null());
+    assert_eq!(page["items"][0]["id"], doctor_id);
+    assert!(page["items"][0].get("password_hash").is_none());
+
+    let req = test::TestRequest::get()
+        .uri("/doctors?limit=500")
+        .insert_header(admin_bearer.clone())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 400);
+
+    // moving a doctor to another hospital is refused
+    let req = test::TestRequest::patch()
+        .uri(&format!("/doctors/{doctor_id}"))
+        .insert_header(admin_bearer.clone())
+        .set_json(json!({"org_id": 2, "department": "Cardiology"}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 403);
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/doctors/{doctor_id}"))
+        .insert_header(admin_bearer.clone())
+        .set_json(json!({"org_id": 1, "department": "Cardiology"}))
+        .to_request();
+    let updated: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(updated["org_id"], 1);
+    assert_eq!(updated["department"], "Cardiology");
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/doctors/{}", Uuid::new_v4()))
+        .insert_header(admin_bearer.clone())
+        .set_json(json!({"department": "ER"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+
+    // ==========================================
+    // ✅ 3. Other hospitals' doctors are invisible
+    // ==========================================
+    let other_reg_no = format!("DOC-{}", Uuid::new_v4());
+    let mut payload = register_payload(&other_reg_no, 1);
+    payload["org_id"] = json!(2);
+    let body = common::sign_up(&app, &db, payload).await;
+    let other_id = body["doctor"]["id"].as_str().unwrap().to_string();
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/doctors?q={other_reg_no}"))
+        .insert_header(admin_bearer.clone())
+        .to_request();
+    let page: Value = test::call_and_read_body_json(&app, req).await;
+    assert!(page["items"].as_array().unwrap().is_empty());
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/doctors/{other_id}"))
+        .insert_header(admin_bearer.clone())
+        .set_json(json!({"doctor_roll": 3}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 404);
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/doctors/{other_id}"))
+        .insert_header(admin_bearer)
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_doctor_credentials_and_expiry_alerts() {
+    let state = common::test_state().await;
+    let db = state.db.clone();
+    let app = test::init_service(create_app(state)).await;
+    let org_id = 1000 + (Uuid::new_v4().as_u128() % 100_000) as i32;
+
+    let mut bearers = Vec::new();
+    let mut ids = Vec::new();
+    for (prefix, roll) in [("DOC", 3), ("ADM", 1)] {
+        let mut payload = register_payload(&format!("{prefix}-{}", Uuid::new_v4()), roll);
+        payload["org_id"] = json!(org_id);
+        let body = common::sign_up(&app, &db, payload).await;
+        ids.push(body["doctor"]["id"].as_str().unwrap().to_string());
+        bearers.push((
+            "Authorization",
+            format!("Bearer {}", body["tokens"]["access"].as_str().unwrap()),
+        ));
+    }
+    let (doctor_id, doctor_bearer, admin_bearer) = (&ids[0], &bearers[0], &bearers[1]);
+
+    // ==========================================
+    // ✅ 1. Expired license blocks prescribing
+    // ==========================================
+    let today = chrono::Utc::now().date_naive();
+    let req = test::TestRequest::post()
+        .uri(&format!("/doctors/{doctor_id}/credentials"))
+        .insert_header(admin_bearer.clone())
+        .set_json(json!({
+            "kind": "license",
+            "title": "Medical license",
+            "number": "MN-12345",
+            "expires_on": today - chrono::Duration::days(1)
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/doctors/{doctor_id}/credentials"))
+        .insert_header(doctor_bearer.clone())
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["credentials"].as_array().unwrap().len(), 1);
+    assert_eq!(body["can_prescribe"], false);
+
+    let prescriber = AuthUser {
+        user_id: doctor_id.parse().unwrap(),
+        role: "Doctor".into(),
+        org_id,
+    };
+    let err = require_active_license(&db, &prescriber).await.unwrap_err();
+    assert_eq!(err.status_code(), 403);
+
+    // ==========================================
+    // ✅ 2. Renewed license, certification expiring soon
+    // ==========================================
+    for (kind, title, days) in [
+        ("license", "Medical license", 365),
+        ("certification", "ACLS", 10),
+    ] {
+        let req = test::TestRequest::post()
+            .uri(&format!("/doctors/{doctor_id}/credentials"))
+            .insert_header(admin_bearer.clone())
+            .set_json(json!({
+                "kind": kind,
+                "title": title,
+                "expires_on": today + chrono::Duration::days(days)
+            }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 201);
+    }
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/doctors/{doctor_id}/credentials"))
+        .insert_header(doctor_bearer.clone())
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["can_prescribe"], true);
+    require_active_license(&db, &prescriber).await.unwrap();
+
+    let req = test::TestRequest::get()
+        .uri("/credentials/expiring?days=30")
+        .insert_header(admin_bearer.clone())
+        .to_request();
+    let rows: Value = test::call_and_read_body_json(&app, req).await;
+    let titles: Vec<_> = rows
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| {
+            (
+                r["title"].as_str().unwrap(),
+                r["days_left"].as_i64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(titles, vec![("Medical license", -1), ("ACLS", 10)]);
+
+    // HR endpoints are admin-only
+    let req = test::TestRequest::get()
+        .uri("/credentials/expiring")
+        .insert_header(doctor_bearer.clone())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 403);
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/doctors/{doctor_id}/credentials"))
+        .insert_header(admin_bearer.clone())
+        .set_json(json!({"kind": "diploma", "title": "x"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+
+    // ==========================================
+    // ✅ 3. Other hospitals' admins cannot reach them
+    // ==========================================
+    let mut payload = register_payload(&format!("ADM-{}", Uuid::new_v4()), 1);
+    payload["org_id"] = json!(org_id + 1);
+    let body = common::sign_up(&app, &db, payload).await;
+    let other_bearer = (
+        "Authorization",
+        format!("Bearer {}", body["tokens"]["access"].as_str().unwrap()),
+    );
+    let req = test::TestRequest::get()
+        .uri(&format!("/doctors/{doctor_id}/credentials"))
+        .insert_header(other_bearer.clone())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 404);
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/doctors/{doctor_id}/credentials"))
+        .insert_header(other_bearer.clone())
+        .set_json(json!({"kind": "license", "title": "Forged license"}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 404);
+
+    let req = test::TestRequest::get()
+        .uri("/credentials/expiring?days=30")
+        .insert_header(other_bearer)
+        .to_request();
+    let rows: Value = test::call_and_read_body_json(&app, req).await;
+    assert!(rows.as_array().unwrap().is_empty());
+}
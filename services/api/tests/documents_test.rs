@@ -0,0 +1,230 @@
+use actix_web::test;
+use api::create_app;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+mod common;
+
+const BOUNDARY: &str = "----hospital-test-boundary";
+
+fn multipart(kind: &str, file_name: &str, content: &[u8]) -> Vec<u8> {
+    let mut body = format!(
+        "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"kind\"\r\n\r\n{kind}\r\n\
+         --{BOUNDARY}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{file_name}\"\r\n\
+         Content-Type: application/octet-stream\r\n\r\n"
+    )
+    .into_bytes();
+    body.extend_from_slice(content);
+    body.extend_from_slice(format!("\r\n--{BOUNDARY}--\r\n").as_bytes());
+    body
+}
+
+fn png(len: usize) -> Vec<u8> {
+    let mut bytes = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+    bytes.resize(len, 7);
+    bytes
+}
+
+#[actix_web::test]
+async fn test_document_upload_download_and_limits() {
+    let mut state = common::test_state().await;
+    let db = state.db.clone();
+    state.max_upload_bytes = 64 * 1024;
+    let app = test::init_service(create_app(state)).await;
+    let org_id = 1000 + (Uuid::new_v4().as_u128() % 100_000) as i32;
+
+    let mut bearers = Vec::new();
+    for (prefix, org) in [("DOC", org_id), ("OUT", org_id + 1)] {
+        let body = common::sign_up(
+            &app,
+            &db,
+            json!({
+                "reg_no": format!("{prefix}-{}", Uuid::new_v4()),
+                "first_name": "Oyun",
+                "last_name": "Tsetseg",
+                "org_id": org,
+                "password": "supersecret"
+            }),
+        )
+        .await;
+        bearers.push((
+            "Authorization",
+            format!("Bearer {}", body["tokens"]["access"].as_str().unwrap()),
+        ));
+    }
+    let (doctor, outsider) = (&bearers[0], &bearers[1]);
+
+    let req = test::TestRequest::post()
+        .uri("/patients")
+        .insert_header(doctor.clone())
+        .set_json(json!({"first_name": "Bat", "last_name": "Erdene"}))
+        .to_request();
+    let patient: Value = test::call_and_read_body_json(&app, req).await;
+    let docs_uri = format!("/patients/{}/documents", patient["id"].as_str().unwrap());
+    let upload = |bearer: &(&'static str, String), body: Vec<u8>| {
+        test::TestRequest::post()
+            .uri(&docs_uri)
+            .insert_header(bearer.clone())
+            .insert_header((
+                "Content-Type",
+                format!("multipart/form-data; boundary={BOUNDARY}"),
+            ))
+            .set_payload(body)
+            .to_request()
+    };
+
+    // ==========================================
+    // ✅ 1. Зураг хуулах → metadata, sha256, sniffed MIME
+    // ==========================================
+    let content = png(40 * 1024);
+    let resp = test::call_service(
+        &app,
+        upload(doctor, multipart("image", "../../x-ray.png", &content)),
+    )
+    .await;
+    assert_eq!(resp.status(), 201);
+    let doc: Value = test::read_body_json(resp).await;
+    assert_eq!(doc["content_type"], "image/png");
+    assert_eq!(doc["file_name"], "x-ray.png");
+    assert_eq!(doc["size_bytes"], content.len());
+    assert_eq!(doc["sha256"].as_str().unwrap().len(), 64);
+    assert!(doc.get("storage_key").is_none());
+    let doc_uri = format!("{docs_uri}/{}", doc["id"].as_str().unwrap());
+
+    // ==========================================
+    // ✅ 2. Татах → ижил байт
+    // ==========================================
+    let req = test::TestRequest::get()
+        .uri(&doc_uri)
+        .insert_header(doctor.clone())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers().get("content-type").unwrap(), "image/png");
+    assert_eq!(
+        resp.headers().get("etag").unwrap().to_str().unwrap(),
+        format!("\"{}\"", doc["sha256"].as_str().unwrap())
+    );
+    let bytes = test::read_body(resp).await;
+    assert_eq!(bytes.as_ref(), content.as_slice());
+
+    // ==========================================
+    // ✅ 3. Хязгаарлалт: MIME, хэмжээ, өөр байгууллага
+    // ==========================================
+    let resp = test::call_service(
+        &app,
+        upload(doctor, multipart("other", "notes.png", b"just some text")),
+    )
+    .await;
+    assert_eq!(resp.status(), 415);
+
+    let resp = test::call_service(
+        &app,
+        upload(doctor, multipart("image", "huge.png", &png(100 * 1024))),
+    )
+    .await;
+    assert_eq!(resp.status(), 413);
+
+    let resp = test::call_service(
+        &app,
+        upload(outsider, multipart("image", "x.png", &content)),
+    )
+    .await;
+    assert_eq!(resp.status(), 403);
+    let req = test::TestRequest::get()
+        .uri(&doc_uri)
+        .insert_header(outsider.clone())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 403);
+
+    // ==========================================
+    // ✅ 4. Устгах
+    // ==========================================
+    let req = test::TestRequest::delete()
+        .uri(&doc_uri)
+        .insert_header(doctor.clone())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 200);
+    let req = test::TestRequest::get()
+        .uri(&docs_uri)
+        .insert_header(doctor.clone())
+        .to_request();
+    let rows: Vec<Value> = test::call_and_read_body_json(&app, req).await;
+    assert!(rows.is_empty());
+}
+
+fn count_files(dir: &std::path::Path) -> usize {
+    std::fs::read_dir(dir).map_or(0, |entries| {
+        entries
+            .map(|e| e.unwrap().path())
+            .map(|p| if p.is_dir() { count_files(&p) } else { 1 })
+            .sum()
+    })
+}
+
+#[actix_web::test]
+async fn test_rejected_upload_leaves_no_blob_behind() {
+    let root = std::env::temp_dir().join(format!("documents-{}", Uuid::new_v4()));
+    let mut state = common::test_state().await;
+    let db = state.db.clone();
+    state.storage =
+        storage::Storage::new(&storage::StorageConfig::Local { root: root.clone() }).unwrap();
+    let app = test::init_service(create_app(state)).await;
+    let org_id = 1000 + (Uuid::new_v4().as_u128() % 100_000) as i32;
+    let body = common::sign_up(
+        &app,
+        &db,
+        json!({"reg_no": format!("DOC-{}", Uuid::new_v4()), "org_id": org_id}),
+    )
+    .await;
+    let bearer = (
+        "Authorization",
+        format!("Bearer {}", body["tokens"]["access"].as_str().unwrap()),
+    );
+    let req = test::TestRequest::post()
+        .uri("/patients")
+        .insert_header(bearer.clone())
+        .set_json(json!({"first_name": "Bat", "last_name": "Erdene"}))
+        .to_request();
+    let patient: Value = test::call_and_read_body_json(&app, req).await;
+    let docs_uri = format!("/patients/{}/documents", patient["id"].as_str().unwrap());
+
+    // The file is stored before the parts after it are read.
+    let file_part = |name: &str| {
+        let mut part = format!(
+            "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{name}\"\r\n\
+             Content-Type: application/octet-stream\r\n\r\n"
+        )
+        .into_bytes();
+        part.extend_from_slice(&png(1024));
+        part.extend_from_slice(b"\r\n");
+        part
+    };
+    let kind_part = |kind: &str| {
+        format!("--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"kind\"\r\n\r\n{kind}\r\n")
+            .into_bytes()
+    };
+    let end = format!("--{BOUNDARY}--\r\n").into_bytes();
+
+    for body in [
+        // ✅ 1. Файлын дараа буруу төрөл
+        [file_part("a.png"), kind_part("bogus"), end.clone()].concat(),
+        // ✅ 2. Файлын дараа хэт урт төрөл
+        [file_part("a.png"), kind_part(&"x".repeat(64)), end.clone()].concat(),
+        // ✅ 3. Хоёр дахь файл
+        [file_part("a.png"), file_part("b.png"), end.clone()].concat(),
+    ] {
+        let req = test::TestRequest::post()
+            .uri(&docs_uri)
+            .insert_header(bearer.clone())
+            .insert_header((
+                "Content-Type",
+                format!("multipart/form-data; boundary={BOUNDARY}"),
+            ))
+            .set_payload(body)
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 400);
+        assert_eq!(count_files(&root), 0, "blob left behind");
+    }
+    let _ = std::fs::remove_dir_all(&root);
+}
@@ -0,0 +1,122 @@
+use actix_web::test;
+use api::create_app;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+mod common;
+
+#[actix_web::test]
+async fn test_break_the_glass_notifies_privacy_officer() {
+    let state = common::test_state().await;
+    let db = state.db.clone();
+    let officer_roll: i32 =
+        sqlx::query_scalar("SELECT roll_id FROM doctor_rolls WHERE roll_name = 'privacy_officer'")
+            .fetch_one(&state.db.0)
+            .await
+            .unwrap();
+    let app = test::init_service(create_app(state)).await;
+    let target_org = 1000 + (Uuid::new_v4().as_u128() % 100_000) as i32;
+
+    let mut bearers = Vec::new();
+    for (prefix, org_id, roll) in [
+        ("PO", target_org, officer_roll),
+        ("ER", target_org + 1, 3),
+        ("PO", target_org + 2, officer_roll),
+    ] {
+        let body = common::sign_up(
+            &app,
+            &db,
+            json!({
+                "reg_no": format!("{prefix}-{}", Uuid::new_v4()),
+                "first_name": "Tuya",
+                "last_name": "Gan",
+                "org_id": org_id,
+                "doctor_roll": roll,
+                "password": "supersecret"
+            }),
+        )
+        .await;
+        bearers.push((
+            "Authorization",
+            format!("Bearer {}", body["tokens"]["access"].as_str().unwrap()),
+        ));
+    }
+    let (officer, er_doctor, other_officer) = (&bearers[0], &bearers[1], &bearers[2]);
+
+    // ==========================================
+    // ✅ 1. Justification is mandatory
+    // ==========================================
+    let req = test::TestRequest::post()
+        .uri("/emergency-access")
+        .insert_header(er_doctor.clone())
+        .set_json(json!({"org_id": target_org, "justification": "urgent"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+
+    // ==========================================
+    // ✅ 2. Time-boxed grant + privacy officer notified
+    // ==========================================
+    let req = test::TestRequest::post()
+        .uri("/emergency-access")
+        .insert_header(er_doctor.clone())
+        .set_json(json!({
+            "org_id": target_org,
+            "justification": "Unconscious patient transferred, need allergy history",
+            "duration_minutes": 30
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
+    let grant: Value = test::read_body_json(resp).await;
+    let grant_id = grant["id"].as_str().unwrap().to_string();
+
+    let req = test::TestRequest::get()
+        .uri("/notifications?unread=true")
+        .insert_header(officer.clone())
+        .to_request();
+    let notes: Value = test::call_and_read_body_json(&app, req).await;
+    let note = notes
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|n| n["payload"]["grant_id"] == grant_id.as_str())
+        .expect("privacy officer was not notified");
+    assert_eq!(note["kind"], "break_glass");
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/notifications/{}/read", note["id"]))
+        .insert_header(officer.clone())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+
+    let req = test::TestRequest::get()
+        .uri("/emergency-access")
+        .insert_header(officer.clone())
+        .to_request();
+    let grants: Value = test::call_and_read_body_json(&app, req).await;
+    assert!(
+        grants
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|g| g["id"] == grant_id.as_str())
+    );
+
+    // ==========================================
+    // ✅ 3. Another org's reviewer cannot see or end it; the grantee can
+    // ==========================================
+    let req = test::TestRequest::delete()
+        .uri(&format!("/emergency-access/{grant_id}"))
+        .insert_header(other_officer.clone())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 404);
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/emergency-access/{grant_id}"))
+        .insert_header(er_doctor.clone())
+        .to_request();
+    let revoked: Value = test::call_and_read_body_json(&app, req).await;
+    assert!(revoked["revoked_at"].is_string());
+}
@@ -0,0 +1,62 @@
+use actix_web::test;
+use api::create_app;
+use serde_json::Value;
+
+mod common;
+
+#[actix_web::test]
+async fn test_health_and_readiness() {
+    let state = common::test_state().await;
+    let pool = state.db.0.clone();
+    let app = test::init_service(create_app(state)).await;
+
+    // ==========================================
+    // ✅ 1. healthz нэвтрэлтгүйгээр 200
+    // ==========================================
+    let req = test::TestRequest::get().uri("/healthz").to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["status"], "ok");
+
+    // ==========================================
+    // ✅ 2. readyz: DB, migration, түлхүүр бүгд хэвийн
+    // ==========================================
+    let req = test::TestRequest::get().uri("/readyz").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["status"], "ready");
+    for check in ["database", "migrations", "keys"] {
+        assert_eq!(body["checks"][check]["status"], "ok", "{check}");
+    }
+    assert_eq!(body["checks"]["keys"]["key_version"], 1);
+
+    // ==========================================
+    // ✅ 3. DB хаагдсан бол 503, healthz хэвээр 200
+    // ==========================================
+    pool.close().await;
+    let req = test::TestRequest::get().uri("/readyz").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 503);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["status"], "unavailable");
+    assert_eq!(body["checks"]["database"]["status"], "error");
+    assert_eq!(body["checks"]["migrations"]["status"], "unknown");
+    assert_eq!(body["checks"]["keys"]["status"], "ok");
+
+    let req = test::TestRequest::get().uri("/healthz").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 200);
+}
+
+#[actix_web::test]
+async fn test_startup_gives_up_on_unreachable_database() {
+    // ==========================================
+    // ✅ 4. Холбогдохгүй бол panic биш алдаа буцаана
+    // ==========================================
+    let started = std::time::Instant::now();
+    let result = db::connect_with_retry("postgres://nobody@127.0.0.1:1/none", 1, 2).await;
+    assert!(result.is_err());
+    // one 0.5 s backoff, each attempt capped at 5 s
+    let elapsed = started.elapsed();
+    assert!(elapsed >= std::time::Duration::from_millis(500));
+    assert!(elapsed < std::time::Duration::from_secs(12));
+}
@@ -0,0 +1,175 @@
+use actix_web::test;
+use api::create_app;
+use auth::hash_password;
+use db::{NewDoctor, insert_doctor_user};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+mod common;
+
+#[actix_web::test]
+async fn test_invitation_only_registration() {
+    let mut state = common::test_state().await;
+    state.open_registration = None;
+
+    // ⚙️ Admin-ийг шууд DB-д үүсгэнэ (нээлттэй бүртгэл хаалттай)
+    let admin_reg_no = format!("ADM-{}", Uuid::new_v4());
+    let hash = hash_password("adminsecret").unwrap();
+    insert_doctor_user(
+        &state.db,
+        &NewDoctor {
+            reg_no: &admin_reg_no,
+            first_name: "Admin",
+            last_name: "Root",
+            rank_name: None,
+            org_name: Some("UB Hospital"),
+            org_id: 1,
+            position: None,
+            birth_date: None,
+            gender: None,
+            doctor_roll: Some(1),
+            department: None,
+            password_hash: &hash,
+        },
+    )
+    .await
+    .unwrap();
+
+    let db = state.db.clone();
+    let app = test::init_service(create_app(state)).await;
+
+    let req = test::TestRequest::post()
+        .uri("/auth/login")
+        .set_json(json!({"reg_no": admin_reg_no, "password": "adminsecret"}))
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    let admin_bearer = (
+        "Authorization",
+        format!("Bearer {}", body["tokens"]["access"].as_str().unwrap()),
+    );
+
+    // ==========================================
+    // ✅ 1. Open registration is refused
+    // ==========================================
+    let reg_no = format!("DOC-{}", Uuid::new_v4());
+    let mut payload = json!({
+        "reg_no": reg_no,
+        "first_name": "Bold",
+        "last_name": "Bat",
+        "org_id": 7,
+        "doctor_roll": 1,
+        "password": "supersecret"
+    });
+    let req = test::TestRequest::post()
+        .uri("/auth/register")
+        .set_json(&payload)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 403);
+
+    // ==========================================
+    // ✅ 2. Admin issues invitation, doctor redeems it
+    // ==========================================
+    // another hospital is off limits
+    let req = test::TestRequest::post()
+        .uri("/invitations")
+        .insert_header(admin_bearer.clone())
+        .set_json(json!({"org_id": 2, "doctor_roll": 1}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 403);
+
+    let req = test::TestRequest::post()
+        .uri("/invitations")
+        .insert_header(admin_bearer.clone())
+        .set_json(
+            json!({"org_id": 1, "org_name": "UB Hospital", "doctor_roll": 3, "department": "ER"}),
+        )
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
+    let body: Value = test::read_body_json(resp).await;
+    let code = body["code"].as_str().unwrap().to_string();
+    assert!(body["invitation"].get("code_hash").is_none());
+
+    payload["invitation_code"] = json!(code);
+    let req = test::TestRequest::post()
+        .uri("/auth/register")
+        .set_json(&payload)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
+    let body: Value = test::read_body_json(resp).await;
+    let bearer = (
+        "Authorization",
+        format!("Bearer {}", body["tokens"]["access"].as_str().unwrap()),
+    );
+
+    // org, role and department come from the invitation, not the payload
+    let req = test::TestRequest::get()
+        .uri("/me")
+        .insert_header(bearer.clone())
+        .to_request();
+    let me: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(me["org_id"], 1);
+    assert_eq!(me["doctor_roll"], 3);
+    assert_eq!(me["department"], "ER");
+
+    let req = test::TestRequest::get()
+        .uri("/invitations")
+        .insert_header(bearer)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 403, "invited junior is not an admin");
+
+    // ==========================================
+    // ✅ 3. Code is single-use
+    // ==========================================
+    payload["reg_no"] = json!(format!("DOC-{}", Uuid::new_v4()));
+    let req = test::TestRequest::post()
+        .uri("/auth/register")
+        .set_json(&payload)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 403);
+
+    // ==========================================
+    // ✅ 4. Other orgs' admins cannot see or revoke it
+    // ==========================================
+    let req = test::TestRequest::post()
+        .uri("/invitations")
+        .insert_header(admin_bearer.clone())
+        .set_json(json!({"doctor_roll": 3}))
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    let id = body["invitation"]["id"].as_str().unwrap().to_string();
+
+    let other = common::sign_up(
+        &app,
+        &db,
+        json!({"reg_no": format!("ADM-{}", Uuid::new_v4()), "org_id": 2, "doctor_roll": 1}),
+    )
+    .await;
+    let other_bearer = (
+        "Authorization",
+        format!("Bearer {}", other["tokens"]["access"].as_str().unwrap()),
+    );
+    let req = test::TestRequest::get()
+        .uri("/invitations")
+        .insert_header(other_bearer.clone())
+        .to_request();
+    let rows: Value = test::call_and_read_body_json(&app, req).await;
+    assert!(rows.as_array().unwrap().iter().all(|r| r["org_id"] == 2));
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/invitations/{id}"))
+        .insert_header(other_bearer)
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 404);
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/invitations/{id}"))
+        .insert_header(admin_bearer)
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 200);
+}
@@ -0,0 +1,128 @@
+use actix_web::test;
+use api::create_app;
+use api::jobs::Jobs;
+use chrono::{Duration as Days, Utc};
+use serde_json::json;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+use uuid::Uuid;
+
+mod common;
+
+#[actix_web::test]
+async fn test_cleanup_jobs_remove_only_stale_rows() {
+    let state = common::test_state().await;
+    let db = state.db.clone();
+    let app = test::init_service(create_app(state)).await;
+    let body = common::sign_up(
+        &app,
+        &db,
+        json!({
+            "reg_no": format!("JB-{}", Uuid::new_v4()),
+            "first_name": "Anu",
+            "last_name": "Bold",
+            "org_id": 1000 + (Uuid::new_v4().as_u128() % 100_000) as i32,
+            "password": "supersecret"
+        }),
+    )
+    .await;
+    let doctor_id: Uuid = body["doctor"]["id"].as_str().unwrap().parse().unwrap();
+
+    // ==========================================
+    // ✅ 1. Хугацаа нь дууссан refresh токеныг устгана
+    // ==========================================
+    let expired = Uuid::new_v4().to_string();
+    let live = Uuid::new_v4().to_string();
+    db::insert_refresh(
+        &db,
+        doctor_id,
+        &expired,
+        "sha256:x",
+        Utc::now() - Days::hours(1),
+    )
+    .await
+    .unwrap();
+    db::insert_refresh(
+        &db,
+        doctor_id,
+        &live,
+        "sha256:y",
+        Utc::now() + Days::hours(1),
+    )
+    .await
+    .unwrap();
+    db::revoke_refresh(&db, &live).await.unwrap();
+    assert!(db::purge_expired_refresh_tokens(&db).await.unwrap() >= 1);
+    assert!(
+        db::get_refresh_by_jti(&db, &expired)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(db::get_refresh_by_jti(&db, &live).await.unwrap().is_some());
+
+    // ==========================================
+    // ✅ 2. Уншсан хуучин мэдэгдлийг устгана
+    // ==========================================
+    for days_ago in [100, 10] {
+        sqlx::query(
+            "INSERT INTO notifications (recipient_id, kind, payload, read_at)
+             VALUES ($1, 'test', '{}', NOW() - make_interval(days => $2))",
+        )
+        .bind(doctor_id)
+        .bind(days_ago)
+        .execute(&db.0)
+        .await
+        .unwrap();
+    }
+    db::purge_read_notifications(&db, 90).await.unwrap();
+    let left = db::list_notifications(&db, doctor_id, false).await.unwrap();
+    assert_eq!(left.len(), 1);
+}
+
+#[actix_web::test]
+async fn test_jobs_stop_between_runs_on_shutdown() {
+    // ==========================================
+    // ✅ 3. Шууд ажиллаж, цуцлахад эхэлсэн ажлаа дуусгана
+    // ==========================================
+    let runs = Rc::new(Cell::new(0));
+    let finished = Rc::new(Cell::new(0));
+    let mut jobs = Jobs::new();
+    let (r, f) = (runs.clone(), finished.clone());
+    jobs.every("slow", Duration::from_secs(3600), move || {
+        let (r, f) = (r.clone(), f.clone());
+        async move {
+            r.set(r.get() + 1);
+            actix_web::rt::time::sleep(Duration::from_millis(200)).await;
+            f.set(f.get() + 1);
+            Ok(0)
+        }
+    });
+    actix_web::rt::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(runs.get(), 1, "first run starts immediately");
+
+    jobs.shutdown(Duration::from_secs(5)).await;
+    assert_eq!(finished.get(), 1, "the running job completes");
+    assert_eq!(runs.get(), 1, "no run starts after cancellation");
+
+    // ==========================================
+    // ✅ 4. Хугацаандаа зогсохгүй бол таслана
+    // ==========================================
+    let finished = Rc::new(Cell::new(false));
+    let mut jobs = Jobs::new();
+    let f = finished.clone();
+    jobs.every("stuck", Duration::from_secs(3600), move || {
+        let f = f.clone();
+        async move {
+            actix_web::rt::time::sleep(Duration::from_secs(60)).await;
+            f.set(true);
+            Ok(0)
+        }
+    });
+    actix_web::rt::time::sleep(Duration::from_millis(20)).await;
+    let started = std::time::Instant::now();
+    jobs.shutdown(Duration::from_millis(100)).await;
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert!(!finished.get());
+}
@@ -0,0 +1,197 @@
+use actix_cors::Cors;
+use actix_governor::GovernorConfigBuilder;
+use actix_web::test;
+use api::metrics::CountingPeerIp;
+use api::{Edge, create_app, create_app_with};
+use serde_json::json;
+use uuid::Uuid;
+
+mod common;
+
+/// Value of the first sample whose line starts with `series`.
+fn sample(text: &str, series: &str) -> f64 {
+    text.lines()
+        .find_map(|l| l.strip_prefix(series)?.trim().parse().ok())
+        .unwrap_or(0.0)
+}
+
+#[actix_web::test]
+async fn test_metrics_are_exposed() {
+    let app = test::init_service(create_app(common::test_state().await)).await;
+    let scrape = || async {
+        let req = test::TestRequest::get()
+            .uri("/metrics")
+            .insert_header(("Authorization", format!("Bearer {}", common::METRICS_TOKEN)))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 200);
+        assert!(
+            resp.headers()
+                .get("content-type")
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .starts_with("text/plain")
+        );
+        String::from_utf8(test::read_body(resp).await.to_vec()).unwrap()
+    };
+    let before = scrape().await;
+
+    // ==========================================
+    // ✅ 1. Нэвтрэлтийн амжилт, алдааг тоолно
+    // ==========================================
+    let reg_no = format!("MT-{}", Uuid::new_v4());
+    let req = test::TestRequest::post()
+        .uri("/auth/register")
+        .set_json(json!({
+            "reg_no": reg_no,
+            "first_name": "Anu",
+            "last_name": "Bold",
+            "password": "supersecret"
+        }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 201);
+    for (password, status) in [("supersecret", 200), ("wrong-password", 401)] {
+        let req = test::TestRequest::post()
+            .uri("/auth/login")
+            .set_json(json!({"reg_no": reg_no, "password": password}))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), status);
+    }
+    let req = test::TestRequest::post().uri("/auth/refresh").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 401);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/items/{}", Uuid::new_v4()))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 401);
+
+    let after = scrape().await;
+    for (series, delta) in [
+        (r#"auth_logins_total{outcome="success"}"#, 1.0),
+        (r#"auth_logins_total{outcome="bad_password"}"#, 1.0),
+        (r#"auth_refresh_total{outcome="invalid"}"#, 1.0),
+        (
+            r#"http_requests_total{method="POST",route="/auth/login",status="200"}"#,
+            1.0,
+        ),
+    ] {
+        assert_eq!(
+            sample(&after, series) - sample(&before, series),
+            delta,
+            "{series}"
+        );
+    }
+
+    // ==========================================
+    // ✅ 2. Route нь загвараар, DB pool-ийн хэмжигдэхүүн
+    // ==========================================
+    assert!(after.contains(r#"route="/items/{id}",status="401""#));
+    assert!(!after.contains("/items/0") && !after.contains(&reg_no));
+    assert!(after.contains("http_request_duration_seconds_bucket"));
+    assert_eq!(sample(&after, "db_pool_max_connections"), 5.0);
+    assert!(after.contains(r#"db_pool_connections{state="idle"}"#));
+}
+
+#[actix_web::test]
+async fn test_metrics_require_the_scrape_token() {
+    let app = test::init_service(create_app(common::test_state().await)).await;
+
+    // ==========================================
+    // ✅ 2. Токенгүй, буруу токентой бол татгалзана
+    // ==========================================
+    for auth in [
+        None,
+        Some("Bearer wrong-metrics-token"),
+        Some("Basic dGVzdA=="),
+    ] {
+        let mut req = test::TestRequest::get().uri("/metrics");
+        if let Some(auth) = auth {
+            req = req.insert_header(("Authorization", auth));
+        }
+        let resp = test::call_service(&app, req.to_request()).await;
+        assert_eq!(resp.status(), 401, "{auth:?}");
+    }
+
+    let mut state = common::test_state().await;
+    state.metrics_token = None;
+    let app = test::init_service(create_app(state)).await;
+    let req = test::TestRequest::get()
+        .uri("/metrics")
+        .insert_header(("Authorization", format!("Bearer {}", common::METRICS_TOKEN)))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_rate_limit_rejections_are_counted() {
+    // ==========================================
+    // ✅ 3. Хязгаар хэтэрсэн хүсэлтийг тоолно
+    // ==========================================
+    let conf = GovernorConfigBuilder::default()
+        .key_extractor(CountingPeerIp)
+        .seconds_per_request(60)
+        .burst_size(1)
+        .finish()
+        .unwrap();
+    let edge = Edge {
+        cors: Cors::default(),
+        governor: conf,
+    };
+    let app = test::init_service(create_app_with(common::test_state().await, Some(edge))).await;
+    let peer = "10.1.2.3:4000".parse().unwrap();
+    let before = api::metrics::metrics().rate_limited.get();
+    for status in [404, 429, 429] {
+        let req = test::TestRequest::get()
+            .uri("/no-such-route")
+            .peer_addr(peer)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), status);
+        // inside ProblemDetails and RequestTrace like any other rejection
+        assert_eq!(
+            resp.headers().get("Content-Type").unwrap(),
+            "application/problem+json"
+        );
+        assert!(resp.headers().contains_key("X-Request-Id"));
+        if status == 429 {
+            assert!(resp.headers().contains_key("retry-after"));
+        }
+    }
+    assert_eq!(api::metrics::metrics().rate_limited.get() - before, 2);
+}
+
+#[actix_web::test]
+async fn test_health_probes_are_not_rate_limited() {
+    // ==========================================
+    // ✅ 4. Health шалгалтыг хязгаарлахгүй
+    // ==========================================
+    let conf = GovernorConfigBuilder::default()
+        .key_extractor(CountingPeerIp)
+        .seconds_per_request(60)
+        .burst_size(1)
+        .finish()
+        .unwrap();
+    let edge = Edge {
+        cors: Cors::default(),
+        governor: conf,
+    };
+    let app = test::init_service(create_app_with(common::test_state().await, Some(edge))).await;
+    let peer = "10.4.5.6:4000".parse().unwrap();
+    for uri in ["/healthz", "/readyz", "/healthz", "/readyz", "/healthz"] {
+        let req = test::TestRequest::get()
+            .uri(uri)
+            .peer_addr(peer)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 200, "{uri}");
+    }
+    // the same peer is still limited elsewhere
+    for status in [404, 429] {
+        let req = test::TestRequest::get()
+            .uri("/no-such-route")
+            .peer_addr(peer)
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), status);
+    }
+}
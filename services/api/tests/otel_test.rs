@@ -0,0 +1,100 @@
+use actix_web::test;
+use api::create_app;
+use api::telemetry::{self, LogFormat};
+use serde_json::json;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+
+mod common;
+
+/// (path, body) of every export request.
+type Received = Arc<Mutex<Vec<(String, Vec<u8>)>>>;
+
+/// Collector stand-in: accepts OTLP/HTTP posts and keeps what it was sent.
+fn collector() -> (String, Received) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let sink = received.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = BufReader::new(stream.unwrap());
+            loop {
+                let mut request_line = String::new();
+                if stream.read_line(&mut request_line).unwrap_or(0) == 0 {
+                    break;
+                }
+                let path = request_line
+                    .split_whitespace()
+                    .nth(1)
+                    .unwrap_or_default()
+                    .to_owned();
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    stream.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':')
+                        && name.eq_ignore_ascii_case("content-length")
+                    {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0; length];
+                stream.read_exact(&mut body).unwrap();
+                sink.lock().unwrap().push((path, body));
+                stream
+                    .get_mut()
+                    .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                    .unwrap();
+            }
+        }
+    });
+    (endpoint, received)
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|w| w == needle)
+}
+
+fn unhex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+#[actix_web::test]
+async fn test_request_and_query_spans_join_the_callers_trace() {
+    let (endpoint, received) = collector();
+    let guard = telemetry::init(LogFormat::Text, Some(&endpoint));
+    let app = test::init_service(create_app(common::test_state().await)).await;
+
+    // ==========================================
+    // ✅ 1. traceparent-ийг үргэлжлүүлж, DB span-уудтай хамт илгээнэ
+    // ==========================================
+    let trace_id = "4bf92f3577b34da6a3ce929d0e0e4736";
+    let parent_id = "00f067aa0ba902b7";
+    let req = test::TestRequest::post()
+        .uri("/auth/login")
+        .insert_header(("traceparent", format!("00-{trace_id}-{parent_id}-01")))
+        .set_json(json!({"reg_no": "OT-nobody", "password": "supersecret"}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 401);
+
+    // flushes the batch
+    drop(guard);
+
+    let received = received.lock().unwrap();
+    assert!(!received.is_empty(), "nothing exported");
+    assert!(received.iter().all(|(path, _)| path == "/v1/traces"));
+    let body: Vec<u8> = received.iter().flat_map(|(_, b)| b.clone()).collect();
+    assert!(contains(&body, &unhex(trace_id)), "trace id not continued");
+    assert!(contains(&body, &unhex(parent_id)), "remote parent missing");
+    for name in ["http.request", "login", "find_doctor_by_reg_no"] {
+        assert!(contains(&body, name.as_bytes()), "{name} span missing");
+    }
+}
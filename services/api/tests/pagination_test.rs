@@ -0,0 +1,100 @@
+use actix_web::test;
+use api::create_app;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+mod common;
+
+#[actix_web::test]
+async fn test_item_cursor_pagination_sort_and_search() {
+    let state = common::test_state().await;
+    let db = state.db.clone();
+    let app = test::init_service(create_app(state)).await;
+
+    // fresh org so the listing only holds this test's rows
+    let body = common::sign_up(
+        &app,
+        &db,
+        json!({
+            "reg_no": format!("PG-{}", Uuid::new_v4()),
+            "first_name": "Anu",
+            "last_name": "Sukh",
+            "org_id": 1000 + (Uuid::new_v4().as_u128() % 100_000) as i32,
+            "password": "supersecret"
+        }),
+    )
+    .await;
+    let bearer = (
+        "Authorization",
+        format!("Bearer {}", body["tokens"]["access"].as_str().unwrap()),
+    );
+    for title in ["echo", "alpha", "delta_x", "charlie", "bravo"] {
+        let req = test::TestRequest::post()
+            .uri("/items")
+            .insert_header(bearer.clone())
+            .set_json(json!({"title": title, "description": null}))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 201);
+    }
+    let get = |uri: String| {
+        test::TestRequest::get()
+            .uri(&uri)
+            .insert_header(bearer.clone())
+            .to_request()
+    };
+
+    // ==========================================
+    // ✅ 1. Cursor-оор хуудаслах, давхардалгүй
+    // ==========================================
+    let mut titles = Vec::new();
+    let mut cursor: Option<String> = None;
+    let mut pages = 0;
+    loop {
+        let mut uri = "/items?sort=title&limit=2".to_string();
+        if let Some(c) = &cursor {
+            uri.push_str(&format!("&cursor={c}"));
+        }
+        let page: Value = test::call_and_read_body_json(&app, get(uri)).await;
+        pages += 1;
+        for row in page["items"].as_array().unwrap() {
+            titles.push(row["title"].as_str().unwrap().to_string());
+        }
+        match page["next_cursor"].as_str() {
+            Some(c) => cursor = Some(c.to_string()),
+            None => break,
+        }
+    }
+    assert_eq!(pages, 3);
+    assert_eq!(titles, ["alpha", "bravo", "charlie", "delta_x", "echo"]);
+
+    // descending
+    let page: Value =
+        test::call_and_read_body_json(&app, get("/items?sort=-title&limit=1".into())).await;
+    assert_eq!(page["items"][0]["title"], "echo");
+
+    // ==========================================
+    // ✅ 2. Хайлт: `_` нь wildcard биш
+    // ==========================================
+    let page: Value = test::call_and_read_body_json(&app, get("/items?q=A_X".into())).await;
+    let rows = page["items"].as_array().unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0]["title"], "delta_x");
+    assert!(page["next_cursor"].is_null());
+
+    // ==========================================
+    // ✅ 3. Буруу параметр → 400
+    // ==========================================
+    let resp = test::call_service(&app, get("/items?sort=description".into())).await;
+    assert_eq!(resp.status(), 400);
+    let resp = test::call_service(&app, get("/items?limit=0".into())).await;
+    assert_eq!(resp.status(), 400);
+    let page: Value =
+        test::call_and_read_body_json(&app, get("/items?sort=title&limit=1".into())).await;
+    let cursor = page["next_cursor"].as_str().unwrap();
+    let resp = test::call_service(
+        &app,
+        get(format!("/items?sort=-created_at&cursor={cursor}")),
+    )
+    .await;
+    assert_eq!(resp.status(), 400);
+}
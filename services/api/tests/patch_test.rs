@@ -0,0 +1,113 @@
+use actix_web::test;
+use api::create_app;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+mod common;
+
+#[actix_web::test]
+async fn test_merge_patch_items_and_profile() {
+    let state = common::test_state().await;
+    let db = state.db.clone();
+    let app = test::init_service(create_app(state)).await;
+    let org_id = 1000 + (Uuid::new_v4().as_u128() % 100_000) as i32;
+    let body = common::sign_up(
+        &app,
+        &db,
+        json!({
+            "reg_no": format!("MP-{}", Uuid::new_v4()),
+            "first_name": "Tuya",
+            "last_name": "Gan",
+            "org_id": org_id,
+            "password": "supersecret"
+        }),
+    )
+    .await;
+    let bearer = (
+        "Authorization",
+        format!("Bearer {}", body["tokens"]["access"].as_str().unwrap()),
+    );
+
+    let req = test::TestRequest::post()
+        .uri("/items")
+        .insert_header(bearer.clone())
+        .set_json(json!({"title": "Allergy list", "description": "penicillin"}))
+        .to_request();
+    let item: Value = test::call_and_read_body_json(&app, req).await;
+    let uri = format!("/items/{}", item["id"].as_str().unwrap());
+    let patch = |version: i64, body: Value| {
+        test::TestRequest::patch()
+            .uri(&uri)
+            .insert_header(bearer.clone())
+            .insert_header(("If-Match", format!("\"{version}\"")))
+            .insert_header(("Content-Type", "application/merge-patch+json"))
+            .set_payload(body.to_string())
+            .to_request()
+    };
+
+    // ==========================================
+    // ✅ 1. Зөвхөн гарчиг солиход тайлбар хэвээр
+    // ==========================================
+    let resp = test::call_service(&app, patch(1, json!({"title": "Allergies"}))).await;
+    assert_eq!(resp.status(), 200);
+    let row: Value = test::read_body_json(resp).await;
+    assert_eq!(row["title"], "Allergies");
+    assert_eq!(row["description"], "penicillin");
+    assert_eq!(row["version"], 2);
+
+    // ==========================================
+    // ✅ 2. null нь тайлбарыг арилгана, гарчиг хэвээр
+    // ==========================================
+    let resp = test::call_service(&app, patch(2, json!({"description": null}))).await;
+    let row: Value = test::read_body_json(resp).await;
+    assert_eq!(row["title"], "Allergies");
+    assert!(row["description"].is_null());
+
+    // empty patch is a no-op and keeps the version
+    let resp = test::call_service(&app, patch(3, json!({}))).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers().get("ETag").unwrap(), "\"3\"");
+
+    // ==========================================
+    // ✅ 3. Буруу хүсэлтүүд
+    // ==========================================
+    let resp = test::call_service(&app, patch(3, json!({"title": null}))).await;
+    assert_eq!(resp.status(), 400);
+    let resp = test::call_service(&app, patch(3, json!({"owner_id": Uuid::new_v4()}))).await;
+    assert_eq!(resp.status(), 400);
+    let resp = test::call_service(&app, patch(1, json!({"title": "stale"}))).await;
+    assert_eq!(resp.status(), 412);
+    let req = test::TestRequest::patch()
+        .uri(&uri)
+        .insert_header(bearer.clone())
+        .set_json(json!({"title": "untagged"}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 428);
+
+    // ==========================================
+    // ✅ 4. Профайл: null утас арилгана, нэр арилгах боломжгүй
+    // ==========================================
+    let req = test::TestRequest::patch()
+        .uri("/me")
+        .insert_header(bearer.clone())
+        .set_json(json!({"phone": "99001122", "position": "Nurse"}))
+        .to_request();
+    let me: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(me["phone"], "99001122");
+
+    let req = test::TestRequest::patch()
+        .uri("/me")
+        .insert_header(bearer.clone())
+        .set_json(json!({"phone": null}))
+        .to_request();
+    let me: Value = test::call_and_read_body_json(&app, req).await;
+    assert!(me["phone"].is_null());
+    assert_eq!(me["position"], "Nurse");
+
+    let req = test::TestRequest::patch()
+        .uri("/me")
+        .insert_header(bearer.clone())
+        .set_json(json!({"first_name": null}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 400);
+}
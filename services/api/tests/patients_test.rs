@@ -0,0 +1,288 @@
+use actix_web::test;
+use api::create_app;
+use db::KeyRing;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+mod common;
+
+#[actix_web::test]
+async fn test_cross_org_read_requires_data_sharing_consent() {
+    let state = common::test_state().await;
+    let db = state.db.clone();
+    let app = test::init_service(create_app(state)).await;
+    let home_org = 1000 + (Uuid::new_v4().as_u128() % 100_000) as i32;
+    let other_org = home_org + 1;
+
+    let mut bearers = Vec::new();
+    for (prefix, org_id) in [("HOME", home_org), ("OTHER", other_org)] {
+        let body = common::sign_up(
+            &app,
+            &db,
+            json!({
+                "reg_no": format!("{prefix}-{}", Uuid::new_v4()),
+                "first_name": "Saraa",
+                "last_name": "Bat",
+                "org_id": org_id,
+                "password": "supersecret"
+            }),
+        )
+        .await;
+        bearers.push((
+            "Authorization",
+            format!("Bearer {}", body["tokens"]["access"].as_str().unwrap()),
+        ));
+    }
+    let (home, other) = (&bearers[0], &bearers[1]);
+    let admin_roll: i32 =
+        sqlx::query_scalar("SELECT roll_id FROM doctor_rolls WHERE roll_name = 'admin'")
+            .fetch_one(&db.0)
+            .await
+            .unwrap();
+    let body = common::sign_up(
+        &app,
+        &db,
+        json!({
+            "reg_no": format!("ADM-{}", Uuid::new_v4()),
+            "org_id": other_org,
+            "doctor_roll": admin_roll
+        }),
+    )
+    .await;
+    let other_admin = (
+        "Authorization",
+        format!("Bearer {}", body["tokens"]["access"].as_str().unwrap()),
+    );
+
+    // ==========================================
+    // ✅ 1. Өвчтөн бүртгэх
+    // ==========================================
+    let req = test::TestRequest::post()
+        .uri("/patients")
+        .insert_header(home.clone())
+        .set_json(json!({"first_name": "Bold", "last_name": "Dorj"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
+    let patient: Value = test::read_body_json(resp).await;
+    let patient_uri = format!("/patients/{}", patient["id"].as_str().unwrap());
+
+    // ==========================================
+    // ✅ 2. Зөвшөөрөлгүй бол өөр байгууллага уншиж чадахгүй
+    // ==========================================
+    let req = test::TestRequest::get()
+        .uri(&patient_uri)
+        .insert_header(other.clone())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 403);
+
+    // Other org cannot record consent on the patient's behalf
+    let consent = json!({
+        "kind": "data_sharing",
+        "scope_org_id": other_org,
+        "form_version": "2024-01",
+        "signed_by": "Bold Dorj",
+        "signature": "data:image/png;base64,AAAA"
+    });
+    let req = test::TestRequest::post()
+        .uri(&format!("{patient_uri}/consents"))
+        .insert_header(other.clone())
+        .set_json(&consent)
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 403);
+
+    // Neither can that org's admin: owner-only actions ignore the role
+    let req = test::TestRequest::post()
+        .uri(&format!("{patient_uri}/consents"))
+        .insert_header(other_admin.clone())
+        .set_json(&consent)
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 403);
+    let req = test::TestRequest::delete()
+        .uri(&format!("{patient_uri}/documents/{}", Uuid::new_v4()))
+        .insert_header(other_admin.clone())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 403);
+
+    // scope_org_id only makes sense for data sharing
+    let req = test::TestRequest::post()
+        .uri(&format!("{patient_uri}/consents"))
+        .insert_header(home.clone())
+        .set_json(json!({
+            "kind": "research",
+            "scope_org_id": other_org,
+            "form_version": "2024-01",
+            "signed_by": "Bold Dorj",
+            "signature": "sig"
+        }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 400);
+
+    // ==========================================
+    // ✅ 3. Мэдээлэл хуваалцах зөвшөөрөл → уншиж болно
+    // ==========================================
+    let req = test::TestRequest::post()
+        .uri(&format!("{patient_uri}/consents"))
+        .insert_header(home.clone())
+        .set_json(&consent)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
+    let first: Value = test::read_body_json(resp).await;
+    assert_eq!(first["version"], 1);
+
+    let req = test::TestRequest::get()
+        .uri(&patient_uri)
+        .insert_header(other.clone())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 200);
+
+    // Re-signing creates a new version
+    let req = test::TestRequest::post()
+        .uri(&format!("{patient_uri}/consents"))
+        .insert_header(home.clone())
+        .set_json(&consent)
+        .to_request();
+    let latest: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(latest["version"], 2);
+
+    // ==========================================
+    // ✅ 4. Зөвшөөрөл цуцлагдвал дахин хаагдана
+    // ==========================================
+    let req = test::TestRequest::post()
+        .uri(&format!(
+            "{patient_uri}/consents/{}/withdraw",
+            latest["id"].as_str().unwrap()
+        ))
+        .insert_header(home.clone())
+        .set_json(json!({"reason": "patient request"}))
+        .to_request();
+    let withdrawn: Value = test::call_and_read_body_json(&app, req).await;
+    assert!(withdrawn["withdrawn_at"].is_string());
+
+    // Withdrawing the latest version is not undone by the older, still-open one
+    let req = test::TestRequest::get()
+        .uri(&patient_uri)
+        .insert_header(other.clone())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 403);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("{patient_uri}/consents"))
+        .insert_header(home.clone())
+        .to_request();
+    let history: Vec<Value> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(history.len(), 2);
+}
+
+#[actix_web::test]
+async fn test_sensitive_fields_encrypted_at_rest_and_rotatable() {
+    let state = common::test_state().await;
+    let db = state.db.clone();
+    let app = test::init_service(create_app(state)).await;
+    let org_id = 1000 + (Uuid::new_v4().as_u128() % 100_000) as i32;
+
+    let body = common::sign_up(
+        &app,
+        &db,
+        json!({
+            "reg_no": format!("ENC-{}", Uuid::new_v4()),
+            "first_name": "Saraa",
+            "last_name": "Bat",
+            "org_id": org_id,
+            "password": "supersecret"
+        }),
+    )
+    .await;
+    let bearer = (
+        "Authorization",
+        format!("Bearer {}", body["tokens"]["access"].as_str().unwrap()),
+    );
+    let national_id = format!("УБ{}", Uuid::new_v4().as_u128() % 100_000_000);
+
+    // ==========================================
+    // ✅ 1. Бүртгэхэд шифрлэгдэнэ, уншихад тайлагдана
+    // ==========================================
+    let req = test::TestRequest::post()
+        .uri("/patients")
+        .insert_header(bearer.clone())
+        .set_json(json!({
+            "first_name": "Bold",
+            "last_name": "Dorj",
+            "national_id": national_id,
+            "diagnoses": "I10 essential hypertension",
+            "notes": "allergic to penicillin"
+        }))
+        .to_request();
+    let patient: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(patient["national_id"], national_id.as_str());
+    assert_eq!(patient["diagnoses"], "I10 essential hypertension");
+    let id = Uuid::parse_str(patient["id"].as_str().unwrap()).unwrap();
+
+    let (plain, sealed, version): (Option<String>, Vec<u8>, i32) =
+        sqlx::query_as("SELECT national_id, notes_enc, dek_version FROM patients WHERE id = $1")
+            .bind(id)
+            .fetch_one(&db.0)
+            .await
+            .unwrap();
+    assert!(plain.is_none());
+    assert_eq!(version, 1);
+    assert!(!String::from_utf8_lossy(&sealed).contains("penicillin"));
+
+    let audit_diff: String = sqlx::query_scalar(
+        "SELECT diff::text FROM audit_log WHERE resource_id = $1 AND action = 'create'",
+    )
+    .bind(id.to_string())
+    .fetch_one(&db.0)
+    .await
+    .unwrap();
+    assert!(!audit_diff.contains(&national_id) && !audit_diff.contains("penicillin"));
+
+    // ==========================================
+    // ✅ 2. Blind index-ээр регистрийн дугаараар хайх
+    // ==========================================
+    let spaced: String = national_id
+        .chars()
+        .flat_map(char::to_lowercase)
+        .collect::<String>()
+        .replacen("уб", "уб ", 1);
+    let req = test::TestRequest::get()
+        .uri(&format!("/patients?national_id={}", urlencode(&spaced)))
+        .insert_header(bearer.clone())
+        .to_request();
+    let found: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(found["items"].as_array().unwrap().len(), 1);
+    assert_eq!(found["items"][0]["id"], patient["id"]);
+
+    let req = test::TestRequest::get()
+        .uri("/patients?limit=0")
+        .insert_header(bearer.clone())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 400);
+
+    // ==========================================
+    // ✅ 3. Мастер түлхүүр солих → rewrap
+    // ==========================================
+    let rotated = KeyRing::new(2, [(1, [7; 32]), (2, [8; 32])], [9; 32]).unwrap();
+    let report = db::reencrypt_patients(&db, &rotated, Some(org_id), 100, false)
+        .await
+        .unwrap();
+    assert_eq!((report.scanned, report.rewrapped), (1, 1));
+
+    let v2_only = KeyRing::new(2, [(2, [8; 32])], [9; 32]).unwrap();
+    let row = db::get_patient(&db.tenant(org_id), &v2_only, &Default::default(), id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(row.notes.as_deref(), Some("allergic to penicillin"));
+    assert_eq!(row.national_id.as_deref(), Some(national_id.as_str()));
+}
+
+fn urlencode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' => (b as char).to_string(),
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
@@ -0,0 +1,107 @@
+use actix_web::test;
+use api::create_app;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+mod common;
+
+#[actix_web::test]
+async fn test_errors_are_problem_json() {
+    let state = common::test_state().await;
+    let db = state.db.clone();
+    let admin_roll =
+        sqlx::query_scalar::<_, i32>("SELECT roll_id FROM doctor_rolls WHERE roll_name = 'admin'")
+            .fetch_one(&state.db.0)
+            .await
+            .unwrap();
+    let app = test::init_service(create_app(state)).await;
+    let org_id = 1000 + (Uuid::new_v4().as_u128() % 100_000) as i32;
+    let reg_no = format!("PJ-{}", Uuid::new_v4());
+    let register = json!({
+        "reg_no": reg_no,
+        "first_name": "Anu",
+        "last_name": "Bold",
+        "org_id": org_id,
+        "doctor_roll": admin_roll,
+        "password": "supersecret"
+    });
+    let body = common::sign_up(&app, &db, register.clone()).await;
+    let bearer = (
+        "Authorization",
+        format!("Bearer {}", body["tokens"]["access"].as_str().unwrap()),
+    );
+
+    // ==========================================
+    // ✅ 1. problem+json, request id буцаана
+    // ==========================================
+    let request_id = Uuid::new_v4().to_string();
+    let req = test::TestRequest::get()
+        .uri("/no-such-route")
+        .insert_header(("X-Request-Id", request_id.clone()))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+    assert_eq!(
+        resp.headers().get("content-type").unwrap(),
+        "application/problem+json"
+    );
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["type"], "about:blank");
+    assert_eq!(body["title"], "Not Found");
+    assert_eq!(body["status"], 404);
+    assert_eq!(body["request_id"], request_id.as_str());
+
+    // ==========================================
+    // ✅ 2. actix-ийн өөрийн алдаа (query, auth) ч мөн адил
+    // ==========================================
+    let req = test::TestRequest::get().uri("/items").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 401);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["status"], 401);
+
+    let req = test::TestRequest::get()
+        .uri("/items?limit=many")
+        .insert_header(bearer.clone())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+    let body: Value = test::read_body_json(resp).await;
+    assert!(body["detail"].is_string());
+
+    let req = test::TestRequest::post()
+        .uri("/auth/register")
+        .set_json(&register)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 409);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["detail"], "reg_no already exists");
+
+    // ==========================================
+    // ✅ 3. Unique зөрчил → 409, SQL текст задрахгүй
+    // ==========================================
+    let definition = json!({
+        "org_id": org_id,
+        "name": "Day",
+        "start_time": "08:00:00",
+        "duration_minutes": 720
+    });
+    let req = test::TestRequest::post()
+        .uri("/shift-definitions")
+        .insert_header(bearer.clone())
+        .set_json(&definition)
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 201);
+    let req = test::TestRequest::post()
+        .uri("/shift-definitions")
+        .insert_header(bearer.clone())
+        .set_json(&definition)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 409);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["type"], "/problems/duplicate");
+    let text = body.to_string();
+    assert!(!text.contains("shift_definitions") && !text.contains("constraint"));
+}
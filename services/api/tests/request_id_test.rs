@@ -0,0 +1,80 @@
+use actix_web::test;
+use api::create_app;
+use api::schemas::{LoginInput, RegisterInput};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+mod common;
+
+#[actix_web::test]
+async fn test_request_ids_are_generated_and_propagated() {
+    let app = test::init_service(create_app(common::test_state().await)).await;
+
+    // ==========================================
+    // ✅ 1. Ирээгүй бол шинээр үүсгэж, хариунд болон алдаанд буцаана
+    // ==========================================
+    let req = test::TestRequest::get().uri("/items").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 401);
+    let id = resp
+        .headers()
+        .get("X-Request-Id")
+        .unwrap()
+        .to_str()
+        .unwrap();
+    let id = id
+        .parse::<Uuid>()
+        .expect("generated ids are UUIDs")
+        .to_string();
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["request_id"], id.as_str());
+
+    // ==========================================
+    // ✅ 2. Клиентийн id-г хадгална, хэвийн бус бол солино
+    // ==========================================
+    let req = test::TestRequest::get()
+        .uri("/no-such-route")
+        .insert_header(("X-Request-Id", "edge-7f3a.42"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.headers().get("X-Request-Id").unwrap(), "edge-7f3a.42");
+
+    for bad in ["has spaces", &"x".repeat(129)] {
+        let req = test::TestRequest::get()
+            .uri("/no-such-route")
+            .insert_header(("X-Request-Id", bad))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        let id = resp
+            .headers()
+            .get("X-Request-Id")
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert!(id.parse::<Uuid>().is_ok(), "{bad:?} should be replaced");
+    }
+}
+
+#[actix_web::test]
+async fn test_secrets_are_redacted() {
+    // ==========================================
+    // ✅ 3. Нууц үг, урилгын код Debug-д гарахгүй
+    // ==========================================
+    let login: LoginInput =
+        serde_json::from_value(json!({"reg_no": "AB-1", "password": "hunter2-secret"})).unwrap();
+    let shown = format!("{login:?}");
+    assert!(shown.contains("AB-1"));
+    assert!(!shown.contains("hunter2-secret"));
+    assert_eq!(login.password.expose(), "hunter2-secret");
+
+    let register: RegisterInput = serde_json::from_value(json!({
+        "reg_no": "AB-2",
+        "first_name": "Anu",
+        "last_name": "Bold",
+        "password": "hunter2-secret",
+        "invitation_code": "inv-code-123"
+    }))
+    .unwrap();
+    let shown = format!("{register:?}");
+    assert!(!shown.contains("hunter2-secret") && !shown.contains("inv-code-123"));
+}
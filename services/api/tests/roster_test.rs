@@ -0,0 +1,240 @@
+use actix_web::test;
+use api::create_app;
+use chrono::{Duration, Utc};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+mod common;
+
+#[actix_web::test]
+async fn test_shift_roster_conflicts_and_on_call() {
+    let state = common::test_state().await;
+    let db = state.db.clone();
+    let app = test::init_service(create_app(state)).await;
+    let org_id = 1000 + (Uuid::new_v4().as_u128() % 100_000) as i32;
+
+    let mut bearers = Vec::new();
+    let mut ids = Vec::new();
+    for (prefix, roll) in [("DOC", 3), ("ADM", 1)] {
+        let body = common::sign_up(
+            &app,
+            &db,
+            json!({
+                "reg_no": format!("{prefix}-{}", Uuid::new_v4()),
+                "first_name": "Nomin",
+                "last_name": "Erdene",
+                "org_id": org_id,
+                "doctor_roll": roll,
+                "password": "supersecret"
+            }),
+        )
+        .await;
+        ids.push(body["doctor"]["id"].as_str().unwrap().to_string());
+        bearers.push((
+            "Authorization",
+            format!("Bearer {}", body["tokens"]["access"].as_str().unwrap()),
+        ));
+    }
+    let (doctor_id, doctor_bearer, admin_bearer) = (&ids[0], &bearers[0], &bearers[1]);
+    let now = Utc::now();
+
+    // ==========================================
+    // ✅ 1. On-call shift covering "now"
+    // ==========================================
+    let req = test::TestRequest::post()
+        .uri("/shifts")
+        .insert_header(admin_bearer.clone())
+        .set_json(json!({
+            "doctor_id": doctor_id,
+            "department": "Cardiology",
+            "starts_at": now - Duration::hours(1),
+            "ends_at": now + Duration::hours(5),
+            "on_call": true
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/on-call?org_id={org_id}&department=cardiology"))
+        .insert_header(doctor_bearer.clone())
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["on_call"].as_array().unwrap().len(), 1);
+    assert_eq!(body["on_call"][0]["doctor_id"], doctor_id.as_str());
+
+    // ==========================================
+    // ✅ 2. Overlap and rest-period conflicts
+    // ==========================================
+    for (start, end, reason) in [
+        (
+            now + Duration::hours(4),
+            now + Duration::hours(8),
+            "overlap",
+        ),
+        (
+            now + Duration::hours(7),
+            now + Duration::hours(12),
+            "rest_period",
+        ),
+    ] {
+        let req = test::TestRequest::post()
+            .uri("/shifts")
+            .insert_header(admin_bearer.clone())
+            .set_json(json!({
+                "doctor_id": doctor_id,
+                "department": "Cardiology",
+                "starts_at": start,
+                "ends_at": end
+            }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 409);
+        let body: Value = test::read_body_json(resp).await;
+        assert_eq!(body["conflicts"][0]["reason"], reason);
+    }
+
+    // ==========================================
+    // ✅ 3. Shift from a definition, well after the rest period
+    // ==========================================
+    let req = test::TestRequest::post()
+        .uri("/shift-definitions")
+        .insert_header(admin_bearer.clone())
+        .set_json(json!({
+            "org_id": org_id,
+            "name": "Night",
+            "start_time": "20:00:00",
+            "duration_minutes": 720,
+            "on_call": true
+        }))
+        .to_request();
+    let def: Value = test::call_and_read_body_json(&app, req).await;
+
+    // the zone is checked up front, not when a shift is first expanded
+    let req = test::TestRequest::post()
+        .uri("/shift-definitions")
+        .insert_header(admin_bearer.clone())
+        .set_json(json!({
+            "org_id": org_id,
+            "name": "Night",
+            "start_time": "20:00:00",
+            "duration_minutes": 720,
+            "timezone": "Mars/Olympus"
+        }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 422);
+
+    let req = test::TestRequest::post()
+        .uri("/shifts")
+        .insert_header(admin_bearer.clone())
+        .set_json(json!({
+            "doctor_id": doctor_id,
+            "department": "Cardiology",
+            "shift_definition_id": def["id"],
+            "date": (now + Duration::days(3)).date_naive()
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
+    let shift: Value = test::read_body_json(resp).await;
+    assert_eq!(shift["on_call"], true);
+    // 20:00 Ulaanbaatar == 12:00 UTC
+    assert!(shift["starts_at"].as_str().unwrap().contains("T12:00:00"));
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/shifts?doctor_id={doctor_id}"))
+        .insert_header(doctor_bearer.clone())
+        .to_request();
+    let rows: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(rows.as_array().unwrap().len(), 2);
+
+    // assignments are admin-only
+    let req = test::TestRequest::post()
+        .uri("/shifts")
+        .insert_header(doctor_bearer.clone())
+        .set_json(json!({
+            "doctor_id": doctor_id,
+            "department": "Cardiology",
+            "starts_at": now + Duration::days(9),
+            "ends_at": now + Duration::days(9) + Duration::hours(8)
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 403);
+
+    // ==========================================
+    // ✅ 4. Another org's admin sees only the on-call list
+    // ==========================================
+    let body = common::sign_up(
+        &app,
+        &db,
+        json!({"reg_no": format!("ADM-{}", Uuid::new_v4()), "org_id": org_id + 1, "doctor_roll": 1}),
+    )
+    .await;
+    let other_bearer = (
+        "Authorization",
+        format!("Bearer {}", body["tokens"]["access"].as_str().unwrap()),
+    );
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/shifts?doctor_id={doctor_id}"))
+        .insert_header(other_bearer.clone())
+        .to_request();
+    let rows: Value = test::call_and_read_body_json(&app, req).await;
+    assert!(rows.as_array().unwrap().is_empty());
+
+    let req = test::TestRequest::get()
+        .uri("/shift-definitions")
+        .insert_header(other_bearer.clone())
+        .to_request();
+    let rows: Value = test::call_and_read_body_json(&app, req).await;
+    assert!(
+        rows.as_array()
+            .unwrap()
+            .iter()
+            .all(|d| d["id"] != def["id"])
+    );
+
+    let req = test::TestRequest::post()
+        .uri("/shift-definitions")
+        .insert_header(other_bearer.clone())
+        .set_json(json!({
+            "org_id": org_id,
+            "name": "Day",
+            "start_time": "08:00:00",
+            "duration_minutes": 480
+        }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 403);
+
+    let req = test::TestRequest::post()
+        .uri("/shifts")
+        .insert_header(other_bearer.clone())
+        .set_json(json!({
+            "doctor_id": doctor_id,
+            "department": "Cardiology",
+            "starts_at": now + Duration::days(20),
+            "ends_at": now + Duration::days(20) + Duration::hours(8)
+        }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 404);
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/shifts/{}", shift["id"].as_str().unwrap()))
+        .insert_header(other_bearer.clone())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 404);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/on-call?org_id={org_id}&department=cardiology"))
+        .insert_header(other_bearer)
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["on_call"].as_array().unwrap().len(), 1);
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/shifts/{}", shift["id"].as_str().unwrap()))
+        .insert_header(admin_bearer.clone())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 200);
+}
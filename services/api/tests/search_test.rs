@@ -0,0 +1,129 @@
+use actix_web::test;
+use api::create_app;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+mod common;
+
+#[actix_web::test]
+async fn test_search_mongolian_ranked_highlighted_and_org_scoped() {
+    let state = common::test_state().await;
+    let db = state.db.clone();
+    let app = test::init_service(create_app(state)).await;
+    let org_id = 1000 + (Uuid::new_v4().as_u128() % 100_000) as i32;
+
+    let mut bearers = Vec::new();
+    for org in [org_id, org_id + 1] {
+        let body = common::sign_up(
+            &app,
+            &db,
+            json!({
+                "reg_no": format!("FTS-{}", Uuid::new_v4()),
+                "first_name": "Solongo",
+                "last_name": "Ganbold",
+                "org_id": org,
+                "password": "supersecret"
+            }),
+        )
+        .await;
+        bearers.push((
+            "Authorization",
+            format!("Bearer {}", body["tokens"]["access"].as_str().unwrap()),
+        ));
+    }
+    let (mine, theirs) = (&bearers[0], &bearers[1]);
+    // a per-run word, so rows left by earlier runs don't match
+    let marker = format!("тэмдэг{}", Uuid::new_v4().simple());
+
+    let create = |bearer: &(&'static str, String), title: String, description: String| {
+        test::TestRequest::post()
+            .uri("/items")
+            .insert_header(bearer.clone())
+            .set_json(json!({"title": title, "description": description}))
+            .to_request()
+    };
+    for req in [
+        create(
+            mine,
+            format!("Зүрхний хэмнэл {marker}"),
+            "Даралт <140/90> & цээжээр өвдөж ирсэн".into(),
+        ),
+        create(
+            mine,
+            "Дараагийн үзлэг".into(),
+            format!("Эмчилгээний дараа {marker} дахин шалгах"),
+        ),
+        create(theirs, format!("Other org {marker}"), String::new()),
+    ] {
+        assert_eq!(test::call_service(&app, req).await.status(), 201);
+    }
+    let search = |bearer: &(&'static str, String), q: &str| {
+        test::TestRequest::get()
+            .uri(&format!("/search?q={}", urlencode(q)))
+            .insert_header(bearer.clone())
+            .to_request()
+    };
+
+    // ==========================================
+    // ✅ 1. Кирилл, том жижиг үсэг ялгахгүй, title жинтэй
+    // ==========================================
+    let res: Value =
+        test::call_and_read_body_json(&app, search(mine, &marker.to_uppercase())).await;
+    let hits = res["items"].as_array().unwrap();
+    assert_eq!(hits.len(), 2);
+    assert!(hits[0]["title"].as_str().unwrap().starts_with("Зүрхний"));
+    assert_eq!(hits[0]["kind"], "item");
+    assert!(hits[0]["snippet"].as_str().unwrap().contains("<mark>"));
+    assert!(hits[0]["rank"].as_f64().unwrap() > hits[1]["rank"].as_f64().unwrap());
+
+    // ==========================================
+    // ✅ 2. Snippet HTML escape хийгдсэн
+    // ==========================================
+    let res: Value =
+        test::call_and_read_body_json(&app, search(mine, &format!("{marker} цээжээр"))).await;
+    let snippet = res["items"][0]["snippet"].as_str().unwrap();
+    assert!(snippet.contains("&lt;140/90&gt; &amp;"));
+    assert!(snippet.contains("<mark>цээжээр</mark>"));
+
+    // ==========================================
+    // ✅ 3. Бусад байгууллагын мөр харагдахгүй
+    // ==========================================
+    let res: Value = test::call_and_read_body_json(&app, search(theirs, &marker)).await;
+    let hits = res["items"].as_array().unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0]["title"], format!("Other org {marker}"));
+
+    let resp = test::call_service(&app, search(mine, "  ")).await;
+    assert_eq!(resp.status(), 400);
+
+    // ==========================================
+    // ✅ 4. Cursor-оор хуудаслана, limit хязгаартай
+    // ==========================================
+    let page = |bearer: &(&'static str, String), query: String| {
+        test::TestRequest::get()
+            .uri(&format!("/search?q={}&{query}", urlencode(&marker)))
+            .insert_header(bearer.clone())
+            .to_request()
+    };
+    let first: Value = test::call_and_read_body_json(&app, page(mine, "limit=1".into())).await;
+    let cursor = first["next_cursor"].as_str().unwrap();
+    let second: Value =
+        test::call_and_read_body_json(&app, page(mine, format!("limit=1&cursor={cursor}"))).await;
+    assert_eq!(second["items"].as_array().unwrap().len(), 1);
+    assert_ne!(second["items"][0]["id"], first["items"][0]["id"]);
+    assert!(second["next_cursor"].is_null());
+
+    let resp = test::call_service(&app, page(mine, "limit=101".into())).await;
+    assert_eq!(resp.status(), 200);
+    let resp = test::call_service(&app, page(mine, "limit=1000".into())).await;
+    assert_eq!(resp.status(), 400);
+}
+
+fn urlencode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' => (b as char).to_string(),
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
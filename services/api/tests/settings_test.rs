@@ -0,0 +1,209 @@
+use actix_web::{test, web};
+use api::create_app;
+use api::state::{Settings, SettingsError};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::env;
+use uuid::Uuid;
+
+mod common;
+
+const KEY_1: &str = "ldi7tcfwlNdiesFu4xQ8JosNUBsBgR4BLhRO1+JZNCs=";
+const KEY_2: &str = "5gReOtkWDuzznGgy6FipO2ppY89UnMgV4mDbnVme2o8=";
+
+/// Stand-in process environment with the required settings plus `extra`.
+fn environment(extra: &[(&str, &str)]) -> config::Environment {
+    let mut vars: HashMap<String, String> = [
+        ("DATABASE_URL", "postgres://app:db-password-123@db/hospital"),
+        ("JWT_SECRET", "jwt-secret-value-456"),
+        ("MASTER_KEY_VERSION", "2"),
+        ("BLIND_INDEX_KEY", KEY_2),
+    ]
+    .into_iter()
+    .map(|(k, v)| (k.to_owned(), v.to_owned()))
+    .collect();
+    vars.insert("MASTER_KEYS".into(), format!("1:{KEY_1},2:{KEY_2}"));
+    for (k, v) in extra {
+        vars.insert((*k).to_owned(), (*v).to_owned());
+    }
+    config::Environment::default().source(Some(vars))
+}
+
+fn config_file(contents: &str) -> String {
+    let path = env::temp_dir().join(format!("settings-{}.toml", Uuid::new_v4()));
+    std::fs::write(&path, contents).unwrap();
+    path.to_str().unwrap().to_owned()
+}
+
+#[actix_web::test]
+async fn test_settings_layer_file_under_environment() {
+    // ==========================================
+    // ✅ 1. TOML файл, түүнийг env дарна
+    // ==========================================
+    let file = config_file(
+        r#"
+        bind_address = "127.0.0.1:9000"
+        db_max_connections = 4
+        rate_limit_burst = 20
+        cors_allowed_origins = ["https://file.example"]
+        "#,
+    );
+    let s = Settings::load_with(
+        Some(&file),
+        environment(&[
+            ("DB_MAX_CONNECTIONS", "8"),
+            (
+                "CORS_ALLOWED_ORIGINS",
+                "https://app.example,http://localhost:5173",
+            ),
+        ]),
+    )
+    .unwrap();
+    assert_eq!(s.bind_address.as_deref(), Some("127.0.0.1:9000"));
+    assert_eq!(s.db_max_connections, Some(8));
+    assert_eq!(s.rate_limit_burst, Some(20));
+    assert_eq!(
+        s.cors_allowed_origins.as_deref().unwrap(),
+        ["https://app.example", "http://localhost:5173"]
+    );
+    // commas in other values are left alone
+    assert_eq!(s.key_ring().unwrap().current_version(), 2);
+
+    // an explicitly named file has to exist
+    let missing = Settings::load_with(Some("/nonexistent/settings.toml"), environment(&[]));
+    assert!(matches!(missing, Err(SettingsError::Load(_))));
+
+    // ==========================================
+    // ✅ 2. Бүх алдааг нэг дор мэдээлнэ
+    // ==========================================
+    let err = Settings::load_with(
+        None,
+        environment(&[
+            ("BIND_ADDRESS", "localhost"),
+            ("DB_MAX_CONNECTIONS", "0"),
+            ("TLS_CERT_PATH", "/etc/tls/cert.pem"),
+            ("CORS_ALLOWED_ORIGINS", "app.example/"),
+            ("STORAGE_BACKEND", "s3"),
+            ("MASTER_KEY_VERSION", "3"),
+            ("OPEN_REGISTRATION", "true"),
+            ("METRICS_TOKEN", "short"),
+        ]),
+    )
+    .unwrap_err();
+    let SettingsError::Invalid(problems) = &err else {
+        panic!("expected validation errors, got {err}");
+    };
+    let text = err.to_string();
+    for needle in [
+        "bind_address",
+        "db_max_connections",
+        "tls_key_path",
+        "app.example/",
+        "s3_bucket",
+        "MASTER_KEY_VERSION",
+        "open_registration_org_id",
+        "metrics_token",
+    ] {
+        assert!(text.contains(needle), "{needle} not reported in {text}");
+    }
+    assert!(problems.len() >= 8);
+
+    // host names resolve; "*" has to stand alone
+    let s = Settings::load_with(
+        None,
+        environment(&[
+            ("BIND_ADDRESS", "localhost:8080"),
+            ("CORS_ALLOWED_ORIGINS", "*"),
+        ]),
+    )
+    .unwrap();
+    assert_eq!(s.bind_address.as_deref(), Some("localhost:8080"));
+    let err = Settings::load_with(
+        None,
+        environment(&[("CORS_ALLOWED_ORIGINS", "*,https://app.example")]),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("cannot be combined"), "{err}");
+}
+
+#[actix_web::test]
+async fn test_admin_config_dump_is_redacted() {
+    let state = common::test_state().await;
+    let db = state.db.clone();
+    let admin_roll =
+        sqlx::query_scalar::<_, i32>("SELECT roll_id FROM doctor_rolls WHERE roll_name = 'admin'")
+            .fetch_one(&state.db.0)
+            .await
+            .unwrap();
+    let settings = Settings::load_with(
+        None,
+        environment(&[
+            ("DB_MAX_CONNECTIONS", "8"),
+            ("S3_SECRET_ACCESS_KEY", "s3-secret-value-789"),
+            ("METRICS_TOKEN", "metrics-token-value-000"),
+        ]),
+    )
+    .unwrap();
+    let app = test::init_service(create_app(state).app_data(web::Data::new(settings))).await;
+    let org_id = 1000 + (Uuid::new_v4().as_u128() % 100_000) as i32;
+    let mut bearers = Vec::new();
+    for roll in [Some(admin_roll), None] {
+        let body = common::sign_up(
+            &app,
+            &db,
+            json!({
+                "reg_no": format!("CF-{}", Uuid::new_v4()),
+                "first_name": "Anu",
+                "last_name": "Bold",
+                "org_id": org_id,
+                "doctor_roll": roll,
+                "password": "supersecret"
+            }),
+        )
+        .await;
+        bearers.push((
+            "Authorization",
+            format!("Bearer {}", body["tokens"]["access"].as_str().unwrap()),
+        ));
+    }
+
+    // ==========================================
+    // ✅ 3. Админ л харна, нууц утгууд далдлагдсан
+    // ==========================================
+    let req = test::TestRequest::get()
+        .uri("/admin/config")
+        .insert_header(bearers[0].clone())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["db_max_connections"], 8);
+    assert!(body["bind_address"].is_null());
+    for secret in [
+        "database_url",
+        "jwt_secret",
+        "master_keys",
+        "blind_index_key",
+        "s3_secret_access_key",
+        "metrics_token",
+    ] {
+        assert_eq!(body[secret], "[REDACTED]", "{secret}");
+    }
+    let text = body.to_string();
+    for value in [
+        "db-password-123",
+        "jwt-secret-value-456",
+        "s3-secret-value-789",
+        "metrics-token-value-000",
+        KEY_1,
+        KEY_2,
+    ] {
+        assert!(!text.contains(value), "{value} leaked");
+    }
+
+    let req = test::TestRequest::get()
+        .uri("/admin/config")
+        .insert_header(bearers[1].clone())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 403);
+}
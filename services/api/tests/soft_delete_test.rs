@@ -0,0 +1,257 @@
+use actix_web::test;
+use api::create_app;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+mod common;
+
+async fn admin_roll(db: &db::Db) -> i32 {
+    sqlx::query_scalar::<_, i32>("SELECT roll_id FROM doctor_rolls WHERE roll_name = 'admin'")
+        .fetch_one(&db.0)
+        .await
+        .unwrap()
+}
+
+#[actix_web::test]
+async fn test_deleted_items_are_hidden_and_admin_restores_them() {
+    let state = common::test_state().await;
+    let db = state.db.clone();
+    let app = test::init_service(create_app(state)).await;
+    let org_id = 1000 + (Uuid::new_v4().as_u128() % 100_000) as i32;
+    let mut doctors = Vec::new();
+    for doctor_roll in [None, Some(admin_roll(&db).await)] {
+        let reg_no = format!("SD-{}", Uuid::new_v4());
+        let body = common::sign_up(
+            &app,
+            &db,
+            json!({
+                "reg_no": reg_no,
+                "first_name": "Saraa",
+                "last_name": "Dorj",
+                "org_id": org_id,
+                "doctor_roll": doctor_roll,
+                "password": "supersecret"
+            }),
+        )
+        .await;
+        doctors.push((
+            body["doctor"]["id"].as_str().unwrap().to_string(),
+            reg_no,
+            (
+                "Authorization",
+                format!("Bearer {}", body["tokens"]["access"].as_str().unwrap()),
+            ),
+        ));
+    }
+    let [(_, _, owner), (_, _, admin)] = &doctors[..] else {
+        unreachable!()
+    };
+
+    let req = test::TestRequest::post()
+        .uri("/items")
+        .insert_header(owner.clone())
+        .set_json(json!({"title": "Discharge summary", "description": null}))
+        .to_request();
+    let item: Value = test::call_and_read_body_json(&app, req).await;
+    let id = item["id"].as_str().unwrap().to_string();
+    let uri = format!("/items/{id}");
+    let listed = |page: &Value| {
+        page["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|r| r["id"] == id.as_str())
+    };
+
+    // ==========================================
+    // ✅ 1. Устгасан item харагдахгүй, мөр нь хадгалагдана
+    // ==========================================
+    let req = test::TestRequest::delete()
+        .uri(&uri)
+        .insert_header(owner.clone())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 200);
+    let req = test::TestRequest::get()
+        .uri(&uri)
+        .insert_header(owner.clone())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 404);
+    let req = test::TestRequest::get()
+        .uri("/items")
+        .insert_header(owner.clone())
+        .to_request();
+    let page: Value = test::call_and_read_body_json(&app, req).await;
+    assert!(!listed(&page));
+
+    let item_id: Uuid = id.parse().unwrap();
+    let deleted_by = sqlx::query_scalar::<_, Option<Uuid>>(
+        "SELECT deleted_by FROM items WHERE id = $1 AND deleted_at IS NOT NULL",
+    )
+    .bind(item_id)
+    .fetch_one(&db.0)
+    .await
+    .unwrap();
+    assert!(deleted_by.is_some());
+
+    // ==========================================
+    // ✅ 2. Хогийн сав, сэргээх нь зөвхөн админд
+    // ==========================================
+    let req = test::TestRequest::get()
+        .uri("/items?deleted=true")
+        .insert_header(owner.clone())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 403);
+    let req = test::TestRequest::post()
+        .uri(&format!("{uri}/restore"))
+        .insert_header(owner.clone())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 403);
+
+    let req = test::TestRequest::get()
+        .uri("/items?deleted=true")
+        .insert_header(admin.clone())
+        .to_request();
+    let page: Value = test::call_and_read_body_json(&app, req).await;
+    assert!(listed(&page));
+
+    let req = test::TestRequest::post()
+        .uri(&format!("{uri}/restore"))
+        .insert_header(admin.clone())
+        .to_request();
+    let row: Value = test::call_and_read_body_json(&app, req).await;
+    assert!(row["deleted_at"].is_null());
+    let req = test::TestRequest::get()
+        .uri(&uri)
+        .insert_header(owner.clone())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 200);
+
+    // restoring a live item is a 404
+    let req = test::TestRequest::post()
+        .uri(&format!("{uri}/restore"))
+        .insert_header(admin.clone())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 404);
+
+    // ==========================================
+    // ✅ 3. SQL-ээр шууд устгах боломжгүй
+    // ==========================================
+    let res = sqlx::query("DELETE FROM items WHERE id = $1")
+        .bind(item_id)
+        .execute(&db.0)
+        .await;
+    assert!(res.is_err());
+}
+
+#[actix_web::test]
+async fn test_doctor_removal_deactivates_and_keeps_items() {
+    let state = common::test_state().await;
+    let db = state.db.clone();
+    let app = test::init_service(create_app(state)).await;
+    let org_id = 1000 + (Uuid::new_v4().as_u128() % 100_000) as i32;
+    let mut doctors = Vec::new();
+    for doctor_roll in [None, Some(admin_roll(&db).await)] {
+        let reg_no = format!("SD-{}", Uuid::new_v4());
+        let body = common::sign_up(
+            &app,
+            &db,
+            json!({
+                "reg_no": reg_no,
+                "first_name": "Saraa",
+                "last_name": "Dorj",
+                "org_id": org_id,
+                "doctor_roll": doctor_roll,
+                "password": "supersecret"
+            }),
+        )
+        .await;
+        doctors.push((
+            body["doctor"]["id"].as_str().unwrap().to_string(),
+            reg_no,
+            (
+                "Authorization",
+                format!("Bearer {}", body["tokens"]["access"].as_str().unwrap()),
+            ),
+        ));
+    }
+    let [(doctor_id, reg_no, doctor), (admin_id, _, admin)] = &doctors[..] else {
+        unreachable!()
+    };
+
+    let req = test::TestRequest::post()
+        .uri("/items")
+        .insert_header(doctor.clone())
+        .set_json(json!({"title": "Ward round notes", "description": null}))
+        .to_request();
+    let item: Value = test::call_and_read_body_json(&app, req).await;
+
+    // ==========================================
+    // ✅ 1. Эмч өөрийгөө болон бусдыг идэвхгүй болгож чадахгүй
+    // ==========================================
+    let req = test::TestRequest::delete()
+        .uri(&format!("/doctors/{admin_id}"))
+        .insert_header(doctor.clone())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 403);
+    let req = test::TestRequest::delete()
+        .uri(&format!("/doctors/{admin_id}"))
+        .insert_header(admin.clone())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 400);
+
+    // ==========================================
+    // ✅ 2. Админ идэвхгүй болгоно → нэвтрэх боломжгүй, item үлдэнэ
+    // ==========================================
+    let req = test::TestRequest::delete()
+        .uri(&format!("/doctors/{doctor_id}"))
+        .insert_header(admin.clone())
+        .to_request();
+    let row: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(row["is_active"], false);
+    assert_eq!(row["deactivated_by"], admin_id.as_str());
+
+    let req = test::TestRequest::post()
+        .uri("/auth/login")
+        .set_json(json!({"reg_no": reg_no, "password": "supersecret"}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 403);
+
+    let revoked = sqlx::query_scalar::<_, bool>(
+        "SELECT bool_and(revoked) FROM refresh_tokens WHERE doctor_id = $1",
+    )
+    .bind(doctor_id.parse::<Uuid>().unwrap())
+    .fetch_one(&db.0)
+    .await
+    .unwrap();
+    assert!(revoked);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/items/{}", item["id"].as_str().unwrap()))
+        .insert_header(admin.clone())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 200);
+
+    // PATCH-ээр идэвхгүй болгохгүй: DELETE-ээр л (token цуцлагдана)
+    let req = test::TestRequest::patch()
+        .uri(&format!("/doctors/{admin_id}"))
+        .insert_header(admin.clone())
+        .set_json(json!({"is_active": false}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 422);
+
+    // ==========================================
+    // ✅ 3. Дахин идэвхжүүлэх
+    // ==========================================
+    let req = test::TestRequest::patch()
+        .uri(&format!("/doctors/{doctor_id}"))
+        .insert_header(admin.clone())
+        .set_json(json!({"is_active": true}))
+        .to_request();
+    let row: Value = test::call_and_read_body_json(&app, req).await;
+    assert!(row["deactivated_at"].is_null());
+    let req = test::TestRequest::post()
+        .uri("/auth/login")
+        .set_json(json!({"reg_no": reg_no, "password": "supersecret"}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 200);
+}
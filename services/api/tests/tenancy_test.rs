@@ -0,0 +1,295 @@
+use actix_web::test;
+use api::create_app;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+mod common;
+
+#[actix_web::test]
+async fn test_items_are_scoped_to_the_callers_org() {
+    let state = common::test_state().await;
+    let db = state.db.clone();
+    let app = test::init_service(create_app(state)).await;
+    let org_a = 1000 + (Uuid::new_v4().as_u128() % 100_000) as i32;
+
+    let mut bearers = Vec::new();
+    for (prefix, org_id) in [("A", org_a), ("B", org_a + 1)] {
+        let body = common::sign_up(
+            &app,
+            &db,
+            json!({
+                "reg_no": format!("{prefix}-{}", Uuid::new_v4()),
+                "first_name": "Nomin",
+                "last_name": "Erdene",
+                "org_id": org_id,
+                "password": "supersecret"
+            }),
+        )
+        .await;
+        bearers.push((
+            "Authorization",
+            format!("Bearer {}", body["tokens"]["access"].as_str().unwrap()),
+        ));
+    }
+    let (a, b) = (&bearers[0], &bearers[1]);
+
+    // ==========================================
+    // ✅ 1. Нэвтрээгүй хэрэглэгч жагсаалт авахгүй
+    // ==========================================
+    let req = test::TestRequest::get().uri("/items").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 401);
+
+    // ==========================================
+    // ✅ 2. Байгууллага A item үүсгэнэ
+    // ==========================================
+    let req = test::TestRequest::post()
+        .uri("/items")
+        .insert_header(a.clone())
+        .set_json(json!({"title": "Ward 3 chart", "description": null}))
+        .to_request();
+    let item: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(item["org_id"], org_a);
+    let id = item["id"].as_str().unwrap();
+
+    let req = test::TestRequest::get()
+        .uri("/items")
+        .insert_header(a.clone())
+        .to_request();
+    let page: Value = test::call_and_read_body_json(&app, req).await;
+    assert!(
+        page["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|r| r["id"] == id)
+    );
+
+    // ==========================================
+    // ✅ 3. Байгууллага B харж, өөрчилж чадахгүй
+    // ==========================================
+    let req = test::TestRequest::get()
+        .uri("/items")
+        .insert_header(b.clone())
+        .to_request();
+    let page: Value = test::call_and_read_body_json(&app, req).await;
+    let rows = page["items"].as_array().unwrap();
+    assert!(rows.iter().all(|r| r["org_id"] == org_a + 1));
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/items/{id}"))
+        .insert_header(b.clone())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_only_owner_department_head_or_admin_modify_items() {
+    let state = common::test_state().await;
+    let db = state.db.clone();
+    let app = test::init_service(create_app(state)).await;
+    let org_id = 1000 + (Uuid::new_v4().as_u128() % 100_000) as i32;
+    let roll = |name: &'static str| {
+        let db = db.clone();
+        async move {
+            sqlx::query_scalar::<_, i32>("SELECT roll_id FROM doctor_rolls WHERE roll_name = $1")
+                .bind(name)
+                .fetch_one(&db.0)
+                .await
+                .unwrap()
+        }
+    };
+    let (head_roll, admin_roll) = (roll("department_head").await, roll("admin").await);
+
+    // owner and head in cardiology, a colleague in surgery, an admin, and
+    // another org's head who must not even see the item
+    let mut bearers = Vec::new();
+    for (org, department, doctor_roll) in [
+        (org_id, "cardiology", None),
+        (org_id, "cardiology", Some(head_roll)),
+        (org_id, "surgery", None),
+        (org_id, "surgery", Some(admin_roll)),
+        (org_id + 1, "cardiology", Some(head_roll)),
+    ] {
+        let reg_no = format!("ACL-{}", Uuid::new_v4());
+        let body = common::sign_up(
+            &app,
+            &db,
+            json!({
+                "reg_no": reg_no,
+                "first_name": "Ganaa",
+                "last_name": "Bold",
+                "org_id": org,
+                "doctor_roll": doctor_roll,
+                "password": "supersecret"
+            }),
+        )
+        .await;
+        sqlx::query("UPDATE doctor_user SET department = $2 WHERE reg_no = $1")
+            .bind(&reg_no)
+            .bind(department)
+            .execute(&db.0)
+            .await
+            .unwrap();
+        bearers.push((
+            "Authorization",
+            format!("Bearer {}", body["tokens"]["access"].as_str().unwrap()),
+        ));
+    }
+    let [owner, head, colleague, admin, foreign_head] = &bearers[..] else {
+        unreachable!()
+    };
+
+    let req = test::TestRequest::post()
+        .uri("/items")
+        .insert_header(owner.clone())
+        .set_json(json!({"title": "Echo report", "description": null}))
+        .to_request();
+    let item: Value = test::call_and_read_body_json(&app, req).await;
+    let uri = format!("/items/{}", item["id"].as_str().unwrap());
+    let put = |bearer: &(&'static str, String), title: &str, version: i64| {
+        test::TestRequest::put()
+            .uri(&uri)
+            .insert_header(bearer.clone())
+            .insert_header(("If-Match", format!("\"{version}\"")))
+            .set_json(json!({"title": title, "description": null}))
+            .to_request()
+    };
+
+    // ==========================================
+    // ✅ 1. Өөр тасгийн эмч → 403, өөр байгууллага → 404
+    // ==========================================
+    let resp = test::call_service(&app, put(colleague, "nope", 1)).await;
+    assert_eq!(resp.status(), 403);
+    let resp = test::call_service(&app, put(foreign_head, "nope", 1)).await;
+    assert_eq!(resp.status(), 404);
+    let req = test::TestRequest::delete()
+        .uri(&uri)
+        .insert_header(colleague.clone())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 403);
+
+    // ==========================================
+    // ✅ 2. Эзэмшигч, тасгийн эрхлэгч, админ → 200
+    // ==========================================
+    for (bearer, title, version) in [(owner, "v2", 1), (head, "v3", 2), (admin, "v4", 3)] {
+        let resp = test::call_service(&app, put(bearer, title, version)).await;
+        assert_eq!(resp.status(), 200);
+        let row: Value = test::read_body_json(resp).await;
+        assert_eq!(row["title"], title);
+    }
+
+    let req = test::TestRequest::delete()
+        .uri(&uri)
+        .insert_header(head.clone())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 200);
+    let req = test::TestRequest::get()
+        .uri(&uri)
+        .insert_header(owner.clone())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 404);
+}
+
+/// Row-level security is the second line of defence; it only binds roles
+/// without BYPASSRLS, so the check switches to such a role.
+#[actix_web::test]
+async fn test_patient_tables_are_isolated_by_row_level_security() {
+    let state = common::test_state().await;
+    let db = state.db.clone();
+    let app = test::init_service(create_app(state)).await;
+    let org_a = 1000 + (Uuid::new_v4().as_u128() % 100_000) as i32;
+
+    let mut patients = Vec::new();
+    for org_id in [org_a, org_a + 1] {
+        let body = common::sign_up(
+            &app,
+            &db,
+            json!({"reg_no": format!("RLS-{}", Uuid::new_v4()), "org_id": org_id}),
+        )
+        .await;
+        let bearer = (
+            "Authorization",
+            format!("Bearer {}", body["tokens"]["access"].as_str().unwrap()),
+        );
+        let req = test::TestRequest::post()
+            .uri("/patients")
+            .insert_header(bearer.clone())
+            .set_json(json!({"first_name": "Tuya", "last_name": "Gan"}))
+            .to_request();
+        let patient: Value = test::call_and_read_body_json(&app, req).await;
+        let id: Uuid = patient["id"].as_str().unwrap().parse().unwrap();
+        let req = test::TestRequest::post()
+            .uri(&format!("/patients/{id}/consents"))
+            .insert_header(bearer)
+            .set_json(json!({
+                "kind": "treatment",
+                "form_version": "2024-01",
+                "signed_by": "Tuya Gan",
+                "signature": "sig"
+            }))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 201);
+        patients.push(id);
+    }
+
+    sqlx::query(
+        r#"DO $$ BEGIN
+               CREATE ROLE rls_probe NOLOGIN NOBYPASSRLS;
+           EXCEPTION WHEN duplicate_object OR unique_violation THEN NULL;
+           END $$"#,
+    )
+    .execute(&db.0)
+    .await
+    .unwrap();
+    sqlx::query(
+        "GRANT SELECT ON patients, patient_consents, patient_documents, patient_orgs TO rls_probe",
+    )
+    .execute(&db.0)
+    .await
+    .unwrap();
+
+    let count = |table: &'static str, org: Option<i32>| {
+        let db = db.clone();
+        let ids = patients.clone();
+        async move {
+            let mut tx = db.0.begin().await.unwrap();
+            sqlx::query("SET LOCAL ROLE rls_probe")
+                .execute(&mut *tx)
+                .await
+                .unwrap();
+            if let Some(org) = org {
+                sqlx::query("SELECT set_config('app.org_id', $1, true)")
+                    .bind(org.to_string())
+                    .execute(&mut *tx)
+                    .await
+                    .unwrap();
+            }
+            let column = if table == "patients" {
+                "id"
+            } else {
+                "patient_id"
+            };
+            sqlx::query_scalar::<_, i64>(&format!(
+                "SELECT count(*) FROM {table} WHERE {column} = ANY($1)"
+            ))
+            .bind(&ids)
+            .fetch_one(&mut *tx)
+            .await
+            .unwrap()
+        }
+    };
+
+    // ==========================================
+    // ✅ 1. Зөвхөн өөрийн байгууллагын мөр харагдана
+    // ==========================================
+    assert_eq!(count("patients", Some(org_a)).await, 1);
+    assert_eq!(count("patient_consents", Some(org_a)).await, 1);
+    assert_eq!(count("patients", Some(org_a + 1)).await, 1);
+
+    // ==========================================
+    // ✅ 2. app.org_id тохируулаагүй бол юу ч харагдахгүй
+    // ==========================================
+    assert_eq!(count("patients", None).await, 0);
+    assert_eq!(count("patient_consents", None).await, 0);
+    assert_eq!(count("patient_documents", None).await, 0);
+}
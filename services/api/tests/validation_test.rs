@@ -0,0 +1,112 @@
+use actix_web::test;
+use api::create_app;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+mod common;
+
+#[actix_web::test]
+async fn test_invalid_bodies_return_field_errors() {
+    let app = test::init_service(create_app(common::test_state().await)).await;
+
+    // ==========================================
+    // ✅ 1. Бүртгэл: буруу reg_no, богино нууц үг → 422
+    // ==========================================
+    let req = test::TestRequest::post()
+        .uri("/auth/register")
+        .set_json(json!({
+            "reg_no": "-- drop",
+            "first_name": "  ",
+            "last_name": "Bat",
+            "password": "short"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 422);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["type"], "/problems/validation");
+    assert_eq!(body["status"], 422);
+    for field in ["reg_no", "first_name", "password"] {
+        assert!(body["fields"][field].is_array(), "missing {field}");
+    }
+    assert!(body["fields"]["last_name"].is_null());
+    assert_eq!(body["fields"]["password"][0]["code"], "length");
+    assert_eq!(body["fields"]["password"][0]["params"]["min"], 8);
+    assert!(
+        !body.to_string().contains("short"),
+        "rejected values must not be echoed"
+    );
+
+    // Cyrillic registry numbers are valid
+    let req = test::TestRequest::post()
+        .uri("/auth/register")
+        .set_json(json!({
+            "reg_no": format!("УБ-{}", Uuid::new_v4()),
+            "first_name": "Saraa",
+            "last_name": "Bat",
+            "password": "supersecret"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
+    let body: Value = test::read_body_json(resp).await;
+    let bearer = (
+        "Authorization",
+        format!("Bearer {}", body["tokens"]["access"].as_str().unwrap()),
+    );
+
+    // ==========================================
+    // ✅ 2. Item: хоосон гарчиг, хэт урт тайлбар
+    // ==========================================
+    let req = test::TestRequest::post()
+        .uri("/items")
+        .insert_header(bearer.clone())
+        .set_json(json!({"title": "   ", "description": "x".repeat(10_001)}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 422);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["fields"]["title"][0]["code"], "blank");
+    assert_eq!(body["fields"]["description"][0]["params"]["max"], 10_000);
+
+    let req = test::TestRequest::post()
+        .uri("/items")
+        .insert_header(bearer.clone())
+        .set_json(json!({"title": "Триаж", "description": "x".repeat(10_000)}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 201);
+
+    // ==========================================
+    // ✅ 3. Эвдэрсэн JSON нь 400 хэвээр, ижил хэлбэртэй
+    // ==========================================
+    let req = test::TestRequest::post()
+        .uri("/items")
+        .insert_header(bearer.clone())
+        .insert_header(("Content-Type", "application/json"))
+        .set_payload("{\"title\": ")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+    let body: Value = test::read_body_json(resp).await;
+    assert!(body["detail"].is_string());
+
+    // ==========================================
+    // ✅ 4. Профайл: зөвхөн илгээсэн талбарыг шалгана
+    // ==========================================
+    let req = test::TestRequest::patch()
+        .uri("/me")
+        .insert_header(bearer.clone())
+        .set_json(json!({"email": "not-an-email"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 422);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["fields"]["email"][0]["code"], "email");
+
+    let req = test::TestRequest::patch()
+        .uri("/me")
+        .insert_header(bearer.clone())
+        .set_json(json!({"email": null, "phone": "99001122"}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 200);
+}